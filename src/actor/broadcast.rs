@@ -4,9 +4,10 @@ use crate::actor::app::WindowId;
 use crate::layout_engine::VirtualWorkspaceId;
 use crate::sys::screen::SpaceId;
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, strum_macros::VariantNames)]
 #[serde(rename_all = "snake_case")]
 #[serde(tag = "type")]
+#[strum(serialize_all = "snake_case")]
 pub enum BroadcastEvent {
     WorkspaceChanged {
         space_id: SpaceId,
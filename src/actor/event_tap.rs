@@ -36,6 +36,9 @@ pub enum Request {
     ScreenParametersChanged(Vec<CGRect>, CoordinateConverter),
     SetEventProcessing(bool),
     SetFocusFollowsMouseEnabled(bool),
+    /// Whether any window is docked. Hovered windows are reported while one
+    /// is, even without focus-follows-mouse.
+    SetDockedWindows(bool),
     SetHotkeys(Vec<(Hotkey, WmCommand)>),
 }
 
@@ -60,6 +63,7 @@ struct State {
     event_processing_enabled: bool,
     focus_follows_mouse_enabled: bool,
     disable_hotkey_active: bool,
+    docked_windows: bool,
     pressed_keys: HashSet<KeyCode>,
     current_flags: CGEventFlags,
 }
@@ -75,6 +79,7 @@ impl Default for State {
             event_processing_enabled: false,
             focus_follows_mouse_enabled: true,
             disable_hotkey_active: false,
+            docked_windows: false,
             pressed_keys: HashSet::default(),
             current_flags: CGEventFlags::empty(),
         }
@@ -270,6 +275,9 @@ impl EventTap {
                 );
                 state.focus_follows_mouse_enabled = enabled;
             }
+            Request::SetDockedWindows(docked) => {
+                state.docked_windows = docked;
+            }
             Request::SetHotkeys(bindings) => {
                 let mut map = self.hotkeys.borrow_mut();
                 map.clear();
@@ -342,14 +350,17 @@ impl EventTap {
             CGEventType::RightMouseUp | CGEventType::LeftMouseUp => {
                 _ = self.events_tx.send(Event::MouseUp);
             }
-            CGEventType::MouseMoved
-                if self.config.settings.focus_follows_mouse
+            CGEventType::MouseMoved => {
+                let focus_follows_mouse = self.config.settings.focus_follows_mouse
                     && state.focus_follows_mouse_enabled
-                    && !state.disable_hotkey_active =>
-            {
-                let loc = CGEvent::location(Some(event));
-                if let Some(wsid) = state.track_mouse_move(loc) {
-                    _ = self.events_tx.send(Event::MouseMovedOverWindow(wsid));
+                    && !state.disable_hotkey_active;
+                if focus_follows_mouse || state.docked_windows {
+                    let loc = CGEvent::location(Some(event));
+                    if let Some(wsid) = state.track_mouse_move(loc) {
+                        _ = self
+                            .events_tx
+                            .send(Event::MouseMovedOverWindow { wsid, focus_follows_mouse });
+                    }
                 }
             }
             _ => (),
@@ -590,7 +601,10 @@ impl State {
         }
     }
 
-    fn track_mouse_move(&mut self, loc: CGPoint) -> Option<WindowServerId> {
+    /// Returns the window the mouse moved over, or `Some(None)` when it moved
+    /// off every window it could focus, such as onto the desktop or the Dock.
+    /// Returns `None` if nothing should be reported.
+    fn track_mouse_move(&mut self, loc: CGPoint) -> Option<Option<WindowServerId>> {
         let new_window = window_server::get_window_at_point(loc);
         if self.above_window == new_window {
             return None;
@@ -631,10 +645,10 @@ impl State {
         if !(0..NSPopUpMenuWindowLevel).contains(&new_window_level)
             && new_window_level != NSWindowLevel::MIN
         {
-            return None;
+            return Some(None);
         }
 
-        new_window
+        Some(new_window)
     }
 }
 
@@ -36,7 +36,7 @@ pub use replay::{Record, replay};
 use serde::{Deserialize, Serialize};
 use serde_json;
 use serde_with::serde_as;
-use tracing::{debug, instrument, trace, warn};
+use tracing::{debug, error, instrument, trace, warn};
 use transaction_manager::TransactionId;
 
 use super::event_tap;
@@ -48,7 +48,9 @@ use crate::actor::{self, menu_bar, stack_line};
 use crate::common::collections::{BTreeMap, HashMap, HashSet};
 use crate::common::config::Config;
 use crate::common::log::MetricsCommand;
-use crate::layout_engine::{self as layout, Direction, LayoutCommand, LayoutEngine, LayoutEvent};
+use crate::layout_engine::{
+    self as layout, Direction, LayoutCommand, LayoutEngine, LayoutEvent, LayoutTree,
+};
 use crate::model::VirtualWorkspaceId;
 use crate::model::tx_store::WindowTxStore;
 use crate::sys::event::MouseState;
@@ -67,7 +69,7 @@ use std::collections::VecDeque;
 use std::path::PathBuf;
 
 use crate::model::server::{
-    ApplicationData, DisplayData, LayoutStateData, WindowData, WorkspaceData,
+    ApplicationData, CommandEffect, DisplayData, LayoutStateData, WindowData, WorkspaceData,
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -172,9 +174,13 @@ pub enum Event {
     /// FIXME: This can be interleaved incorrectly with the MouseState in app
     /// actor events.
     MouseUp,
-    /// The mouse cursor moved over a new window. Only sent if focus-follows-
-    /// mouse is enabled.
-    MouseMovedOverWindow(WindowServerId),
+    /// The mouse cursor moved over a new window, or off every window (`None`).
+    /// Sent while focus-follows-mouse is enabled, which `focus_follows_mouse`
+    /// tells, and while any window is docked.
+    MouseMovedOverWindow {
+        wsid: Option<WindowServerId>,
+        focus_follows_mouse: bool,
+    },
     /// System woke from sleep; used to re-subscribe SLS notifications.
     SystemWoke,
 
@@ -237,8 +243,36 @@ pub enum Event {
         response: r#continue::Sender<Option<LayoutStateData>>,
     },
     #[serde(skip)]
+    QueryLayoutTree {
+        space_id: Option<SpaceId>,
+        workspace: Option<usize>,
+        #[serde(skip)]
+        response: r#continue::Sender<Option<LayoutTree>>,
+    },
+    #[serde(skip)]
     QueryMetrics(r#continue::Sender<serde_json::Value>),
 
+    /// Runs `commands` in order within one event, so the layout is only
+    /// updated once, after the last of them. Responds with the effect or error
+    /// of each command that ran; when `abort_on_error` is set the commands
+    /// after the first failure do not run.
+    #[serde(skip)]
+    CommandBatch {
+        commands: Vec<Command>,
+        abort_on_error: bool,
+        #[serde(skip)]
+        response: r#continue::Sender<Vec<Result<CommandEffect, String>>>,
+    },
+
+    #[serde(skip)]
+    ApplyLayoutTree {
+        space_id: Option<SpaceId>,
+        workspace: Option<usize>,
+        tree: LayoutTree,
+        #[serde(skip)]
+        response: r#continue::Sender<Result<(), String>>,
+    },
+
     #[serde(skip)]
     ConfigUpdated(Config),
 
@@ -269,8 +303,9 @@ pub enum DisplaySelector {
     Uuid(String),
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, strum_macros::VariantNames)]
 #[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
 pub enum ReactorCommand {
     Debug,
     Serialize,
@@ -292,6 +327,18 @@ pub enum ReactorCommand {
         selector: DisplaySelector,
         window_id: Option<u32>,
     },
+    /// Saves the layout of the active workspace under `name`.
+    SaveLayout {
+        name: String,
+    },
+    /// Arranges the windows of the active workspace like the layout saved
+    /// under `name`.
+    RestoreLayout {
+        name: String,
+    },
+    DeleteLayout {
+        name: String,
+    },
 }
 
 #[derive(Default, Debug, Clone)]
@@ -527,6 +574,7 @@ impl Reactor {
                 event_broadcaster: broadcast_tx,
                 wm_sender: None,
                 events_tx: None,
+                docked_hover_tracking: false,
             },
             notification_manager: managers::NotificationManager {
                 last_sls_notification_ids: Vec::new(),
@@ -656,6 +704,7 @@ impl Reactor {
             event,
             Event::QueryApplications(..)
                 | Event::QueryLayoutState { .. }
+                | Event::QueryLayoutTree { .. }
                 | Event::QueryMetrics(..)
                 | Event::QueryWindowInfo { .. }
                 | Event::QueryWindows { .. }
@@ -779,8 +828,8 @@ impl Reactor {
             }
             Event::MenuOpened => SystemEventHandler::handle_menu_opened(self),
             Event::MenuClosed => SystemEventHandler::handle_menu_closed(self),
-            Event::MouseMovedOverWindow(wsid) => {
-                WindowEventHandler::handle_mouse_moved_over_window(self, wsid);
+            Event::MouseMovedOverWindow { wsid, focus_follows_mouse } => {
+                WindowEventHandler::handle_mouse_moved_over_window(self, wsid, focus_follows_mouse);
             }
             Event::SystemWoke => SystemEventHandler::handle_system_woke(self),
             Event::MissionControlNativeEntered => {
@@ -795,62 +844,30 @@ impl Reactor {
             Event::RaiseTimeout { sequence_id } => {
                 SystemEventHandler::handle_raise_timeout(self, sequence_id);
             }
-            Event::Command(Command::Layout(cmd)) => {
-                CommandEventHandler::handle_command_layout(self, cmd);
-            }
-            Event::Command(Command::Metrics(cmd)) => {
-                CommandEventHandler::handle_command_metrics(self, cmd);
-            }
-            Event::ConfigUpdated(new_cfg) => {
-                CommandEventHandler::handle_config_updated(self, new_cfg);
-            }
-            Event::Command(Command::Reactor(ReactorCommand::Debug)) => {
-                CommandEventHandler::handle_command_reactor_debug(self);
-            }
-            Event::Command(Command::Reactor(ReactorCommand::Serialize)) => {
-                CommandEventHandler::handle_command_reactor_serialize(self);
-            }
-            Event::Command(Command::Reactor(ReactorCommand::SaveAndExit)) => {
-                CommandEventHandler::handle_command_reactor_save_and_exit(self);
-            }
-            Event::Command(Command::Reactor(ReactorCommand::SwitchSpace(dir))) => {
-                CommandEventHandler::handle_command_reactor_switch_space(self, dir);
-            }
-            Event::Command(Command::Reactor(ReactorCommand::FocusWindow {
-                window_id: wid,
-                window_server_id,
-            })) => {
-                CommandEventHandler::handle_command_reactor_focus_window(
-                    self,
-                    wid,
-                    window_server_id,
-                );
-            }
-            Event::Command(Command::Reactor(ReactorCommand::ShowMissionControlAll)) => {
-                CommandEventHandler::handle_command_reactor_show_mission_control_all(self);
-            }
-            Event::Command(Command::Reactor(ReactorCommand::ShowMissionControlCurrent)) => {
-                CommandEventHandler::handle_command_reactor_show_mission_control_current(self);
-            }
-            Event::Command(Command::Reactor(ReactorCommand::DismissMissionControl)) => {
-                CommandEventHandler::handle_command_reactor_dismiss_mission_control(self);
-            }
-            Event::Command(Command::Reactor(ReactorCommand::MoveMouseToDisplay(selector))) => {
-                CommandEventHandler::handle_command_reactor_move_mouse_to_display(self, &selector);
+            Event::Command(command) => {
+                if let Err(e) = self.handle_command(command) {
+                    error!("{}", e);
+                }
             }
-            Event::Command(Command::Reactor(ReactorCommand::FocusDisplay(selector))) => {
-                CommandEventHandler::handle_command_reactor_focus_display(self, &selector);
+            Event::CommandBatch {
+                commands,
+                abort_on_error,
+                response,
+            } => {
+                CommandEventHandler::handle_command_batch(self, commands, abort_on_error, response);
             }
-            Event::Command(Command::Reactor(ReactorCommand::MoveWindowToDisplay {
-                selector,
-                window_id,
-            })) => {
-                CommandEventHandler::handle_command_reactor_move_window_to_display(
-                    self, &selector, window_id,
+            Event::ApplyLayoutTree {
+                space_id,
+                workspace,
+                tree,
+                response,
+            } => {
+                CommandEventHandler::handle_apply_layout_tree(
+                    self, space_id, workspace, tree, response,
                 );
             }
-            Event::Command(Command::Reactor(ReactorCommand::CloseWindow { window_server_id })) => {
-                CommandEventHandler::handle_command_reactor_close_window(self, window_server_id)
+            Event::ConfigUpdated(new_cfg) => {
+                CommandEventHandler::handle_config_updated(self, new_cfg);
             }
             _ => (),
         }
@@ -880,6 +897,7 @@ impl Reactor {
                 });
             self.maybe_send_menu_update();
         }
+        self.update_docked_hover_tracking();
 
         self.workspace_switch_manager.mark_workspace_switch_inactive();
         if self.workspace_switch_manager.active_workspace_switch.is_some() && !layout_changed {
@@ -912,6 +930,73 @@ impl Reactor {
         }
     }
 
+    /// Runs `command`, returning why it failed for the commands that can.
+    fn handle_command(&mut self, command: Command) -> Result<(), String> {
+        match command {
+            Command::Layout(cmd) => {
+                CommandEventHandler::handle_command_layout(self, cmd);
+            }
+            Command::Metrics(cmd) => {
+                CommandEventHandler::handle_command_metrics(self, cmd);
+            }
+            Command::Reactor(ReactorCommand::Debug) => {
+                CommandEventHandler::handle_command_reactor_debug(self);
+            }
+            Command::Reactor(ReactorCommand::Serialize) => {
+                CommandEventHandler::handle_command_reactor_serialize(self);
+            }
+            Command::Reactor(ReactorCommand::SaveAndExit) => {
+                CommandEventHandler::handle_command_reactor_save_and_exit(self);
+            }
+            Command::Reactor(ReactorCommand::SwitchSpace(dir)) => {
+                CommandEventHandler::handle_command_reactor_switch_space(self, dir);
+            }
+            Command::Reactor(ReactorCommand::FocusWindow {
+                window_id: wid,
+                window_server_id,
+            }) => {
+                CommandEventHandler::handle_command_reactor_focus_window(
+                    self,
+                    wid,
+                    window_server_id,
+                );
+            }
+            Command::Reactor(ReactorCommand::ShowMissionControlAll) => {
+                CommandEventHandler::handle_command_reactor_show_mission_control_all(self);
+            }
+            Command::Reactor(ReactorCommand::ShowMissionControlCurrent) => {
+                CommandEventHandler::handle_command_reactor_show_mission_control_current(self);
+            }
+            Command::Reactor(ReactorCommand::DismissMissionControl) => {
+                CommandEventHandler::handle_command_reactor_dismiss_mission_control(self);
+            }
+            Command::Reactor(ReactorCommand::MoveMouseToDisplay(selector)) => {
+                CommandEventHandler::handle_command_reactor_move_mouse_to_display(self, &selector);
+            }
+            Command::Reactor(ReactorCommand::FocusDisplay(selector)) => {
+                CommandEventHandler::handle_command_reactor_focus_display(self, &selector);
+            }
+            Command::Reactor(ReactorCommand::MoveWindowToDisplay { selector, window_id }) => {
+                CommandEventHandler::handle_command_reactor_move_window_to_display(
+                    self, &selector, window_id,
+                );
+            }
+            Command::Reactor(ReactorCommand::CloseWindow { window_server_id }) => {
+                CommandEventHandler::handle_command_reactor_close_window(self, window_server_id)
+            }
+            Command::Reactor(ReactorCommand::SaveLayout { name }) => {
+                CommandEventHandler::handle_command_reactor_save_layout(self, &name)?
+            }
+            Command::Reactor(ReactorCommand::RestoreLayout { name }) => {
+                CommandEventHandler::handle_command_reactor_restore_layout(self, &name)?
+            }
+            Command::Reactor(ReactorCommand::DeleteLayout { name }) => {
+                CommandEventHandler::handle_command_reactor_delete_layout(&name)?
+            }
+        }
+        Ok(())
+    }
+
     fn create_window_data(&self, window_id: WindowId) -> Option<WindowData> {
         let window_state = self.window_manager.windows.get(&window_id)?;
         if window_state.is_minimized {
@@ -926,6 +1011,8 @@ impl Reactor {
             title: window_state.title.clone(),
             frame: window_state.frame_monotonic,
             is_floating: self.layout_manager.layout_engine.is_window_floating(window_id),
+            is_sticky: self.layout_manager.layout_engine.is_window_sticky(window_id),
+            marks: self.layout_manager.layout_engine.marks_for_window(window_id),
             is_focused: self.main_window() == Some(window_id),
             bundle_id: preferred_name,
             window_server_id: window_state.window_server_id.map(|wsid| wsid.as_u32()),
@@ -1405,6 +1492,9 @@ impl Reactor {
                         .layout_engine
                         .virtual_workspace_manager_mut()
                         .store_floating_position(space, ws_id, wid, session.last_frame);
+                    self.layout_manager
+                        .layout_engine
+                        .remember_floating_frames(&[(wid, session.last_frame)]);
                 }
             }
         }
@@ -1736,53 +1826,67 @@ impl Reactor {
             return;
         };
 
-        let Some(window_state) = self.window_manager.windows.get(&app_window_id) else {
-            return;
+        self.switch_to_workspace_of_window(app_window_id, WorkspaceSwitchOrigin::Auto);
+    }
+
+    /// Switches the window's space to the virtual workspace holding it, if that
+    /// workspace is not already active. Returns whether a switch happened.
+    fn switch_to_workspace_of_window(
+        &mut self,
+        window_id: WindowId,
+        origin: WorkspaceSwitchOrigin,
+    ) -> bool {
+        let Some(window_state) = self.window_manager.windows.get(&window_id) else {
+            return false;
         };
         let Some(window_space) = self
             .best_space_for_window(&window_state.frame_monotonic, window_state.window_server_id)
         else {
-            return;
+            return false;
         };
 
         let workspace_manager = self.layout_manager.layout_engine.virtual_workspace_manager();
         let Some(window_workspace) =
-            workspace_manager.workspace_for_window(window_space, app_window_id)
+            workspace_manager.workspace_for_window(window_space, window_id)
         else {
-            return;
+            return false;
         };
 
         let Some(current_workspace) =
             self.layout_manager.layout_engine.active_workspace(window_space)
         else {
-            return;
+            return false;
         };
 
-        if window_workspace != current_workspace {
-            let workspaces = self
-                .layout_manager
-                .layout_engine
-                .virtual_workspace_manager_mut()
-                .list_workspaces(window_space);
-            if let Some((workspace_index, _)) =
-                workspaces.iter().enumerate().find(|(_, (ws_id, _))| *ws_id == window_workspace)
-            {
-                debug!(
-                    "Auto-switching to workspace {} for activated app (pid: {})",
-                    workspace_index, pid
-                );
+        if window_workspace == current_workspace {
+            return false;
+        }
 
-                self.store_current_floating_positions(window_space);
-                self.workspace_switch_manager
-                    .start_workspace_switch(WorkspaceSwitchOrigin::Auto);
+        let workspaces = self
+            .layout_manager
+            .layout_engine
+            .virtual_workspace_manager_mut()
+            .list_workspaces(window_space);
+        let Some((workspace_index, _)) =
+            workspaces.iter().enumerate().find(|(_, (ws_id, _))| *ws_id == window_workspace)
+        else {
+            return false;
+        };
 
-                let response = self.layout_manager.layout_engine.handle_virtual_workspace_command(
-                    window_space,
-                    &layout::LayoutCommand::SwitchToWorkspace(workspace_index),
-                );
-                self.handle_layout_response(response, Some(window_space));
-            }
-        }
+        debug!(
+            "Auto-switching to workspace {} for window {:?}",
+            workspace_index, window_id
+        );
+
+        self.store_current_floating_positions(window_space);
+        self.workspace_switch_manager.start_workspace_switch(origin);
+
+        let response = self.layout_manager.layout_engine.handle_virtual_workspace_command(
+            window_space,
+            &layout::LayoutCommand::SwitchToWorkspace(workspace_index),
+        );
+        self.handle_layout_response(response, Some(window_space));
+        true
     }
 
     fn handle_layout_response(
@@ -2271,6 +2375,18 @@ impl Reactor {
         }
     }
 
+    /// Has the event tap report hovered windows while any window is docked.
+    fn update_docked_hover_tracking(&mut self) {
+        let docked = self.layout_manager.layout_engine.has_docked_windows();
+        if docked == self.communication_manager.docked_hover_tracking {
+            return;
+        }
+        self.communication_manager.docked_hover_tracking = docked;
+        if let Some(event_tap_tx) = self.communication_manager.event_tap_tx.as_ref() {
+            event_tap_tx.send(event_tap::Request::SetDockedWindows(docked));
+        }
+    }
+
     fn update_focus_follows_mouse_state(&self) {
         let should_enable = matches!(self.menu_manager.menu_state, MenuState::Closed)
             && !self.is_mission_control_active();
@@ -2488,6 +2604,7 @@ impl Reactor {
             .windows_in_active_workspace(space)
             .into_iter()
             .filter(|&wid| self.layout_manager.layout_engine.is_window_floating(wid))
+            .filter(|&wid| !self.layout_manager.layout_engine.is_window_docked(wid))
             .filter_map(|wid| {
                 self.window_manager
                     .windows
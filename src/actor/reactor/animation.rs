@@ -8,6 +8,7 @@ use crate::actor::app::{AppThreadHandle, Request, WindowId, pid_t};
 use crate::actor::reactor::Reactor;
 use crate::common::collections::HashMap;
 use crate::common::config::AnimationEasing;
+use crate::common::easing::ease;
 use crate::sys::geometry::{Round, SameAs};
 use crate::sys::power;
 use crate::sys::screen::SpaceId;
@@ -21,6 +22,7 @@ pub struct Animation<'a> {
     start: Instant,
     interval: Duration,
     frames: u32,
+    easing: AnimationEasing,
 
     windows: Vec<(
         &'a AppThreadHandle,
@@ -33,7 +35,7 @@ pub struct Animation<'a> {
 }
 
 impl<'a> Animation<'a> {
-    pub fn new(fps: f64, duration: f64, _: AnimationEasing) -> Self {
+    pub fn new(fps: f64, duration: f64, easing: AnimationEasing) -> Self {
         let interval = Duration::from_secs_f64(1.0 / fps);
         // let now = unsafe { CFAbsoluteTimeGetCurrent() };
         let now = Instant::now();
@@ -41,6 +43,7 @@ impl<'a> Animation<'a> {
             start: now, // + interval, // not necessary, provide one extra frame to get things going
             interval,
             frames: (duration * fps).round() as u32,
+            easing,
             windows: vec![],
         }
     }
@@ -80,7 +83,7 @@ impl<'a> Animation<'a> {
 
             next_frames.clear();
             for (_, _, from, to, _, _) in &self.windows {
-                next_frames.push(get_frame(*from, *to, t));
+                next_frames.push(get_frame(*from, *to, ease(self.easing, t)));
             }
 
             let deadline = self.start + frame * self.interval;
@@ -117,8 +120,7 @@ impl<'a> Animation<'a> {
     }
 }
 
-fn get_frame(a: CGRect, b: CGRect, t: f64) -> CGRect {
-    let s = ease(t);
+fn get_frame(a: CGRect, b: CGRect, s: f64) -> CGRect {
     CGRect {
         origin: CGPoint {
             x: blend(a.origin.x, b.origin.x, s),
@@ -131,15 +133,6 @@ fn get_frame(a: CGRect, b: CGRect, t: f64) -> CGRect {
     }
 }
 
-// https://notes.yvt.jp/Graphics/Easing-Functions/
-fn ease(t: f64) -> f64 {
-    if t < 0.5 {
-        (1.0 - f64::sqrt(1.0 - f64::powi(2.0 * t, 2))) / 2.0
-    } else {
-        (f64::sqrt(1.0 - f64::powi(-2.0 * t + 2.0, 2)) + 1.0) / 2.0
-    }
-}
-
 fn blend(a: f64, b: f64, s: f64) -> f64 { (1.0 - s) * a + s * b }
 
 pub struct AnimationManager;
@@ -158,7 +151,7 @@ impl AnimationManager {
         let mut anim = Animation::new(
             reactor.config_manager.config.settings.animation_fps,
             reactor.config_manager.config.settings.animation_duration,
-            reactor.config_manager.config.settings.animation_easing.clone(),
+            reactor.config_manager.config.settings.animation_easing,
         );
         let mut animated_count = 0;
         let mut animated_wids_wsids: Vec<u32> = Vec::new();
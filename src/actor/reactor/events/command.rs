@@ -3,14 +3,18 @@ use tracing::{error, info, warn};
 use super::super::Screen;
 use crate::actor::app::{AppThreadHandle, WindowId};
 use crate::actor::reactor::transaction_manager::TransactionId;
-use crate::actor::reactor::{DisplaySelector, Reactor, WorkspaceSwitchOrigin};
+use crate::actor::reactor::{Command, DisplaySelector, Reactor, WorkspaceSwitchOrigin};
 use crate::actor::stack_line::Event as StackLineEvent;
 use crate::actor::wm_controller::WmEvent;
 use crate::actor::{menu_bar, raise_manager};
 use crate::common::collections::HashMap;
 use crate::common::config::{self as config, Config};
 use crate::common::log::{MetricsCommand, handle_command};
-use crate::layout_engine::{EventResponse, LayoutCommand, LayoutEvent};
+use crate::layout_engine::{
+    EventResponse, LayoutCommand, LayoutEvent, LayoutTree, SavedLayout, SavedLayoutStore,
+    SavedWindow, WindowCandidate,
+};
+use crate::model::server::CommandEffect;
 use crate::sys::screen::{SpaceId, order_visible_spaces_by_position};
 use crate::sys::window_server::{self as window_server, WindowServerId};
 
@@ -61,6 +65,14 @@ impl CommandEventHandler {
             reactor.workspace_switch_manager.mark_workspace_switch_inactive();
         }
 
+        if let LayoutCommand::FocusMark { name } = &cmd {
+            // Bring the marked window's workspace forward first; the layout
+            // engine then focuses it like any other window.
+            if let Some(wid) = reactor.layout_manager.layout_engine.marked_window(name) {
+                reactor.switch_to_workspace_of_window(wid, WorkspaceSwitchOrigin::Manual);
+            }
+        }
+
         let response = match &cmd {
             LayoutCommand::NextWorkspace(_)
             | LayoutCommand::PrevWorkspace(_)
@@ -101,6 +113,42 @@ impl CommandEventHandler {
         handle_command(cmd);
     }
 
+    pub fn handle_apply_layout_tree(
+        reactor: &mut Reactor,
+        space_id: Option<SpaceId>,
+        workspace: Option<usize>,
+        tree: LayoutTree,
+        response: r#continue::Sender<Result<(), String>>,
+    ) {
+        let result = match reactor.query_space(space_id) {
+            Some(space) => {
+                reactor.layout_manager.layout_engine.apply_layout_tree(space, workspace, &tree)
+            }
+            None => Err("No space to apply the layout tree to".to_string()),
+        };
+        response.send(result);
+    }
+
+    pub fn handle_command_batch(
+        reactor: &mut Reactor,
+        commands: Vec<Command>,
+        abort_on_error: bool,
+        response: r#continue::Sender<Vec<Result<CommandEffect, String>>>,
+    ) {
+        let mut results = Vec::with_capacity(commands.len());
+        for command in commands {
+            let before = reactor.command_effect(None);
+            let result =
+                reactor.handle_command(command).map(|()| reactor.command_effect(Some(&before)));
+            let failed = result.is_err();
+            results.push(result);
+            if failed && abort_on_error {
+                break;
+            }
+        }
+        response.send(results);
+    }
+
     pub fn handle_config_updated(reactor: &mut Reactor, new_cfg: Config) {
         let old_keys = reactor.config_manager.config.keys.clone();
 
@@ -422,12 +470,9 @@ impl CommandEventHandler {
             window_id,
         );
 
+        // The layout is updated once the event is handled, which for a batch
+        // is after its last command.
         reactor.handle_layout_response(response, None);
-
-        let _ = reactor.update_layout(false, false).unwrap_or_else(|e| {
-            warn!("Layout update failed: {}", e);
-            false
-        });
     }
 
     pub fn handle_command_reactor_close_window(
@@ -443,4 +488,79 @@ impl CommandEventHandler {
             warn!("Close window command ignored because no window is tracked");
         }
     }
+
+    pub fn handle_command_reactor_save_layout(
+        reactor: &mut Reactor,
+        name: &str,
+    ) -> Result<(), String> {
+        let Some(space) = reactor.workspace_command_space() else {
+            return Err(format!(
+                "Could not save layout '{name}': there is no active space"
+            ));
+        };
+        let Some(tree) = reactor.layout_manager.layout_engine.layout_tree(space, None) else {
+            return Err(format!(
+                "Could not save layout '{name}': there is no active workspace"
+            ));
+        };
+        let saved = SavedLayout::from_tree(&tree, |wid| {
+            let window = reactor.window_manager.windows.get(&wid)?;
+            let app_id = reactor
+                .app_manager
+                .apps
+                .get(&wid.pid)
+                .and_then(|app| app.info.bundle_id.clone());
+            Some(SavedWindow::new(app_id, &window.title))
+        });
+        SavedLayoutStore::default()
+            .save(name, &saved)
+            .map_err(|e| format!("Could not save layout '{name}': {e:#}"))?;
+        info!("Saved layout '{}'", name);
+        Ok(())
+    }
+
+    pub fn handle_command_reactor_restore_layout(
+        reactor: &mut Reactor,
+        name: &str,
+    ) -> Result<(), String> {
+        let Some(space) = reactor.workspace_command_space() else {
+            return Err(format!(
+                "Could not restore layout '{name}': there is no active space"
+            ));
+        };
+        let saved = SavedLayoutStore::default()
+            .load(name)
+            .map_err(|e| format!("Could not restore layout '{name}': {e:#}"))?;
+        let candidates: Vec<_> = reactor
+            .layout_manager
+            .layout_engine
+            .windows_in_active_workspace(space)
+            .into_iter()
+            .filter(|&wid| !reactor.layout_manager.layout_engine.is_window_floating(wid))
+            .filter_map(|wid| {
+                let window = reactor.window_manager.windows.get(&wid)?;
+                Some(WindowCandidate {
+                    id: wid,
+                    app_id: reactor
+                        .app_manager
+                        .apps
+                        .get(&wid.pid)
+                        .and_then(|app| app.info.bundle_id.clone()),
+                    title: window.title.clone(),
+                })
+            })
+            .collect();
+        let tree = saved.to_tree(&candidates);
+        reactor
+            .layout_manager
+            .layout_engine
+            .apply_layout_tree(space, None, &tree)
+            .map_err(|e| format!("Could not restore layout '{name}': {e}"))
+    }
+
+    pub fn handle_command_reactor_delete_layout(name: &str) -> Result<(), String> {
+        SavedLayoutStore::default()
+            .delete(name)
+            .map_err(|e| format!("Could not delete layout '{name}': {e:#}"))
+    }
 }
@@ -10,6 +10,8 @@ use crate::layout_engine::LayoutEvent;
 use crate::sys::app::WindowInfo as Window;
 use crate::sys::event::{MouseState, get_mouse_state};
 use crate::sys::geometry::SameAs;
+use crate::sys::process;
+use crate::sys::screen::SpaceId;
 use crate::sys::window_server::{WindowServerId, WindowServerInfo};
 
 pub struct WindowEventHandler;
@@ -56,6 +58,8 @@ impl WindowEventHandler {
         if is_manageable {
             if let Some(space) = reactor.best_space_for_window(&frame, server_id) {
                 if reactor.is_space_active(space) {
+                    // Look this up before the new window can take focus.
+                    let swallow_parent = swallow_target(reactor, space, wid);
                     if let Some(app_info) =
                         reactor.app_manager.apps.get(&wid.pid).map(|app| app.info.clone())
                     {
@@ -65,6 +69,11 @@ impl WindowEventHandler {
                         reactor.process_windows_for_app_rules(wid.pid, vec![wid], app_info);
                     }
                     reactor.send_layout_event(LayoutEvent::WindowAdded(space, wid));
+                    if let Some(parent) = swallow_parent
+                        && reactor.layout_manager.layout_engine.swallow_window(space, parent, wid)
+                    {
+                        debug!(?wid, ?parent, "Window swallowed its parent's window");
+                    }
                 }
             }
         }
@@ -411,11 +420,22 @@ impl WindowEventHandler {
         }
     }
 
-    pub fn handle_mouse_moved_over_window(reactor: &mut Reactor, wsid: WindowServerId) {
-        let Some(&wid) = reactor.window_manager.window_ids.get(&wsid) else {
+    pub fn handle_mouse_moved_over_window(
+        reactor: &mut Reactor,
+        wsid: Option<WindowServerId>,
+        focus_follows_mouse: bool,
+    ) {
+        let wid = wsid.and_then(|wsid| reactor.window_manager.window_ids.get(&wsid).copied());
+        // Docked windows slide in while hovered and back out once the pointer
+        // moves anywhere else, including the desktop and windows rift does not
+        // manage; the layout update after this event animates the move.
+        if reactor.layout_manager.layout_engine.reveal_docked_window(wid) {
+            trace!(?wid, "Docked window reveal state changed");
+        }
+        let Some(wid) = wid else {
             return;
         };
-        if !reactor.should_raise_on_mouse_over(wid) {
+        if !focus_follows_mouse || !reactor.should_raise_on_mouse_over(wid) {
             return;
         }
 
@@ -433,6 +453,28 @@ impl WindowEventHandler {
     }
 }
 
+/// Finds the window a new window should swallow: the last focused window of
+/// the nearest ancestor process that matches a swallow rule.
+fn swallow_target(reactor: &Reactor, space: SpaceId, wid: WindowId) -> Option<WindowId> {
+    let swallow = &reactor.config_manager.config.settings.swallow;
+    if swallow.rules.is_empty() {
+        return None;
+    }
+    let info = &reactor.app_manager.apps.get(&wid.pid)?.info;
+    if swallow.is_excluded(info.bundle_id.as_deref()) {
+        return None;
+    }
+
+    let parent_pid = process::ancestors(wid.pid).find(|pid| {
+        reactor.app_manager.apps.get(pid).is_some_and(|app| {
+            swallow.swallows(app.info.bundle_id.as_deref(), app.info.localized_name.as_deref())
+        })
+    })?;
+    reactor
+        .last_focused_window_in_space(space)
+        .filter(|parent| parent.pid == parent_pid)
+}
+
 fn handle_mouse_up_if_needed(reactor: &mut Reactor, mouse_state: Option<MouseState>) {
     if mouse_state == Some(MouseState::Up)
         && (matches!(
@@ -19,8 +19,9 @@ use crate::actor::{event_tap, menu_bar, raise_manager, stack_line, window_notify
 use crate::common::collections::{HashMap, HashSet};
 use crate::common::config::{Config, WindowSnappingSettings};
 use crate::layout_engine::LayoutEngine;
+use crate::sys::geometry::CGRectExt;
 use crate::sys::screen::{ScreenId, SpaceId};
-use crate::sys::window_server::{WindowServerId, WindowServerInfo};
+use crate::sys::window_server::{WindowServerId, WindowServerInfo, current_cursor_location};
 
 /// Manages window state and lifecycle
 pub struct WindowManager {
@@ -182,6 +183,8 @@ pub struct CommunicationManager {
     pub event_broadcaster: BroadcastSender,
     pub wm_sender: Option<wm_controller::Sender>,
     pub events_tx: Option<actor::Sender<Event>>,
+    /// Whether the event tap was last told that a window is docked
+    pub docked_hover_tracking: bool,
 }
 
 /// Manages recording state
@@ -214,6 +217,7 @@ impl LayoutManager {
     fn calculate_layout(reactor: &mut Reactor) -> LayoutResult {
         let screens = reactor.space_manager.screens.clone();
         let mut layout_result = LayoutResult::new();
+        let cursor = current_cursor_location().ok();
 
         for screen in screens {
             let Some(space) = reactor.space_manager.space_for_screen(&screen) else {
@@ -243,6 +247,7 @@ impl LayoutManager {
                     reactor.config_manager.config.settings.ui.stack_line.thickness(),
                     reactor.config_manager.config.settings.ui.stack_line.horiz_placement,
                     reactor.config_manager.config.settings.ui.stack_line.vert_placement,
+                    cursor.filter(|point| screen.frame.contains(*point)),
                     |wid| reactor.window_manager.windows.get(&wid).map(|w| w.frame_monotonic),
                 );
             layout_result.push((space, layout));
@@ -1,11 +1,13 @@
-use objc2_core_foundation::CGRect;
+use objc2_core_foundation::{CGPoint, CGRect, CGSize};
 
 use crate::actor::app::WindowId;
 use crate::actor::menu_bar;
 use crate::actor::reactor::{Event, Reactor};
 use crate::common::collections::HashSet;
+use crate::layout_engine::{Direction, Orientation};
 use crate::model::server::{
-    ApplicationData, DisplayData, LayoutStateData, WindowData, WorkspaceData,
+    ActiveWorkspaceData, ApplicationData, CommandEffect, DisplayData, LayoutStateData,
+    PreselectionData, WindowData, WorkspaceData,
 };
 use crate::model::virtual_workspace::VirtualWorkspaceId;
 use crate::sys::screen::{SpaceId, get_active_space_number};
@@ -37,6 +39,12 @@ impl Reactor {
                 let layout_state = self.handle_layout_state_query(space_id);
                 response.send(layout_state);
             }
+            Event::QueryLayoutTree { space_id, workspace, response } => {
+                let tree = self.query_space(space_id).and_then(|space| {
+                    self.layout_manager.layout_engine.layout_tree(space, workspace)
+                });
+                response.send(tree);
+            }
             Event::QueryMetrics(response) => {
                 let metrics = self.handle_metrics_query();
                 response.send(metrics);
@@ -78,6 +86,34 @@ impl Reactor {
         }));
     }
 
+    /// Focused window and active workspace, compared against `before`, the
+    /// effect taken before a command ran.
+    pub(super) fn command_effect(&self, before: Option<&CommandEffect>) -> CommandEffect {
+        let engine = &self.layout_manager.layout_engine;
+        let focused_window = engine.focused_window();
+        let workspace = self.workspace_command_space().and_then(|space| {
+            let id = engine.active_workspace(space)?;
+            Some(ActiveWorkspaceData {
+                id: format!("{:?}", id),
+                index: engine.active_workspace_idx(space),
+                name: engine.workspace_name(space, id)?,
+            })
+        });
+        CommandEffect {
+            focus_changed: before.is_some_and(|b| b.focused_window != focused_window),
+            workspace_changed: before.is_some_and(|b| b.workspace != workspace),
+            focused_window,
+            workspace,
+        }
+    }
+
+    /// `space_id`, or the active space when none is given.
+    pub(super) fn query_space(&self, space_id: Option<SpaceId>) -> Option<SpaceId> {
+        space_id
+            .or_else(|| get_active_space_number())
+            .or_else(|| self.space_manager.screens.first().and_then(|s| s.space))
+    }
+
     fn handle_workspace_query(&mut self, space_id_param: Option<SpaceId>) -> Vec<WorkspaceData> {
         let mut workspaces = Vec::new();
 
@@ -296,12 +332,24 @@ impl Reactor {
 
         let focused_window = self.main_window();
 
+        let preselection = self.layout_manager.layout_engine.preselection(space_id).and_then(
+            |(window, direction, share)| {
+                let frame = self.window_manager.windows.get(&window)?.frame_monotonic;
+                Some(PreselectionData {
+                    window,
+                    direction,
+                    area: preselection_area(frame, direction, share),
+                })
+            },
+        );
+
         Some(LayoutStateData {
             space_id: space_id_u64,
             mode: "tiling".to_string(), // TODO: Determine actual mode
             floating_windows,
             tiled_windows,
             focused_window,
+            preselection,
         })
     }
 
@@ -512,3 +560,21 @@ impl Reactor {
         serde_json::to_string_pretty(&out)
     }
 }
+
+/// The `share` of `frame` a window inserted towards `direction` will occupy.
+fn preselection_area(frame: CGRect, direction: Direction, share: f64) -> CGRect {
+    let size = match direction.orientation() {
+        Orientation::Horizontal => CGSize::new(frame.size.width * share, frame.size.height),
+        Orientation::Vertical => CGSize::new(frame.size.width, frame.size.height * share),
+    };
+    let origin = match direction {
+        Direction::Left | Direction::Up => frame.origin,
+        Direction::Right => {
+            CGPoint::new(frame.origin.x + frame.size.width - size.width, frame.origin.y)
+        }
+        Direction::Down => {
+            CGPoint::new(frame.origin.x, frame.origin.y + frame.size.height - size.height)
+        }
+    };
+    CGRect::new(origin, size)
+}
@@ -104,6 +104,94 @@ fn it_manages_windows_on_enabled_spaces() {
     );
 }
 
+#[test]
+fn config_reload_keeps_the_layout_mode_set_at_runtime() {
+    use crate::common::config::LayoutMode;
+
+    let mut apps = Apps::new();
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &crate::common::config::VirtualWorkspaceSettings::default(),
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    let space = SpaceId::new(1);
+    let full_screen = CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.));
+    reactor.handle_event(screen_params_event(vec![full_screen], vec![Some(space)], vec![]));
+    reactor.handle_events(apps.make_app(1, make_windows(2)));
+    let mode = |reactor: &mut Reactor| {
+        reactor
+            .layout_manager
+            .layout_engine
+            .layout_tree(space, None)
+            .and_then(|tree| tree.mode)
+    };
+
+    reactor.handle_event(Event::Command(Command::Layout(LayoutCommand::SetLayoutMode(
+        LayoutMode::Bsp,
+    ))));
+    assert_eq!(mode(&mut reactor), Some(LayoutMode::Bsp));
+
+    let mut config = reactor.config_manager.config.clone();
+    reactor.handle_event(Event::ConfigUpdated(config.clone()));
+    assert_eq!(mode(&mut reactor), Some(LayoutMode::Bsp));
+
+    // Changing the mode in the config still applies it.
+    config.settings.layout.mode = LayoutMode::Grid;
+    reactor.handle_event(Event::ConfigUpdated(config));
+    assert_eq!(mode(&mut reactor), Some(LayoutMode::Grid));
+}
+
+#[test]
+fn docked_window_tucks_away_when_the_pointer_leaves_it() {
+    let mut apps = Apps::new();
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &crate::common::config::VirtualWorkspaceSettings::default(),
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    let space = SpaceId::new(1);
+    let full_screen = CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.));
+    reactor.handle_event(screen_params_event(vec![full_screen], vec![Some(space)], vec![]));
+    reactor.handle_events(apps.make_app(1, make_windows(2)));
+    apps.simulate_events();
+
+    let docked = WindowId::new(1, 1);
+    reactor.send_layout_event(LayoutEvent::WindowFocused(space, docked));
+    reactor.handle_event(Event::Command(Command::Layout(LayoutCommand::DockWindow(
+        Direction::Right,
+    ))));
+    apps.simulate_events();
+    let docked_x = |apps: &Apps| apps.windows.get(&docked).unwrap().frame.origin.x;
+    assert_eq!(docked_x(&apps), 990.);
+
+    reactor.handle_event(Event::MouseMovedOverWindow {
+        wsid: Some(WindowServerId::new(1)),
+        focus_follows_mouse: false,
+    });
+    apps.simulate_events();
+    let frame = apps.windows.get(&docked).unwrap().frame;
+    assert_eq!(frame.max().x, 1000.);
+
+    // Moving onto the desktop, or a window rift doesn't manage, tucks it away.
+    reactor.handle_event(Event::MouseMovedOverWindow {
+        wsid: None,
+        focus_follows_mouse: false,
+    });
+    apps.simulate_events();
+    assert_eq!(docked_x(&apps), 990.);
+
+    reactor.handle_event(Event::MouseMovedOverWindow {
+        wsid: Some(WindowServerId::new(1)),
+        focus_follows_mouse: false,
+    });
+    reactor.handle_event(Event::MouseMovedOverWindow {
+        wsid: Some(WindowServerId::new(99)),
+        focus_follows_mouse: false,
+    });
+    apps.simulate_events();
+    assert_eq!(docked_x(&apps), 990.);
+}
+
 #[test]
 fn it_clears_screen_state_when_no_displays_are_reported() {
     let mut reactor = Reactor::new_for_test(LayoutEngine::new(
@@ -1,9 +1,13 @@
 use std::io::{self, Write};
+use std::path::PathBuf;
 use std::process::{self};
 
 use clap::{Parser, Subcommand};
 use rift_wm::actor::reactor::{self, DisplaySelector};
-use rift_wm::ipc::{RiftCommand, RiftMachClient, RiftRequest, RiftResponse};
+use rift_wm::common::config::{LayoutMode, socket_file};
+use rift_wm::ipc::{
+    PROTOCOL_VERSION, RiftCommand, RiftMachClient, RiftRequest, RiftResponse, RiftSocketClient,
+};
 use rift_wm::layout_engine as layout;
 use rift_wm::sys::window_server::WindowServerId;
 use serde_json::Value;
@@ -12,6 +16,10 @@ use serde_json::Value;
 #[command(name = "rift-cli")]
 #[command(about = "Command-line interface for rift window manager")]
 struct Cli {
+    /// Talk to rift over its Unix socket instead of the Mach port
+    /// (defaults to ~/.rift/rift.sock)
+    #[arg(long, global = true, num_args = 0..=1, value_name = "PATH")]
+    socket: Option<Option<PathBuf>>,
     #[command(subcommand)]
     command: Commands,
 }
@@ -74,8 +82,20 @@ enum QueryCommands {
     Applications,
     /// Get layout state for a space
     Layout { space_id: u64 },
+    /// Get the layout tree of a workspace as JSON (defaults to the active one)
+    LayoutTree {
+        #[arg(long)]
+        space_id: Option<u64>,
+        /// Workspace index on the space
+        #[arg(long)]
+        workspace: Option<usize>,
+    },
+    /// List the names of saved layouts
+    SavedLayouts,
     /// Get performance metrics
     Metrics,
+    /// Show the protocol version and the requests, commands and events rift supports
+    Protocol,
 }
 
 #[derive(Subcommand)]
@@ -110,6 +130,25 @@ enum ExecuteCommands {
         #[command(subcommand)]
         display_cmd: DisplayCommands,
     },
+    /// Rebuild a workspace's layout from a JSON layout tree
+    ApplyLayoutTree {
+        /// File to read the tree from, or `-` for stdin
+        file: String,
+        #[arg(long)]
+        space_id: Option<u64>,
+        /// Workspace index on the space (defaults to the active one)
+        #[arg(long)]
+        workspace: Option<usize>,
+    },
+    /// Run a JSON array of commands, updating the layout once at the end.
+    /// Config commands cannot be batched.
+    Batch {
+        /// File to read the commands from, or `-` for stdin
+        file: String,
+        /// Skip the remaining commands after one fails
+        #[arg(long)]
+        abort_on_error: bool,
+    },
     /// Save current state and exit rift
     SaveAndExit,
     /// Show timing metrics
@@ -128,6 +167,43 @@ enum WindowCommands {
     },
     /// Toggle window floating state
     ToggleFloat,
+    /// Park the focused window off-screen on an edge (left, right, up, down);
+    /// it slides into view while hovered
+    Dock { edge: String },
+    /// Return the focused (or currently revealed) docked window to normal floating
+    Undock,
+    /// Open the next window beside the focused one (left, right, up, down); bsp layouts only
+    Preselect { direction: String },
+    /// Clear the preselection of the focused window
+    CancelPreselect,
+    /// Move the focused floating window by a number of points
+    MoveFloating {
+        #[arg(long, default_value_t = 0.0, allow_negative_numbers = true)]
+        dx: f64,
+        #[arg(long, default_value_t = 0.0, allow_negative_numbers = true)]
+        dy: f64,
+    },
+    /// Grow (or with negative values shrink) the focused floating window by a number of points
+    ResizeFloating {
+        #[arg(long, default_value_t = 0.0, allow_negative_numbers = true)]
+        dw: f64,
+        #[arg(long, default_value_t = 0.0, allow_negative_numbers = true)]
+        dh: f64,
+    },
+    /// Snap the focused floating window to a screen half, quarter or third
+    /// (left_half, right_half, top_half, bottom_half, top_left, top_right,
+    /// bottom_left, bottom_right, left_third, center_third, right_third)
+    SnapFloating { region: String },
+    /// Center the focused floating window on its screen
+    CenterFloating,
+    /// Put a named mark on the focused window, moving it if another window has it
+    Mark { name: String },
+    /// Remove a mark by name, or every mark of the focused window if omitted
+    Unmark { name: Option<String> },
+    /// Focus the marked window, switching to its workspace if needed
+    FocusMark { name: String },
+    /// Swap the focused window with the marked one (same layout only)
+    SwapWithMark { name: String },
     /// Toggle fullscreen mode (fills the whole screen, ignores outer gaps)
     ToggleFullscreen,
     /// Toggle fullscreen within configured outer gaps (respects outer gaps / fills tiling area)
@@ -143,6 +219,28 @@ enum WindowCommands {
     ///   rift-cli execute window resize-by --amount 0.05    # grow by 5%
     ///   rift-cli execute window resize-by --amount -0.10   # shrink by 10%
     ResizeBy { amount: f64 },
+    /// Move one edge of the selected window (left, right, up, down) by a fraction
+    /// of the screen; positive moves it outwards, negative inwards.
+    /// Examples:
+    ///   rift-cli execute window resize-edge right --amount 0.05
+    ///   rift-cli execute window resize-edge left --amount -0.05
+    ResizeEdge {
+        direction: String,
+        #[arg(long, allow_negative_numbers = true)]
+        amount: f64,
+    },
+    /// Set the selected window to an exact size; pass exactly one of the options.
+    /// Examples:
+    ///   rift-cli execute window set-size --ratio 0.66   # two thirds of its container
+    ///   rift-cli execute window set-size --width 800    # 800 points wide
+    SetSize {
+        #[arg(long)]
+        ratio: Option<f64>,
+        #[arg(long)]
+        width: Option<f64>,
+        #[arg(long)]
+        height: Option<f64>,
+    },
     /// Close a window by window server identifier
     Close {
         /// Window Id (window server id or idx from window id)
@@ -184,10 +282,42 @@ enum LayoutCommands {
     ToggleStack,
     /// Global orientation toggle that works consistently across layout modes (and between splits/stacks)
     ToggleOrientation,
+    /// Rotate the split containing the selection clockwise (90, 180 or 270; bsp layouts)
+    Rotate {
+        #[arg(allow_negative_numbers = true)]
+        degrees: i32,
+    },
+    /// Mirror the split containing the selection: horizontal swaps left and right,
+    /// vertical swaps top and bottom (bsp layouts)
+    Flip { axis: String },
+    /// Even out the sizes in the layout (bsp: by window count; master_stack: master ratio)
+    Balance,
     /// Unjoin previously joined windows
     Unjoin,
     /// Toggle floating on the focused selection (tree focus)
     ToggleFocusFloat,
+    /// Add a window to the master area (master/stack layout)
+    IncrementMasterCount,
+    /// Remove a window from the master area (master/stack layout)
+    DecrementMasterCount,
+    /// Move the selected window to the master area (master/stack layout)
+    PromoteToMaster,
+    /// Move the focused window to the scratchpad, optionally under a name
+    SendToScratchpad { name: Option<String> },
+    /// Show or hide the most recent (or the named) scratchpad window
+    ToggleScratchpad { name: Option<String> },
+    /// Keep the focused window visible on every workspace (makes it float)
+    ToggleSticky,
+    /// Switch the global layout mode (traditional, bsp, monocle, grid, master_stack)
+    SetMode { mode: String },
+    /// Switch the active workspace to a layout mode (traditional, bsp, monocle, grid, master_stack)
+    SetWorkspaceMode { mode: String },
+    /// Save the layout of the active workspace under a name (in ~/.rift/layouts)
+    Save { name: String },
+    /// Arrange the windows of the active workspace like a saved layout
+    Restore { name: String },
+    /// Delete a saved layout
+    Delete { name: String },
 }
 
 #[derive(Subcommand)]
@@ -202,6 +332,7 @@ enum ConfigCommands {
     SetAnimationFps {
         value: f64,
     },
+    /// Set the easing curve, e.g. ease_in_out_cubic, cubic_bezier(0.25,0.1,0.25,1.0) or spring(180,20)
     SetAnimationEasing {
         value: String,
     },
@@ -320,6 +451,11 @@ enum SubscribeCommands {
         /// Event to subscribe to (workspace_changed, windows_changed, window_title_changed, *)
         event: String,
     },
+    /// Subscribe to events over the Unix socket and print them as they happen
+    Socket {
+        /// Event to subscribe to (workspace_changed, windows_changed, window_title_changed, *)
+        event: String,
+    },
     /// Subscribe to events via CLI command execution
     Cli {
         /// Event to subscribe to (workspace_changed, windows_changed, window_title_changed, *)
@@ -357,6 +493,16 @@ fn main() {
         process::exit(0);
     }
 
+    let stream_events = matches!(&cli.command, Commands::Subscribe {
+        subscribe: SubscribeCommands::Socket { .. }
+    });
+    let socket = match cli.socket {
+        Some(path) => Some(path.unwrap_or_else(socket_file)),
+        None if stream_events => Some(socket_file()),
+        None => None,
+    };
+    let pretty = std::env::var("RIFT_CLI_PRETTY").map(|v| v != "0").unwrap_or(false);
+
     let request = match build_request(cli.command) {
         Ok(req) => req,
         Err(e) => {
@@ -365,22 +511,29 @@ fn main() {
         }
     };
 
-    let client = match RiftMachClient::connect() {
-        Ok(c) => c,
-        Err(e) => {
-            eprintln!("Failed to connect to rift: {}", e);
-            process::exit(1);
-        }
+    // Send request and handle response.
+    let mut socket_client = None;
+    let response = match socket {
+        Some(path) => match RiftSocketClient::connect(&path) {
+            Ok(c) => socket_client.insert(c).send_request(&request),
+            Err(e) => {
+                eprintln!("Failed to connect to rift: {}", e);
+                process::exit(1);
+            }
+        },
+        None => match RiftMachClient::connect() {
+            Ok(c) => c.send_request(&request),
+            Err(e) => {
+                eprintln!("Failed to connect to rift: {}", e);
+                process::exit(1);
+            }
+        },
     };
 
-    // Send request and handle response.
-    match client.send_request(&request) {
+    match response {
         Ok(resp) => match resp {
             RiftResponse::Success { data } => {
-                if let Err(e) = write_json(
-                    &data,
-                    std::env::var("RIFT_CLI_PRETTY").map(|v| v != "0").unwrap_or(false),
-                ) {
+                if let Err(e) = write_json(&data, pretty) {
                     eprintln!("Failed to handle response: {}", e);
                     process::exit(1);
                 }
@@ -403,6 +556,23 @@ fn main() {
             process::exit(1);
         }
     }
+
+    if let (true, Some(client)) = (stream_events, socket_client.as_mut()) {
+        loop {
+            let event = match client.next_event() {
+                Ok(event) => event,
+                Err(e) => {
+                    eprintln!("Communication error: {}", e);
+                    process::exit(1);
+                }
+            };
+            let value = serde_json::to_value(event).unwrap_or(Value::Null);
+            if let Err(e) = write_json(&value, pretty) {
+                eprintln!("Failed to handle event: {}", e);
+                process::exit(1);
+            }
+        }
+    }
 }
 
 fn build_request(command: Commands) -> Result<RiftRequest, String> {
@@ -425,13 +595,22 @@ fn build_query_request(query: QueryCommands) -> Result<RiftRequest, String> {
         QueryCommands::Window { window_id } => Ok(RiftRequest::GetWindowInfo { window_id }),
         QueryCommands::Applications => Ok(RiftRequest::GetApplications),
         QueryCommands::Layout { space_id } => Ok(RiftRequest::GetLayoutState { space_id }),
+        QueryCommands::LayoutTree { space_id, workspace } => {
+            Ok(RiftRequest::GetLayoutTree { space_id, workspace })
+        }
+        QueryCommands::SavedLayouts => Ok(RiftRequest::ListSavedLayouts),
         QueryCommands::Metrics => Ok(RiftRequest::GetMetrics),
+        QueryCommands::Protocol => Ok(RiftRequest::Hello {
+            client_version: PROTOCOL_VERSION,
+        }),
     }
 }
 
 fn build_subscribe_request(sub: SubscribeCommands) -> Result<RiftRequest, String> {
     match sub {
-        SubscribeCommands::Mach { event } => Ok(RiftRequest::Subscribe { event }),
+        SubscribeCommands::Mach { event } | SubscribeCommands::Socket { event } => {
+            Ok(RiftRequest::Subscribe { event })
+        }
         SubscribeCommands::Cli { event, command, args } => {
             Ok(RiftRequest::SubscribeCli { event, command, args })
         }
@@ -451,6 +630,15 @@ fn build_execute_request(execute: ExecuteCommands) -> Result<RiftRequest, String
             map_mission_control_command(mission_cmd)?
         }
         ExecuteCommands::Display { display_cmd } => map_display_command(display_cmd)?,
+        ExecuteCommands::ApplyLayoutTree { file, space_id, workspace } => {
+            let tree = read_layout_tree(&file)?;
+            return Ok(RiftRequest::ApplyLayoutTree { space_id, workspace, tree });
+        }
+        ExecuteCommands::Batch { file, abort_on_error } => {
+            let commands = serde_json::from_str(&read_input(&file)?)
+                .map_err(|e| format!("Invalid commands: {}", e))?;
+            return Ok(RiftRequest::ExecuteBatch { commands, abort_on_error });
+        }
         ExecuteCommands::SaveAndExit => {
             RiftCommand::Reactor(reactor::Command::Reactor(reactor::ReactorCommand::SaveAndExit))
         }
@@ -463,30 +651,30 @@ fn build_execute_request(execute: ExecuteCommands) -> Result<RiftRequest, String
         return Ok(RiftRequest::GetConfig);
     }
 
-    let maybe_config_json = match &rift_command {
-        RiftCommand::Config(cfg_cmd) => match serde_json::to_string(cfg_cmd) {
-            Ok(s) => Some(s),
-            Err(_) => None,
-        },
-        _ => None,
-    };
+    if let RiftCommand::Reactor(reactor::Command::Reactor(reactor::ReactorCommand::SaveAndExit)) =
+        &rift_command
+    {
+        // rift exits before it could answer an `execute` request.
+        let command = serde_json::to_string(&rift_command)
+            .map_err(|e| format!("Failed to serialize command: {}", e))?;
+        return Ok(RiftRequest::ExecuteCommand { command, args: vec![] });
+    }
 
-    let command_str = serde_json::to_string(&rift_command)
-        .map_err(|e| format!("Failed to serialize command: {}", e))?;
+    Ok(RiftRequest::Execute { command: rift_command })
+}
 
-    if let Some(cfg_json) = maybe_config_json {
-        Ok(RiftRequest::ExecuteCommand {
-            command: command_str,
-            args: vec!["__apply_config__".to_string(), cfg_json],
-        })
+fn read_input(file: &str) -> Result<String, String> {
+    if file == "-" {
+        io::read_to_string(io::stdin()).map_err(|e| format!("Failed to read stdin: {}", e))
     } else {
-        Ok(RiftRequest::ExecuteCommand {
-            command: command_str,
-            args: vec![],
-        })
+        std::fs::read_to_string(file).map_err(|e| format!("Failed to read {}: {}", file, e))
     }
 }
 
+fn read_layout_tree(file: &str) -> Result<layout::LayoutTree, String> {
+    serde_json::from_str(&read_input(file)?).map_err(|e| format!("Invalid layout tree: {}", e))
+}
+
 fn map_window_command(cmd: WindowCommands) -> Result<RiftCommand, String> {
     use layout::LayoutCommand as LC;
     match cmd {
@@ -498,6 +686,48 @@ fn map_window_command(cmd: WindowCommands) -> Result<RiftCommand, String> {
         WindowCommands::ToggleFloat => Ok(RiftCommand::Reactor(reactor::Command::Layout(
             LC::ToggleWindowFloating,
         ))),
+        WindowCommands::Preselect { direction } => Ok(RiftCommand::Reactor(
+            reactor::Command::Layout(LC::Preselect(parse_focus_direction(&direction)?)),
+        )),
+        WindowCommands::CancelPreselect => Ok(RiftCommand::Reactor(reactor::Command::Layout(
+            LC::CancelPreselect,
+        ))),
+        WindowCommands::Dock { edge } => Ok(RiftCommand::Reactor(reactor::Command::Layout(
+            LC::DockWindow(parse_focus_direction(&edge)?),
+        ))),
+        WindowCommands::Undock => {
+            Ok(RiftCommand::Reactor(reactor::Command::Layout(LC::UndockWindow)))
+        }
+        WindowCommands::MoveFloating { dx, dy } => Ok(RiftCommand::Reactor(
+            reactor::Command::Layout(LC::MoveFloatingWindow { dx, dy }),
+        )),
+        WindowCommands::ResizeFloating { dw, dh } => Ok(RiftCommand::Reactor(
+            reactor::Command::Layout(LC::ResizeFloatingWindow { dw, dh }),
+        )),
+        WindowCommands::SnapFloating { region } => Ok(RiftCommand::Reactor(
+            reactor::Command::Layout(LC::SnapFloatingWindow(parse_snap_region(&region)?)),
+        )),
+        WindowCommands::CenterFloating => Ok(RiftCommand::Reactor(reactor::Command::Layout(
+            LC::CenterFloatingWindow,
+        ))),
+        WindowCommands::Mark { name } => {
+            Ok(RiftCommand::Reactor(reactor::Command::Layout(LC::SetMark {
+                name,
+            })))
+        }
+        WindowCommands::Unmark { name } => {
+            Ok(RiftCommand::Reactor(reactor::Command::Layout(LC::UnsetMark {
+                name,
+            })))
+        }
+        WindowCommands::FocusMark { name } => {
+            Ok(RiftCommand::Reactor(reactor::Command::Layout(LC::FocusMark {
+                name,
+            })))
+        }
+        WindowCommands::SwapWithMark { name } => Ok(RiftCommand::Reactor(
+            reactor::Command::Layout(LC::SwapWithMark { name }),
+        )),
         WindowCommands::ToggleFullscreen => Ok(RiftCommand::Reactor(reactor::Command::Layout(
             LC::ToggleFullscreen,
         ))),
@@ -513,6 +743,23 @@ fn map_window_command(cmd: WindowCommands) -> Result<RiftCommand, String> {
         WindowCommands::ResizeBy { amount } => Ok(RiftCommand::Reactor(reactor::Command::Layout(
             LC::ResizeWindowBy { amount },
         ))),
+        WindowCommands::ResizeEdge { direction, amount } => {
+            Ok(RiftCommand::Reactor(reactor::Command::Layout(LC::ResizeEdge {
+                direction: parse_focus_direction(&direction)?,
+                amount,
+            })))
+        }
+        WindowCommands::SetSize { ratio, width, height } => {
+            let target = match (ratio, width, height) {
+                (Some(ratio), None, None) => layout::SizeTarget::Ratio(ratio),
+                (None, Some(width), None) => layout::SizeTarget::Width(width),
+                (None, None, Some(height)) => layout::SizeTarget::Height(height),
+                _ => return Err("Pass exactly one of --ratio, --width or --height".to_string()),
+            };
+            Ok(RiftCommand::Reactor(reactor::Command::Layout(
+                LC::SetWindowSize(target),
+            )))
+        }
         WindowCommands::Close { window_id } => {
             let wsid = parse_window_server_id(&window_id)?;
             Ok(RiftCommand::Reactor(reactor::Command::Reactor(
@@ -581,12 +828,62 @@ fn map_layout_command(cmd: LayoutCommands) -> Result<RiftCommand, String> {
         LayoutCommands::ToggleOrientation => Ok(RiftCommand::Reactor(reactor::Command::Layout(
             LC::ToggleOrientation,
         ))),
+        LayoutCommands::Rotate { degrees } => {
+            if degrees % 90 != 0 {
+                return Err(format!(
+                    "Invalid rotation '{}'; must be a multiple of 90",
+                    degrees
+                ));
+            }
+            Ok(RiftCommand::Reactor(reactor::Command::Layout(LC::RotateTree {
+                degrees,
+            })))
+        }
+        LayoutCommands::Flip { axis } => Ok(RiftCommand::Reactor(reactor::Command::Layout(
+            LC::FlipTree(parse_orientation(&axis)?),
+        ))),
+        LayoutCommands::Balance => {
+            Ok(RiftCommand::Reactor(reactor::Command::Layout(LC::BalanceTree)))
+        }
         LayoutCommands::Unjoin => {
             Ok(RiftCommand::Reactor(reactor::Command::Layout(LC::UnjoinWindows)))
         }
         LayoutCommands::ToggleFocusFloat => Ok(RiftCommand::Reactor(reactor::Command::Layout(
             LC::ToggleFocusFloating,
         ))),
+        LayoutCommands::IncrementMasterCount => Ok(RiftCommand::Reactor(reactor::Command::Layout(
+            LC::IncrementMasterCount,
+        ))),
+        LayoutCommands::DecrementMasterCount => Ok(RiftCommand::Reactor(reactor::Command::Layout(
+            LC::DecrementMasterCount,
+        ))),
+        LayoutCommands::PromoteToMaster => Ok(RiftCommand::Reactor(reactor::Command::Layout(
+            LC::PromoteToMaster,
+        ))),
+        LayoutCommands::SendToScratchpad { name } => Ok(RiftCommand::Reactor(
+            reactor::Command::Layout(LC::SendToScratchpad { name }),
+        )),
+        LayoutCommands::ToggleScratchpad { name } => Ok(RiftCommand::Reactor(
+            reactor::Command::Layout(LC::ToggleScratchpad { name }),
+        )),
+        LayoutCommands::ToggleSticky => {
+            Ok(RiftCommand::Reactor(reactor::Command::Layout(LC::ToggleSticky)))
+        }
+        LayoutCommands::SetMode { mode } => Ok(RiftCommand::Reactor(reactor::Command::Layout(
+            LC::SetLayoutMode(parse_layout_mode(&mode)?),
+        ))),
+        LayoutCommands::SetWorkspaceMode { mode } => Ok(RiftCommand::Reactor(
+            reactor::Command::Layout(LC::SetWorkspaceLayoutMode(parse_layout_mode(&mode)?)),
+        )),
+        LayoutCommands::Save { name } => Ok(RiftCommand::Reactor(reactor::Command::Reactor(
+            reactor::ReactorCommand::SaveLayout { name },
+        ))),
+        LayoutCommands::Restore { name } => Ok(RiftCommand::Reactor(reactor::Command::Reactor(
+            reactor::ReactorCommand::RestoreLayout { name },
+        ))),
+        LayoutCommands::Delete { name } => Ok(RiftCommand::Reactor(reactor::Command::Reactor(
+            reactor::ReactorCommand::DeleteLayout { name },
+        ))),
     }
 }
 
@@ -631,7 +928,7 @@ fn map_config_command(cmd: ConfigCommands) -> Result<RiftCommand, String> {
                 "ease_in_circ" => AnimationEasing::EaseInCirc,
                 "ease_out_circ" => AnimationEasing::EaseOutCirc,
                 "ease_in_out_circ" => AnimationEasing::EaseInOutCirc,
-                _ => return Err(format!("Invalid animation easing: {}", value)),
+                other => parse_custom_easing(other)?,
             };
             ConfigCommand::SetAnimationEasing(easing)
         }
@@ -758,6 +1055,77 @@ fn parse_focus_direction(value: &str) -> Result<layout::Direction, String> {
     }
 }
 
+fn parse_orientation(value: &str) -> Result<layout::Orientation, String> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "horizontal" => Ok(layout::Orientation::Horizontal),
+        "vertical" => Ok(layout::Orientation::Vertical),
+        other => Err(format!(
+            "Invalid axis '{}'; must be horizontal or vertical",
+            other
+        )),
+    }
+}
+
+fn parse_snap_region(value: &str) -> Result<layout::SnapRegion, String> {
+    use layout::SnapRegion;
+    match value.trim().to_ascii_lowercase().replace('-', "_").as_str() {
+        "left_half" => Ok(SnapRegion::LeftHalf),
+        "right_half" => Ok(SnapRegion::RightHalf),
+        "top_half" => Ok(SnapRegion::TopHalf),
+        "bottom_half" => Ok(SnapRegion::BottomHalf),
+        "top_left" => Ok(SnapRegion::TopLeft),
+        "top_right" => Ok(SnapRegion::TopRight),
+        "bottom_left" => Ok(SnapRegion::BottomLeft),
+        "bottom_right" => Ok(SnapRegion::BottomRight),
+        "left_third" => Ok(SnapRegion::LeftThird),
+        "center_third" => Ok(SnapRegion::CenterThird),
+        "right_third" => Ok(SnapRegion::RightThird),
+        other => Err(format!(
+            "Invalid snap region '{}'; must be a half (left_half, right_half, top_half, bottom_half), \
+             a quarter (top_left, top_right, bottom_left, bottom_right) or a third \
+             (left_third, center_third, right_third)",
+            other
+        )),
+    }
+}
+
+fn parse_custom_easing(value: &str) -> Result<rift_wm::common::config::AnimationEasing, String> {
+    use rift_wm::common::config::AnimationEasing;
+
+    let invalid = || format!("Invalid animation easing: {}", value);
+    let (name, args) = match value.split_once('(') {
+        Some((name, rest)) => (name.trim(), rest.trim().strip_suffix(')').ok_or_else(invalid)?),
+        None => (value.trim(), ""),
+    };
+    let args = args
+        .split(',')
+        .map(str::trim)
+        .filter(|arg| !arg.is_empty())
+        .map(|arg| arg.parse::<f64>().map_err(|_| invalid()))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    match (name, args.as_slice()) {
+        ("cubic_bezier", &[x1, y1, x2, y2]) => Ok(AnimationEasing::CubicBezier { x1, y1, x2, y2 }),
+        ("spring", &[]) => Ok(AnimationEasing::default_spring()),
+        ("spring", &[stiffness, damping]) => Ok(AnimationEasing::Spring { stiffness, damping }),
+        _ => Err(invalid()),
+    }
+}
+
+fn parse_layout_mode(value: &str) -> Result<LayoutMode, String> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "traditional" => Ok(LayoutMode::Traditional),
+        "bsp" => Ok(LayoutMode::Bsp),
+        "monocle" => Ok(LayoutMode::Monocle),
+        "grid" => Ok(LayoutMode::Grid),
+        "master_stack" => Ok(LayoutMode::MasterStack),
+        other => Err(format!(
+            "Invalid layout mode '{}'; must be traditional, bsp, monocle, grid, or master_stack",
+            other
+        )),
+    }
+}
+
 fn write_json(value: &Value, pretty: bool) -> Result<(), String> {
     let stdout = io::stdout();
     let mut handle = stdout.lock();
@@ -141,15 +141,22 @@ Enable it in System Settings > Desktop & Dock (Mission Control) and restart Rift
 
     let (broadcast_tx, broadcast_rx) = rift_wm::actor::channel();
 
-    let layout = if opt.restore {
-        LayoutEngine::load(restore_file()).unwrap()
+    let restored = if opt.restore {
+        // A restore file from an older version may no longer parse; start
+        // with a fresh layout rather than refusing to launch.
+        LayoutEngine::load(restore_file())
+            .inspect_err(|e| eprintln!("Could not restore layout, starting fresh: {e:#}"))
+            .ok()
     } else {
+        None
+    };
+    let layout = restored.unwrap_or_else(|| {
         LayoutEngine::new(
             &config.virtual_workspaces,
             &config.settings.layout,
             Some(broadcast_tx.clone()),
         )
-    };
+    });
     let (event_tap_tx, event_tap_rx) = rift_wm::actor::channel();
     let (menu_tx, menu_rx) = rift_wm::actor::channel();
     let (stack_line_tx, stack_line_rx) = rift_wm::actor::channel();
@@ -191,6 +198,11 @@ Enable it in System Settings > Desktop & Dock (Mission Control) and restart Rift
             process::exit(1);
         }
     };
+    if let Err(err) =
+        ipc::run_socket_server(events_tx.clone(), config_tx.clone(), server_state.clone())
+    {
+        eprintln!("Failed to start the IPC socket server: {}", err);
+    }
 
     let mach_bridge_rx = broadcast_rx;
 
@@ -1,4 +1,5 @@
 pub mod collections;
 pub mod config;
+pub mod easing;
 pub mod log;
 pub mod util;
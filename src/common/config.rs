@@ -18,8 +18,9 @@ const DEPRECATED_MAP: &[(&str, &str)] = &[
     ("toggle_tile_orientation", "toggle_orientation"),
 ];
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, strum_macros::VariantNames)]
 #[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
 pub enum ConfigCommand {
     SetAnimate(bool),
     SetAnimationDuration(f64),
@@ -58,6 +59,8 @@ pub enum ConfigCommand {
 
 pub fn data_dir() -> PathBuf { dirs::home_dir().unwrap().join(".rift") }
 pub fn restore_file() -> PathBuf { data_dir().join("layout.ron") }
+pub fn layouts_dir() -> PathBuf { data_dir().join("layouts") }
+pub fn socket_file() -> PathBuf { data_dir().join("rift.sock") }
 pub fn config_file() -> PathBuf {
     dirs::home_dir().unwrap().join(".config").join("rift").join("config.toml")
 }
@@ -83,6 +86,9 @@ pub struct VirtualWorkspaceSettings {
     pub reapply_app_rules_on_title_change: bool,
     #[serde(default)]
     pub app_rules: Vec<AppWorkspaceRule>,
+    /// Per-workspace layout modes; workspaces without a rule use `settings.layout.mode`
+    #[serde(default)]
+    pub layout_modes: Vec<WorkspaceLayoutRule>,
 }
 
 // Allow specifying a workspace by numeric index or by name in the config.
@@ -94,6 +100,15 @@ pub enum WorkspaceSelector {
     Name(String),
 }
 
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct WorkspaceLayoutRule {
+    /// Workspace index (0 based) or workspace name
+    pub workspace: WorkspaceSelector,
+    /// Layout mode used by the workspace
+    pub mode: LayoutMode,
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 #[serde(deny_unknown_fields)]
 pub struct AppWorkspaceRule {
@@ -127,6 +142,19 @@ pub struct AppWorkspaceRule {
     /// non-empty string and will be compared against the accessibility subrole
     /// reported by the AX APIs for a window (exact string match).
     pub ax_subrole: Option<String>,
+
+    /// Optional: Send matching windows to the scratchpad under this name instead of
+    /// a workspace. They start hidden and float when shown with `toggle_scratchpad`.
+    pub scratchpad: Option<String>,
+
+    /// Optional: Keep matching windows visible on every virtual workspace of their
+    /// display. Sticky windows always float.
+    #[serde(default)]
+    pub sticky: bool,
+
+    /// Optional: Placement policy for matching windows when they float, overriding
+    /// `settings.layout.floating.placement`.
+    pub placement: Option<FloatingPlacement>,
 }
 
 impl Default for VirtualWorkspaceSettings {
@@ -141,6 +169,7 @@ impl Default for VirtualWorkspaceSettings {
             default_workspace: 0,
             reapply_app_rules_on_title_change: false,
             app_rules: Vec::new(),
+            layout_modes: Vec::new(),
         }
     }
 }
@@ -170,6 +199,17 @@ impl VirtualWorkspaceSettings {
             ));
         }
 
+        for rule in &self.layout_modes {
+            if let WorkspaceSelector::Index(idx) = rule.workspace {
+                if idx >= self.default_workspace_count {
+                    issues.push(format!(
+                        "layout_modes references workspace {} but only {} workspaces are configured",
+                        idx, self.default_workspace_count
+                    ));
+                }
+            }
+        }
+
         // Validate rules and check duplicates in a single pass
         let mut seen_app_ids = crate::common::collections::HashSet::default();
         let mut seen_app_names = crate::common::collections::HashSet::default();
@@ -204,6 +244,20 @@ impl VirtualWorkspaceSettings {
                 }
             }
 
+            if rule.workspace.is_some() && rule.scratchpad.is_some() {
+                issues.push(format!(
+                    "App rule {} sets both workspace and scratchpad; scratchpad takes precedence",
+                    index
+                ));
+            }
+
+            if rule.sticky && rule.scratchpad.is_some() {
+                issues.push(format!(
+                    "App rule {} sets both sticky and scratchpad; sticky is ignored for scratchpad windows",
+                    index
+                ));
+            }
+
             if let Some(ref app_id) = rule.app_id {
                 if !app_id.is_empty() && !app_id.contains('.') {
                     issues.push(format!(
@@ -330,6 +384,10 @@ pub struct Settings {
     #[serde(default)]
     pub window_snapping: WindowSnappingSettings,
 
+    /// Terminals whose tile is taken over by GUI windows they launch
+    #[serde(default)]
+    pub swallow: SwallowSettings,
+
     /// Commands to run on startup (e.g., for subscribing to events)
     #[serde(default)]
     pub run_on_start: Vec<String>,
@@ -367,6 +425,63 @@ pub enum AnimationEasing {
     EaseInCirc,
     EaseOutCirc,
     EaseInOutCirc,
+    /// Custom curve, equivalent to CSS `cubic-bezier(x1, y1, x2, y2)`
+    CubicBezier {
+        x1: f64,
+        y1: f64,
+        x2: f64,
+        y2: f64,
+    },
+    /// Damped spring (unit mass); low damping overshoots the target before settling
+    Spring {
+        #[serde(default = "default_spring_stiffness")]
+        stiffness: f64,
+        #[serde(default = "default_spring_damping")]
+        damping: f64,
+    },
+}
+
+fn default_spring_stiffness() -> f64 { 180.0 }
+
+fn default_spring_damping() -> f64 { 20.0 }
+
+impl AnimationEasing {
+    pub fn default_spring() -> Self {
+        AnimationEasing::Spring {
+            stiffness: default_spring_stiffness(),
+            damping: default_spring_damping(),
+        }
+    }
+
+    pub fn validate(&self) -> Vec<String> {
+        let mut issues = Vec::new();
+        match *self {
+            AnimationEasing::CubicBezier { x1, x2, .. } => {
+                if !(0.0..=1.0).contains(&x1) || !(0.0..=1.0).contains(&x2) {
+                    issues.push(format!(
+                        "animation_easing cubic_bezier x1 and x2 must be between 0.0 and 1.0, got {} and {}",
+                        x1, x2
+                    ));
+                }
+            }
+            AnimationEasing::Spring { stiffness, damping } => {
+                if stiffness <= 0.0 {
+                    issues.push(format!(
+                        "animation_easing spring stiffness must be positive, got {}",
+                        stiffness
+                    ));
+                }
+                if damping <= 0.0 {
+                    issues.push(format!(
+                        "animation_easing spring damping must be positive, got {}",
+                        damping
+                    ));
+                }
+            }
+            _ => {}
+        }
+        issues
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
@@ -431,6 +546,68 @@ pub struct WindowSnappingSettings {
     pub drag_swap_fraction: f64,
 }
 
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct SwallowSettings {
+    /// Apps (usually terminals) whose focused window is replaced by a window
+    /// opened by one of their child processes, until that window closes
+    #[serde(default)]
+    pub rules: Vec<SwallowRule>,
+    /// Bundle identifiers of apps that never swallow their parent's window
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct SwallowRule {
+    /// Application bundle identifier (e.g., "com.apple.Terminal")
+    pub app_id: Option<String>,
+    /// Optional: Application name pattern (alternative to app_id)
+    pub app_name: Option<String>,
+}
+
+impl SwallowRule {
+    /// Matches like an app rule: case-insensitive bundle id, and an app name
+    /// that contains (or is contained in) the pattern.
+    pub fn matches(&self, bundle_id: Option<&str>, app_name: Option<&str>) -> bool {
+        if self.app_id.is_none() && self.app_name.is_none() {
+            return false;
+        }
+        let id_matches = self.app_id.as_deref().is_none_or(|rule_id| {
+            bundle_id.is_some_and(|bundle_id| rule_id.eq_ignore_ascii_case(bundle_id))
+        });
+        let name_matches = self.app_name.as_deref().is_none_or(|rule_name| {
+            app_name.is_some_and(|name| {
+                let (name, rule_name) = (name.to_lowercase(), rule_name.to_lowercase());
+                name.contains(&rule_name) || rule_name.contains(&name)
+            })
+        });
+        id_matches && name_matches
+    }
+}
+
+impl SwallowSettings {
+    pub fn swallows(&self, bundle_id: Option<&str>, app_name: Option<&str>) -> bool {
+        self.rules.iter().any(|rule| rule.matches(bundle_id, app_name))
+    }
+
+    pub fn is_excluded(&self, bundle_id: Option<&str>) -> bool {
+        bundle_id.is_some_and(|bundle_id| {
+            self.exclude.iter().any(|excluded| excluded.eq_ignore_ascii_case(bundle_id))
+        })
+    }
+
+    pub fn validate(&self) -> Vec<String> {
+        self.rules
+            .iter()
+            .enumerate()
+            .filter(|(_, rule)| rule.app_id.is_none() && rule.app_name.is_none())
+            .map(|(index, _)| format!("Swallow rule {} must set app_id or app_name", index))
+            .collect()
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy, Default)]
 #[serde(rename_all = "snake_case")]
 pub enum MenuBarDisplayMode {
@@ -534,10 +711,19 @@ pub struct LayoutSettings {
     /// Gap configuration for window spacing
     #[serde(default)]
     pub gaps: GapSettings,
+    /// Master/stack layout configuration
+    #[serde(default)]
+    pub master_stack: MasterStackSettings,
+    /// Binary space partitioning layout configuration
+    #[serde(default)]
+    pub bsp: BspSettings,
+    /// Floating window configuration
+    #[serde(default)]
+    pub floating: FloatingSettings,
 }
 
 /// Layout mode enum
-#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy, Default)]
 #[serde(rename_all = "snake_case")]
 pub enum LayoutMode {
     /// Traditional container-based tiling (i3/sway style)
@@ -545,6 +731,12 @@ pub enum LayoutMode {
     Traditional,
     /// Binary space partitioning tiling
     Bsp,
+    /// Every window fills the tiling area; focus cycles between them
+    Monocle,
+    /// Windows are arranged in a near-square grid of rows and columns
+    Grid,
+    /// dwm-style master area with the remaining windows stacked beside it
+    MasterStack,
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy)]
@@ -575,6 +767,71 @@ pub struct StackSettings {
     pub default_orientation: StackDefaultOrientation,
 }
 
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct MasterStackSettings {
+    /// Fraction of the tiling area width given to the master area
+    #[serde(default = "default_master_ratio")]
+    pub master_ratio: f64,
+    /// Number of windows placed in the master area
+    #[serde(default = "default_master_count")]
+    pub master_count: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct BspSettings {
+    /// Which window a new window splits, and on which side of it it goes
+    #[serde(default)]
+    pub insertion: BspInsertion,
+    /// Fraction of a new split kept by the window that was split
+    #[serde(default = "default_bsp_split_ratio")]
+    pub split_ratio: f64,
+}
+
+/// Where new windows are inserted in a bsp layout
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum BspInsertion {
+    /// Split the focused window side by side, with the new window on the right
+    #[default]
+    SideBySide,
+    /// Split the focused window along its longer side
+    LongestSide,
+    /// Split the focused window, turning clockwise with each level so that
+    /// windows opened in a row spiral inwards
+    Spiral,
+    /// Split the last window in the tree, alternating the split direction
+    Rightmost,
+    /// Split the focused window in the opposite direction to the split it is in
+    Alternate,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct FloatingSettings {
+    /// Where floating windows without a stored position are placed
+    #[serde(default)]
+    pub placement: FloatingPlacement,
+}
+
+/// Placement policy for new floating windows
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum FloatingPlacement {
+    /// Centered on the screen
+    #[default]
+    Center,
+    /// Centered on the mouse pointer, kept on screen
+    UnderCursor,
+    /// Offset down and to the right of the previous floating window
+    Cascade,
+    /// Wherever it overlaps other floating windows the least
+    Smart,
+    /// Where the last window of the same app was, centered otherwise
+    Remember,
+}
+
 /// Gap configuration for window spacing
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
 #[serde(deny_unknown_fields)]
@@ -641,6 +898,24 @@ impl Default for StackSettings {
     }
 }
 
+impl Default for MasterStackSettings {
+    fn default() -> Self {
+        Self {
+            master_ratio: default_master_ratio(),
+            master_count: default_master_count(),
+        }
+    }
+}
+
+impl Default for BspSettings {
+    fn default() -> Self {
+        Self {
+            insertion: BspInsertion::default(),
+            split_ratio: default_bsp_split_ratio(),
+        }
+    }
+}
+
 impl Settings {
     pub fn validate(&self) -> Vec<String> {
         let mut issues = Vec::new();
@@ -659,7 +934,9 @@ impl Settings {
             ));
         }
 
+        issues.extend(self.animation_easing.validate());
         issues.extend(self.layout.validate());
+        issues.extend(self.swallow.validate());
 
         if self.gestures.swipe_vertical_tolerance < 0.0 {
             issues.push(format!(
@@ -680,6 +957,10 @@ impl LayoutSettings {
 
         issues.extend(self.gaps.validate());
 
+        issues.extend(self.master_stack.validate());
+
+        issues.extend(self.bsp.validate());
+
         issues
     }
 }
@@ -699,6 +980,36 @@ impl StackSettings {
     }
 }
 
+impl MasterStackSettings {
+    pub fn validate(&self) -> Vec<String> {
+        let mut issues = Vec::new();
+
+        if !(0.05..=0.95).contains(&self.master_ratio) {
+            issues.push(format!(
+                "master_stack.master_ratio must be between 0.05 and 0.95, got {}",
+                self.master_ratio
+            ));
+        }
+
+        issues
+    }
+}
+
+impl BspSettings {
+    pub fn validate(&self) -> Vec<String> {
+        let mut issues = Vec::new();
+
+        if !(0.05..=0.95).contains(&self.split_ratio) {
+            issues.push(format!(
+                "bsp.split_ratio must be between 0.05 and 0.95, got {}",
+                self.split_ratio
+            ));
+        }
+
+        issues
+    }
+}
+
 impl GapSettings {
     pub fn validate(&self) -> Vec<String> {
         let mut issues = Vec::new();
@@ -805,6 +1116,12 @@ fn default_stack_offset() -> f64 { 40.0 }
 
 fn default_stack_orientation() -> StackDefaultOrientation { StackDefaultOrientation::Perpendicular }
 
+fn default_master_ratio() -> f64 { 0.5 }
+
+fn default_master_count() -> usize { 1 }
+
+fn default_bsp_split_ratio() -> f64 { 0.5 }
+
 fn default_animation_duration() -> f64 { 0.3 }
 
 fn default_animation_fps() -> f64 { 100.0 }
@@ -1201,4 +1518,22 @@ mod tests {
         let (s, _maybe_dep) = suggestion.unwrap();
         assert_eq!(s, "toggle_stack");
     }
+
+    #[test]
+    fn swallow_rules_match_like_app_rules() {
+        let toml = r#"
+            [settings.swallow]
+            rules = [ { app_id = "com.apple.Terminal" }, { app_name = "kitty" }, {} ]
+            exclude = ["com.apple.Preview"]
+        "#;
+        let cfg = Config::parse(toml).unwrap();
+        let swallow = &cfg.settings.swallow;
+
+        assert!(swallow.swallows(Some("com.apple.terminal"), Some("Terminal")));
+        assert!(swallow.swallows(Some("net.kovidgoyal.kitty"), Some("Kitty")));
+        assert!(!swallow.swallows(Some("com.googlecode.iterm2"), Some("iTerm2")));
+        assert!(swallow.is_excluded(Some("com.apple.Preview")));
+        assert!(!swallow.is_excluded(None));
+        assert_eq!(swallow.validate().len(), 1);
+    }
 }
@@ -0,0 +1,260 @@
+//! Easing curves used by window animations.
+//!
+//! Every function maps animation progress `t` in `[0, 1]` to eased progress.
+//! All curves start at 0 and end at 1; springs may overshoot in between.
+
+use std::f64::consts::PI;
+
+use crate::common::config::AnimationEasing;
+
+pub fn ease(easing: AnimationEasing, t: f64) -> f64 {
+    let t = t.clamp(0.0, 1.0);
+    match easing {
+        AnimationEasing::Linear => t,
+        AnimationEasing::EaseInOut | AnimationEasing::EaseInOutCirc => ease_in_out_circ(t),
+        AnimationEasing::EaseInSine => 1.0 - (t * PI / 2.0).cos(),
+        AnimationEasing::EaseOutSine => (t * PI / 2.0).sin(),
+        AnimationEasing::EaseInOutSine => -((PI * t).cos() - 1.0) / 2.0,
+        AnimationEasing::EaseInQuad => ease_in_pow(t, 2),
+        AnimationEasing::EaseOutQuad => ease_out_pow(t, 2),
+        AnimationEasing::EaseInOutQuad => ease_in_out_pow(t, 2),
+        AnimationEasing::EaseInCubic => ease_in_pow(t, 3),
+        AnimationEasing::EaseOutCubic => ease_out_pow(t, 3),
+        AnimationEasing::EaseInOutCubic => ease_in_out_pow(t, 3),
+        AnimationEasing::EaseInQuart => ease_in_pow(t, 4),
+        AnimationEasing::EaseOutQuart => ease_out_pow(t, 4),
+        AnimationEasing::EaseInOutQuart => ease_in_out_pow(t, 4),
+        AnimationEasing::EaseInQuint => ease_in_pow(t, 5),
+        AnimationEasing::EaseOutQuint => ease_out_pow(t, 5),
+        AnimationEasing::EaseInOutQuint => ease_in_out_pow(t, 5),
+        AnimationEasing::EaseInExpo => {
+            if t == 0.0 {
+                0.0
+            } else {
+                2f64.powf(10.0 * t - 10.0)
+            }
+        }
+        AnimationEasing::EaseOutExpo => {
+            if t == 1.0 {
+                1.0
+            } else {
+                1.0 - 2f64.powf(-10.0 * t)
+            }
+        }
+        AnimationEasing::EaseInOutExpo => {
+            if t == 0.0 || t == 1.0 {
+                t
+            } else if t < 0.5 {
+                2f64.powf(20.0 * t - 10.0) / 2.0
+            } else {
+                (2.0 - 2f64.powf(-20.0 * t + 10.0)) / 2.0
+            }
+        }
+        AnimationEasing::EaseInCirc => 1.0 - (1.0 - t * t).sqrt(),
+        AnimationEasing::EaseOutCirc => (1.0 - (t - 1.0).powi(2)).sqrt(),
+        AnimationEasing::CubicBezier { x1, y1, x2, y2 } => cubic_bezier(x1, y1, x2, y2, t),
+        AnimationEasing::Spring { stiffness, damping } => spring(stiffness, damping, t),
+    }
+}
+
+fn ease_in_pow(t: f64, n: i32) -> f64 { t.powi(n) }
+
+fn ease_out_pow(t: f64, n: i32) -> f64 { 1.0 - (1.0 - t).powi(n) }
+
+fn ease_in_out_pow(t: f64, n: i32) -> f64 {
+    if t < 0.5 {
+        2f64.powi(n - 1) * t.powi(n)
+    } else {
+        1.0 - (-2.0 * t + 2.0).powi(n) / 2.0
+    }
+}
+
+// https://notes.yvt.jp/Graphics/Easing-Functions/
+fn ease_in_out_circ(t: f64) -> f64 {
+    if t < 0.5 {
+        (1.0 - f64::sqrt(1.0 - f64::powi(2.0 * t, 2))) / 2.0
+    } else {
+        (f64::sqrt(1.0 - f64::powi(-2.0 * t + 2.0, 2)) + 1.0) / 2.0
+    }
+}
+
+/// CSS-style `cubic-bezier(x1, y1, x2, y2)` with fixed end points (0, 0) and
+/// (1, 1). `x1` and `x2` are expected to be in `[0, 1]` so that the curve is
+/// a function of time.
+pub fn cubic_bezier(x1: f64, y1: f64, x2: f64, y2: f64, t: f64) -> f64 {
+    if t <= 0.0 || t >= 1.0 {
+        return t.clamp(0.0, 1.0);
+    }
+
+    let bezier = |p1: f64, p2: f64, s: f64| {
+        let inv = 1.0 - s;
+        3.0 * inv * inv * s * p1 + 3.0 * inv * s * s * p2 + s * s * s
+    };
+    let bezier_slope = |p1: f64, p2: f64, s: f64| {
+        let inv = 1.0 - s;
+        3.0 * inv * inv * p1 + 6.0 * inv * s * (p2 - p1) + 3.0 * s * s * (1.0 - p2)
+    };
+
+    // Find the curve parameter whose x matches t. Newton's method converges
+    // quickly for well-behaved curves; fall back to bisection otherwise.
+    let mut s = t;
+    for _ in 0..8 {
+        let err = bezier(x1, x2, s) - t;
+        if err.abs() < 1e-7 {
+            return bezier(y1, y2, s);
+        }
+        let slope = bezier_slope(x1, x2, s);
+        if slope.abs() < 1e-6 {
+            break;
+        }
+        s -= err / slope;
+    }
+
+    let (mut lo, mut hi) = (0.0, 1.0);
+    s = t;
+    for _ in 0..64 {
+        let x = bezier(x1, x2, s);
+        if (x - t).abs() < 1e-7 {
+            break;
+        }
+        if x < t {
+            lo = s;
+        } else {
+            hi = s;
+        }
+        s = (lo + hi) / 2.0;
+    }
+    bezier(y1, y2, s)
+}
+
+/// Damped spring with unit mass, released from 0 towards a target of 1.
+/// `t` spans the whole animation, so `stiffness` and `damping` are relative to
+/// the configured animation duration. The final frame always lands on 1.
+pub fn spring(stiffness: f64, damping: f64, t: f64) -> f64 {
+    if t <= 0.0 {
+        return 0.0;
+    }
+    if t >= 1.0 {
+        return 1.0;
+    }
+
+    let omega = stiffness.max(f64::EPSILON).sqrt();
+    let zeta = damping.max(0.0) / (2.0 * omega);
+    if zeta < 1.0 {
+        let omega_d = omega * (1.0 - zeta * zeta).sqrt();
+        let decay = (-zeta * omega * t).exp();
+        1.0 - decay * ((omega_d * t).cos() + zeta * omega / omega_d * (omega_d * t).sin())
+    } else {
+        // Critically damped; over-damped springs are treated the same, which
+        // only makes them settle a little sooner than a physical spring would.
+        1.0 - (-omega * t).exp() * (1.0 + omega * t)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: [AnimationEasing; 23] = [
+        AnimationEasing::Linear,
+        AnimationEasing::EaseInOut,
+        AnimationEasing::EaseInSine,
+        AnimationEasing::EaseOutSine,
+        AnimationEasing::EaseInOutSine,
+        AnimationEasing::EaseInQuad,
+        AnimationEasing::EaseOutQuad,
+        AnimationEasing::EaseInOutQuad,
+        AnimationEasing::EaseInCubic,
+        AnimationEasing::EaseOutCubic,
+        AnimationEasing::EaseInOutCubic,
+        AnimationEasing::EaseInQuart,
+        AnimationEasing::EaseOutQuart,
+        AnimationEasing::EaseInOutQuart,
+        AnimationEasing::EaseInQuint,
+        AnimationEasing::EaseOutQuint,
+        AnimationEasing::EaseInOutQuint,
+        AnimationEasing::EaseInExpo,
+        AnimationEasing::EaseOutExpo,
+        AnimationEasing::EaseInOutExpo,
+        AnimationEasing::EaseInCirc,
+        AnimationEasing::EaseOutCirc,
+        AnimationEasing::EaseInOutCirc,
+    ];
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-6, "{a} != {b}");
+    }
+
+    #[test]
+    fn curves_start_at_zero_and_end_at_one() {
+        for easing in ALL {
+            assert_close(ease(easing, 0.0), 0.0);
+            assert_close(ease(easing, 1.0), 1.0);
+        }
+    }
+
+    #[test]
+    fn standard_curves_are_monotonic() {
+        for easing in ALL {
+            let mut prev = 0.0;
+            for i in 1..=100 {
+                let v = ease(easing, f64::from(i) / 100.0);
+                assert!(v >= prev - 1e-9, "{easing:?} decreased at step {i}");
+                prev = v;
+            }
+        }
+    }
+
+    #[test]
+    fn in_out_curves_are_symmetric() {
+        for easing in [
+            AnimationEasing::EaseInOutSine,
+            AnimationEasing::EaseInOutQuad,
+            AnimationEasing::EaseInOutCubic,
+            AnimationEasing::EaseInOutExpo,
+            AnimationEasing::EaseInOutCirc,
+        ] {
+            assert_close(ease(easing, 0.5), 0.5);
+            assert_close(ease(easing, 0.2), 1.0 - ease(easing, 0.8));
+        }
+    }
+
+    #[test]
+    fn known_values() {
+        assert_close(ease(AnimationEasing::EaseInQuad, 0.5), 0.25);
+        assert_close(ease(AnimationEasing::EaseOutCubic, 0.5), 0.875);
+        assert_close(ease(AnimationEasing::EaseInOutQuart, 0.25), 0.03125);
+        assert_close(ease(AnimationEasing::EaseInExpo, 0.5), 2f64.powi(-5));
+    }
+
+    #[test]
+    fn linear_cubic_bezier_is_identity() {
+        for i in 0..=10 {
+            let t = f64::from(i) / 10.0;
+            assert_close(cubic_bezier(0.0, 0.0, 1.0, 1.0, t), t);
+            assert_close(cubic_bezier(1.0 / 3.0, 1.0 / 3.0, 2.0 / 3.0, 2.0 / 3.0, t), t);
+        }
+    }
+
+    #[test]
+    fn css_ease_matches_reference() {
+        // cubic-bezier(0.25, 0.1, 0.25, 1.0) is CSS `ease`; at x = 0.5 it is ~0.8024.
+        let v = cubic_bezier(0.25, 0.1, 0.25, 1.0, 0.5);
+        assert!((v - 0.8024).abs() < 1e-3, "{v}");
+    }
+
+    #[test]
+    fn spring_settles_and_can_overshoot() {
+        assert_eq!(spring(180.0, 20.0, 0.0), 0.0);
+        assert_eq!(spring(180.0, 20.0, 1.0), 1.0);
+        assert!((spring(180.0, 20.0, 0.9) - 1.0).abs() < 1e-3);
+
+        let underdamped_peak =
+            (1..100).map(|i| spring(300.0, 5.0, f64::from(i) / 100.0)).fold(0.0, f64::max);
+        assert!(underdamped_peak > 1.0);
+
+        let critically_damped_peak =
+            (1..100).map(|i| spring(100.0, 20.0, f64::from(i) / 100.0)).fold(0.0, f64::max);
+        assert!(critically_damped_peak <= 1.0);
+    }
+}
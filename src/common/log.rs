@@ -33,8 +33,9 @@ fn timing_layer() -> TimingLayer {
         .layer(|| Histogram::new_with_max(100_000_000, 2).unwrap())
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, strum_macros::VariantNames)]
 #[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
 pub enum MetricsCommand {
     ShowTiming,
 }
@@ -1,26 +1,27 @@
 use std::ffi::{CStr, c_char};
-use std::time::Duration;
 
-use r#continue::continuation;
 use tracing::{debug, error, info};
 
 pub mod cli_exec;
+pub mod dispatcher;
 pub mod protocol;
+pub mod socket;
 pub mod subscriptions;
 
-pub use protocol::{RiftCommand, RiftRequest, RiftResponse};
+pub use dispatcher::RequestDispatcher;
+pub use protocol::{
+    ErrorCode, PROTOCOL_VERSION, RiftCommand, RiftRequest, RiftResponse, ServerInfo,
+};
+pub use socket::RiftSocketClient;
 
-use crate::actor::config as config_actor;
-use crate::actor::reactor::{self, Event};
-use crate::ipc::subscriptions::SharedServerState;
-use crate::sys::dispatch::block_on;
+use crate::actor::{config as config_actor, reactor};
+use crate::common::config::socket_file;
+use crate::ipc::subscriptions::{ClientId, SharedServerState};
 use crate::sys::mach::{
     is_mach_server_registered, mach_msg_header_t, mach_send_request, mach_server_run,
     send_mach_reply,
 };
 
-type ClientPort = u32;
-
 pub fn run_mach_server(
     reactor_tx: reactor::Sender,
     config_tx: config_actor::Sender,
@@ -38,15 +39,29 @@ pub fn run_mach_server(
 
     let thread_state = shared_state.clone();
     std::thread::spawn(move || {
-        let handler = MachHandler::new(reactor_tx, config_tx, thread_state.clone());
+        let dispatcher = RequestDispatcher::new(reactor_tx, config_tx, thread_state.clone());
         unsafe {
-            mach_server_run(Box::into_raw(Box::new(handler)) as *mut _, handle_mach_request_c);
+            mach_server_run(
+                Box::into_raw(Box::new(dispatcher)) as *mut _,
+                handle_mach_request_c,
+            );
         }
     });
 
     Ok(shared_state)
 }
 
+/// Serves the IPC protocol on the Unix socket at `~/.rift/rift.sock`, next to
+/// the Mach server whose subscription state it shares.
+pub fn run_socket_server(
+    reactor_tx: reactor::Sender,
+    config_tx: config_actor::Sender,
+    server_state: SharedServerState,
+) -> std::io::Result<()> {
+    let dispatcher = RequestDispatcher::new(reactor_tx, config_tx, server_state.clone());
+    socket::listen(&socket_file(), std::sync::Arc::new(dispatcher), server_state)
+}
+
 pub struct RiftMachClient {
     connected: bool,
 }
@@ -94,304 +109,6 @@ impl RiftMachClient {
     }
 }
 
-struct MachHandler {
-    reactor_tx: reactor::Sender,
-    config_tx: config_actor::Sender,
-    server_state: SharedServerState,
-}
-
-impl MachHandler {
-    fn new(
-        reactor_tx: reactor::Sender,
-        config_tx: config_actor::Sender,
-        server_state: SharedServerState,
-    ) -> Self {
-        Self {
-            reactor_tx,
-            config_tx,
-            server_state,
-        }
-    }
-
-    fn perform_query<T>(
-        &self,
-        make_event: impl FnOnce(r#continue::Sender<T>) -> Event,
-    ) -> Result<T, String>
-    where
-        T: Send + 'static,
-    {
-        let (cont_tx, cont_fut) = continuation::<T>();
-        let event = make_event(cont_tx);
-
-        if let Err(e) = self.reactor_tx.try_send(event) {
-            return Err(format!("Failed to send query: {}", e));
-        }
-
-        match block_on(cont_fut, Duration::from_secs(5)) {
-            Ok(res) => Ok(res),
-            Err(e) => Err(format!("Failed to get response: {}", e)),
-        }
-    }
-
-    fn perform_config_query<T>(
-        &self,
-        make_event: impl FnOnce(r#continue::Sender<T>) -> config_actor::Event,
-    ) -> Result<T, String>
-    where
-        T: Send + 'static,
-    {
-        let (cont_tx, cont_fut) = continuation::<T>();
-        let event = make_event(cont_tx);
-
-        if let Err(e) = self.config_tx.try_send(event) {
-            return Err(format!("Failed to send config query: {}", e));
-        }
-
-        match block_on(cont_fut, Duration::from_secs(5)) {
-            Ok(res) => Ok(res),
-            Err(e) => Err(format!("Failed to get response: {}", e)),
-        }
-    }
-
-    fn handle_request(&self, request: RiftRequest, client_port: ClientPort) -> RiftResponse {
-        debug!("Handling request: {:?} from client {}", request, client_port);
-
-        match request {
-            RiftRequest::Subscribe { event } => {
-                let state = self.server_state.read();
-                state.subscribe_client(client_port, event.clone());
-                RiftResponse::Success {
-                    data: serde_json::json!({ "subscribed": event }),
-                }
-            }
-            RiftRequest::Unsubscribe { event } => {
-                let state = self.server_state.read();
-                state.unsubscribe_client(client_port, event.clone());
-                RiftResponse::Success {
-                    data: serde_json::json!({ "unsubscribed": event }),
-                }
-            }
-            RiftRequest::SubscribeCli { event, command, args } => {
-                let state = self.server_state.read();
-                state.subscribe_cli(event.clone(), command.clone(), args.clone());
-                RiftResponse::Success {
-                    data: serde_json::json!({
-                        "cli_subscribed": event,
-                        "command": command,
-                        "args": args
-                    }),
-                }
-            }
-            RiftRequest::UnsubscribeCli { event } => {
-                let state = self.server_state.read();
-                state.unsubscribe_cli(event.clone());
-                RiftResponse::Success {
-                    data: serde_json::json!({ "cli_unsubscribed": event }),
-                }
-            }
-            RiftRequest::ListCliSubscriptions => {
-                let state = self.server_state.read();
-                let data = state.list_cli_subscriptions();
-                RiftResponse::Success { data }
-            }
-
-            RiftRequest::GetWorkspaces { space_id } => {
-                match self.perform_query(|tx| Event::QueryWorkspaces {
-                    space_id: space_id.map(crate::sys::screen::SpaceId::new),
-                    response: tx,
-                }) {
-                    Ok(workspaces) => RiftResponse::Success {
-                        data: serde_json::to_value(workspaces).unwrap(),
-                    },
-                    Err(e) => {
-                        error!("{}", e);
-                        RiftResponse::Error {
-                            error: serde_json::json!({ "message": "Failed to get workspace response", "details": format!("{}", e) }),
-                        }
-                    }
-                }
-            }
-
-            RiftRequest::GetDisplays => match self.perform_query(|tx| Event::QueryDisplays(tx)) {
-                Ok(displays) => RiftResponse::Success {
-                    data: serde_json::to_value(displays).unwrap(),
-                },
-                Err(e) => {
-                    error!("{}", e);
-                    RiftResponse::Error {
-                        error: serde_json::json!({ "message": "Failed to get displays response", "details": format!("{}", e) }),
-                    }
-                }
-            },
-
-            RiftRequest::GetWindows { space_id } => {
-                let space_id = space_id.map(|id| crate::sys::screen::SpaceId::new(id));
-
-                match self.perform_query(|tx| Event::QueryWindows { space_id, response: tx }) {
-                    Ok(windows) => RiftResponse::Success {
-                        data: serde_json::to_value(windows).unwrap(),
-                    },
-                    Err(e) => {
-                        error!("{}", e);
-                        RiftResponse::Error {
-                            error: serde_json::json!({ "message": "Failed to get windows response", "details": format!("{}", e) }),
-                        }
-                    }
-                }
-            }
-
-            RiftRequest::GetWindowInfo { window_id } => {
-                let window_id = match crate::actor::app::WindowId::from_debug_string(&window_id) {
-                    Some(wid) => wid,
-                    None => {
-                        error!("Invalid window_id format: {}", window_id);
-                        return RiftResponse::Error {
-                            error: serde_json::json!({ "message": "Invalid window_id format", "window_id": window_id }),
-                        };
-                    }
-                };
-
-                match self.perform_query(|tx| Event::QueryWindowInfo { window_id, response: tx }) {
-                    Ok(Some(window)) => RiftResponse::Success {
-                        data: serde_json::to_value(window).unwrap(),
-                    },
-                    Ok(None) => RiftResponse::Error {
-                        error: serde_json::json!({ "message": "Window not found" }),
-                    },
-                    Err(e) => {
-                        error!("{}", e);
-                        RiftResponse::Error {
-                            error: serde_json::json!({ "message": "Failed to get window info response", "details": format!("{}", e) }),
-                        }
-                    }
-                }
-            }
-
-            RiftRequest::GetLayoutState { space_id } => {
-                match self.perform_query(|tx| Event::QueryLayoutState { space_id, response: tx }) {
-                    Ok(Some(layout_state)) => RiftResponse::Success {
-                        data: serde_json::to_value(layout_state).unwrap(),
-                    },
-                    Ok(None) => RiftResponse::Error {
-                        error: serde_json::json!({ "message": "Space not found or inactive" }),
-                    },
-                    Err(e) => {
-                        error!("{}", e);
-                        RiftResponse::Error {
-                            error: serde_json::json!({ "message": "Failed to get layout state response", "details": format!("{}", e) }),
-                        }
-                    }
-                }
-            }
-
-            RiftRequest::GetApplications => {
-                match self.perform_query(|tx| Event::QueryApplications(tx)) {
-                    Ok(applications) => RiftResponse::Success {
-                        data: serde_json::to_value(applications).unwrap(),
-                    },
-                    Err(e) => {
-                        error!("{}", e);
-                        RiftResponse::Error {
-                            error: serde_json::json!({ "message": "Failed to get applications response", "details": format!("{}", e) }),
-                        }
-                    }
-                }
-            }
-
-            RiftRequest::GetMetrics => match self.perform_query(|tx| Event::QueryMetrics(tx)) {
-                Ok(metrics) => RiftResponse::Success { data: metrics },
-                Err(e) => {
-                    error!("{}", e);
-                    RiftResponse::Error {
-                        error: serde_json::json!({ "message": "Failed to get metrics response", "details": format!("{}", e) }),
-                    }
-                }
-            },
-
-            RiftRequest::GetConfig => {
-                match self.perform_config_query(|tx| config_actor::Event::QueryConfig(tx)) {
-                    Ok(config) => match serde_json::to_value(&config) {
-                        Ok(value) => RiftResponse::Success { data: value },
-                        Err(e) => {
-                            error!("Failed to serialize config: {}", e);
-                            RiftResponse::Error {
-                                error: serde_json::json!({ "message": "Failed to serialize config", "details": format!("{}", e) }),
-                            }
-                        }
-                    },
-                    Err(e) => {
-                        error!("{}", e);
-                        RiftResponse::Error {
-                            error: serde_json::json!({ "message": "Failed to get config response", "details": format!("{}", e) }),
-                        }
-                    }
-                }
-            }
-
-            RiftRequest::ExecuteCommand { command, args } => {
-                match serde_json::from_str::<RiftCommand>(&command) {
-                    Ok(RiftCommand::Config(_)) => {
-                        if args.len() >= 2 && args[0] == "__apply_config__" {
-                            match serde_json::from_str::<crate::common::config::ConfigCommand>(
-                                &args[1],
-                            ) {
-                                Ok(cfg_cmd) => match self.perform_config_query(|tx| {
-                                    config_actor::Event::ApplyConfig { cmd: cfg_cmd, response: tx }
-                                }) {
-                                    Ok(apply_result) => match apply_result {
-                                        Ok(()) => RiftResponse::Success {
-                                            data: serde_json::json!("Config applied successfully"),
-                                        },
-                                        Err(msg) => RiftResponse::Error {
-                                            error: serde_json::json!({ "message": msg }),
-                                        },
-                                    },
-                                    Err(e) => {
-                                        error!("{}", e);
-                                        RiftResponse::Error {
-                                            error: serde_json::json!({ "message": format!("Failed to apply config: {}", e) }),
-                                        }
-                                    }
-                                },
-                                Err(e) => {
-                                    error!("Failed to parse config command from args: {}", e);
-                                    RiftResponse::Error {
-                                        error: serde_json::json!({ "message": format!("Invalid config command in args: {}", e) }),
-                                    }
-                                }
-                            }
-                        } else {
-                            RiftResponse::Success {
-                                data: serde_json::json!("No-op config command"),
-                            }
-                        }
-                    }
-                    Ok(RiftCommand::Reactor(reactor_command)) => {
-                        let event = Event::Command(reactor_command);
-
-                        if let Err(e) = self.reactor_tx.try_send(event) {
-                            error!("Failed to send command to reactor: {}", e);
-                            return RiftResponse::Error {
-                                error: serde_json::json!({ "message": "Failed to execute command", "details": format!("{}", e) }),
-                            };
-                        }
-
-                        RiftResponse::Success {
-                            data: serde_json::json!("Command executed successfully"),
-                        }
-                    }
-                    Err(e) => {
-                        error!("Failed to parse command: {}", e);
-                        RiftResponse::Error {
-                            error: serde_json::json!({ "message": format!("Invalid command format: {}", e) }),
-                        }
-                    }
-                }
-            }
-        }
-    }
-}
-
 unsafe extern "C" fn handle_mach_request_c(
     context: *mut std::ffi::c_void,
     message: *mut c_char,
@@ -406,7 +123,7 @@ unsafe extern "C" fn handle_mach_request_c(
         return;
     }
 
-    let handler = unsafe { &*(context as *const MachHandler) };
+    let dispatcher = unsafe { &*(context as *const RequestDispatcher) };
     let message_slice = unsafe { std::slice::from_raw_parts(message as *const u8, len as usize) };
 
     let trimmed_slice = if let Some(pos) = message_slice.iter().position(|&b| b == 0) {
@@ -435,15 +152,16 @@ unsafe extern "C" fn handle_mach_request_c(
         Ok(req) => req,
         Err(e) => {
             error!("Failed to parse request: {}", e);
-            let error_response = RiftResponse::Error {
-                error: serde_json::json!({ "message": format!("Invalid request format: {}", e) }),
-            };
+            let error_response = RiftResponse::error(
+                ErrorCode::InvalidRequest,
+                format!("Invalid request format: {}", e),
+            );
             send_response(original_msg, &error_response);
             return;
         }
     };
 
-    let response = handler.handle_request(request, client_port);
+    let response = dispatcher.dispatch(request, ClientId::Mach(client_port));
     send_response(original_msg, &response);
 }
 
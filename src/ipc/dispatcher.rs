@@ -0,0 +1,873 @@
+//! Transport-independent handling of IPC requests.
+
+use std::time::Duration;
+
+use r#continue::continuation;
+use tracing::{debug, error, info, warn};
+
+use crate::actor::config as config_actor;
+use crate::actor::reactor::{self, Event};
+use crate::common::config::ConfigCommand;
+use crate::ipc::protocol::{
+    CommandResult, ErrorCode, PROTOCOL_VERSION, RiftCommand, RiftRequest, RiftResponse, ServerInfo,
+};
+use crate::ipc::socket::RequestHandler;
+use crate::ipc::subscriptions::{ClientId, SharedServerState};
+use crate::layout_engine::SavedLayoutStore;
+use crate::model::server::CommandEffect;
+use crate::sys::dispatch::block_on;
+
+/// How long a request waits for the reactor or config actor to answer.
+const QUERY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Why the reactor or config actor did not answer a request, or why it
+/// failed.
+#[derive(Debug)]
+struct DispatchError {
+    code: ErrorCode,
+    message: String,
+}
+
+impl DispatchError {
+    fn new(code: ErrorCode, message: String) -> Self { DispatchError { code, message } }
+}
+
+impl std::fmt::Display for DispatchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// Answers [`RiftRequest`]s by querying the reactor and config actor and
+/// updating subscription state. Transports parse requests, pass them here
+/// along with the [`ClientId`] they came from, and send back the response.
+pub struct RequestDispatcher {
+    reactor_tx: reactor::Sender,
+    config_tx: config_actor::Sender,
+    server_state: SharedServerState,
+    timeout: Duration,
+}
+
+impl RequestDispatcher {
+    pub fn new(
+        reactor_tx: reactor::Sender,
+        config_tx: config_actor::Sender,
+        server_state: SharedServerState,
+    ) -> Self {
+        Self {
+            reactor_tx,
+            config_tx,
+            server_state,
+            timeout: QUERY_TIMEOUT,
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    fn perform_query<T>(
+        &self,
+        make_event: impl FnOnce(r#continue::Sender<T>) -> Event,
+    ) -> Result<T, DispatchError>
+    where
+        T: Send + 'static,
+    {
+        let (cont_tx, cont_fut) = continuation::<T>();
+        let event = make_event(cont_tx);
+
+        if let Err(e) = self.reactor_tx.try_send(event) {
+            return Err(DispatchError::new(
+                ErrorCode::Unavailable,
+                format!("Failed to send query: {}", e),
+            ));
+        }
+
+        block_on(cont_fut, self.timeout).map_err(|e| {
+            DispatchError::new(ErrorCode::Timeout, format!("Failed to get response: {}", e))
+        })
+    }
+
+    fn perform_config_query<T>(
+        &self,
+        make_event: impl FnOnce(r#continue::Sender<T>) -> config_actor::Event,
+    ) -> Result<T, DispatchError>
+    where
+        T: Send + 'static,
+    {
+        let (cont_tx, cont_fut) = continuation::<T>();
+        let event = make_event(cont_tx);
+
+        if let Err(e) = self.config_tx.try_send(event) {
+            return Err(DispatchError::new(
+                ErrorCode::Unavailable,
+                format!("Failed to send config query: {}", e),
+            ));
+        }
+
+        block_on(cont_fut, self.timeout).map_err(|e| {
+            DispatchError::new(ErrorCode::Timeout, format!("Failed to get response: {}", e))
+        })
+    }
+
+    /// Runs a batch of reactor commands as one [`Event::CommandBatch`]. Config
+    /// commands are rejected: the reactor only sees them as a config update of
+    /// its own, which would lay out the windows in the middle of the batch.
+    fn execute_batch(
+        &self,
+        commands: Vec<RiftCommand>,
+        abort_on_error: bool,
+    ) -> Result<Vec<CommandResult>, DispatchError> {
+        let commands = commands
+            .into_iter()
+            .map(|command| match command {
+                RiftCommand::Reactor(command) => Ok(command),
+                RiftCommand::Config(_) => Err(DispatchError::new(
+                    ErrorCode::InvalidRequest,
+                    "Config commands cannot be batched; send them with execute".to_string(),
+                )),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(self.execute_reactor_commands(commands, abort_on_error))
+    }
+
+    fn execute_reactor_commands(
+        &self,
+        commands: Vec<reactor::Command>,
+        abort_on_error: bool,
+    ) -> Vec<CommandResult> {
+        let count = commands.len();
+        let mut results: Vec<_> = match self.perform_query(|tx| Event::CommandBatch {
+            commands,
+            abort_on_error,
+            response: tx,
+        }) {
+            Ok(results) => results
+                .into_iter()
+                .map(|result| match result {
+                    Ok(effect) => CommandResult::Ok { effect },
+                    Err(message) => CommandResult::Failed {
+                        code: ErrorCode::Failed,
+                        message,
+                    },
+                })
+                .collect(),
+            Err(e) => {
+                error!("{}", e);
+                let failed = CommandResult::Failed {
+                    code: e.code,
+                    message: e.to_string(),
+                };
+                vec![failed; count]
+            }
+        };
+        results.resize(count, CommandResult::Skipped);
+        results
+    }
+
+    fn execute_reactor_command(
+        &self,
+        command: reactor::Command,
+    ) -> Result<CommandEffect, DispatchError> {
+        let mut results = self.perform_query(|tx| Event::CommandBatch {
+            commands: vec![command],
+            abort_on_error: true,
+            response: tx,
+        })?;
+        match results.pop() {
+            Some(Ok(effect)) => Ok(effect),
+            Some(Err(message)) => Err(DispatchError::new(ErrorCode::Failed, message)),
+            None => Err(DispatchError::new(
+                ErrorCode::Failed,
+                "Command did not run".into(),
+            )),
+        }
+    }
+
+    fn apply_config_command(&self, cmd: ConfigCommand) -> Result<(), DispatchError> {
+        self.perform_config_query(|tx| config_actor::Event::ApplyConfig { cmd, response: tx })?
+            .map_err(|message| DispatchError::new(ErrorCode::Failed, message))
+    }
+
+    pub fn dispatch(&self, request: RiftRequest, client: ClientId) -> RiftResponse {
+        debug!("Handling request: {:?} from client {}", request, client);
+
+        match request {
+            RiftRequest::Hello { client_version } => {
+                if client_version != PROTOCOL_VERSION {
+                    info!(
+                        "Client {} speaks protocol version {}, server speaks {}",
+                        client, client_version, PROTOCOL_VERSION
+                    );
+                }
+                RiftResponse::Success {
+                    data: serde_json::to_value(ServerInfo::current()).unwrap(),
+                }
+            }
+            RiftRequest::Subscribe { event } => {
+                let state = self.server_state.read();
+                state.subscribe_client(client, event.clone());
+                RiftResponse::Success {
+                    data: serde_json::json!({ "subscribed": event }),
+                }
+            }
+            RiftRequest::Unsubscribe { event } => {
+                let state = self.server_state.read();
+                state.unsubscribe_client(client, event.clone());
+                RiftResponse::Success {
+                    data: serde_json::json!({ "unsubscribed": event }),
+                }
+            }
+            RiftRequest::SubscribeCli { event, command, args } => {
+                let state = self.server_state.read();
+                state.subscribe_cli(event.clone(), command.clone(), args.clone());
+                RiftResponse::Success {
+                    data: serde_json::json!({
+                        "cli_subscribed": event,
+                        "command": command,
+                        "args": args
+                    }),
+                }
+            }
+            RiftRequest::UnsubscribeCli { event } => {
+                let state = self.server_state.read();
+                state.unsubscribe_cli(event.clone());
+                RiftResponse::Success {
+                    data: serde_json::json!({ "cli_unsubscribed": event }),
+                }
+            }
+            RiftRequest::ListCliSubscriptions => {
+                let state = self.server_state.read();
+                let data = state.list_cli_subscriptions();
+                RiftResponse::Success { data }
+            }
+
+            RiftRequest::GetWorkspaces { space_id } => {
+                match self.perform_query(|tx| Event::QueryWorkspaces {
+                    space_id: space_id.map(crate::sys::screen::SpaceId::new),
+                    response: tx,
+                }) {
+                    Ok(workspaces) => RiftResponse::Success {
+                        data: serde_json::to_value(workspaces).unwrap(),
+                    },
+                    Err(e) => {
+                        error!("{}", e);
+                        RiftResponse::error_with_details(
+                            e.code,
+                            "Failed to get workspace response",
+                            e,
+                        )
+                    }
+                }
+            }
+
+            RiftRequest::GetDisplays => match self.perform_query(|tx| Event::QueryDisplays(tx)) {
+                Ok(displays) => RiftResponse::Success {
+                    data: serde_json::to_value(displays).unwrap(),
+                },
+                Err(e) => {
+                    error!("{}", e);
+                    RiftResponse::error_with_details(e.code, "Failed to get displays response", e)
+                }
+            },
+
+            RiftRequest::GetWindows { space_id } => {
+                let space_id = space_id.map(|id| crate::sys::screen::SpaceId::new(id));
+
+                match self.perform_query(|tx| Event::QueryWindows { space_id, response: tx }) {
+                    Ok(windows) => RiftResponse::Success {
+                        data: serde_json::to_value(windows).unwrap(),
+                    },
+                    Err(e) => {
+                        error!("{}", e);
+                        RiftResponse::error_with_details(
+                            e.code,
+                            "Failed to get windows response",
+                            e,
+                        )
+                    }
+                }
+            }
+
+            RiftRequest::GetWindowInfo { window_id } => {
+                let window_id = match crate::actor::app::WindowId::from_debug_string(&window_id) {
+                    Some(wid) => wid,
+                    None => {
+                        error!("Invalid window_id format: {}", window_id);
+                        return RiftResponse::error_with_details(
+                            ErrorCode::InvalidArgument,
+                            "Invalid window_id format",
+                            window_id,
+                        );
+                    }
+                };
+
+                match self.perform_query(|tx| Event::QueryWindowInfo { window_id, response: tx }) {
+                    Ok(Some(window)) => RiftResponse::Success {
+                        data: serde_json::to_value(window).unwrap(),
+                    },
+                    Ok(None) => RiftResponse::error(ErrorCode::NotFound, "Window not found"),
+                    Err(e) => {
+                        error!("{}", e);
+                        RiftResponse::error_with_details(
+                            e.code,
+                            "Failed to get window info response",
+                            e,
+                        )
+                    }
+                }
+            }
+
+            RiftRequest::GetLayoutState { space_id } => {
+                match self.perform_query(|tx| Event::QueryLayoutState { space_id, response: tx }) {
+                    Ok(Some(layout_state)) => RiftResponse::Success {
+                        data: serde_json::to_value(layout_state).unwrap(),
+                    },
+                    Ok(None) => {
+                        RiftResponse::error(ErrorCode::NotFound, "Space not found or inactive")
+                    }
+                    Err(e) => {
+                        error!("{}", e);
+                        RiftResponse::error_with_details(
+                            e.code,
+                            "Failed to get layout state response",
+                            e,
+                        )
+                    }
+                }
+            }
+
+            RiftRequest::GetLayoutTree { space_id, workspace } => {
+                match self.perform_query(|tx| Event::QueryLayoutTree {
+                    space_id: space_id.map(crate::sys::screen::SpaceId::new),
+                    workspace,
+                    response: tx,
+                }) {
+                    Ok(Some(tree)) => RiftResponse::Success {
+                        data: serde_json::to_value(tree).unwrap(),
+                    },
+                    Ok(None) => RiftResponse::error(ErrorCode::NotFound, "Workspace not found"),
+                    Err(e) => {
+                        error!("{}", e);
+                        RiftResponse::error_with_details(
+                            e.code,
+                            "Failed to get layout tree response",
+                            e,
+                        )
+                    }
+                }
+            }
+
+            RiftRequest::ApplyLayoutTree { space_id, workspace, tree } => {
+                match self.perform_query(|tx| Event::ApplyLayoutTree {
+                    space_id: space_id.map(crate::sys::screen::SpaceId::new),
+                    workspace,
+                    tree,
+                    response: tx,
+                }) {
+                    Ok(Ok(())) => RiftResponse::Success {
+                        data: serde_json::json!("Layout tree applied"),
+                    },
+                    Ok(Err(message)) => RiftResponse::error(ErrorCode::Failed, message),
+                    Err(e) => {
+                        error!("{}", e);
+                        RiftResponse::error_with_details(e.code, "Failed to apply layout tree", e)
+                    }
+                }
+            }
+
+            RiftRequest::ListSavedLayouts => match SavedLayoutStore::default().list() {
+                Ok(names) => RiftResponse::Success {
+                    data: serde_json::to_value(names).unwrap(),
+                },
+                Err(e) => RiftResponse::error_with_details(
+                    ErrorCode::Failed,
+                    "Failed to list saved layouts",
+                    format!("{:#}", e),
+                ),
+            },
+
+            RiftRequest::GetApplications => {
+                match self.perform_query(|tx| Event::QueryApplications(tx)) {
+                    Ok(applications) => RiftResponse::Success {
+                        data: serde_json::to_value(applications).unwrap(),
+                    },
+                    Err(e) => {
+                        error!("{}", e);
+                        RiftResponse::error_with_details(
+                            e.code,
+                            "Failed to get applications response",
+                            e,
+                        )
+                    }
+                }
+            }
+
+            RiftRequest::GetMetrics => match self.perform_query(|tx| Event::QueryMetrics(tx)) {
+                Ok(metrics) => RiftResponse::Success { data: metrics },
+                Err(e) => {
+                    error!("{}", e);
+                    RiftResponse::error_with_details(e.code, "Failed to get metrics response", e)
+                }
+            },
+
+            RiftRequest::GetConfig => {
+                match self.perform_config_query(|tx| config_actor::Event::QueryConfig(tx)) {
+                    Ok(config) => match serde_json::to_value(&config) {
+                        Ok(value) => RiftResponse::Success { data: value },
+                        Err(e) => {
+                            error!("Failed to serialize config: {}", e);
+                            RiftResponse::error_with_details(
+                                ErrorCode::Failed,
+                                "Failed to serialize config",
+                                e,
+                            )
+                        }
+                    },
+                    Err(e) => {
+                        error!("{}", e);
+                        RiftResponse::error_with_details(e.code, "Failed to get config response", e)
+                    }
+                }
+            }
+
+            RiftRequest::ExecuteBatch { commands, abort_on_error } => {
+                match self.execute_batch(commands, abort_on_error) {
+                    Ok(results) => RiftResponse::Success {
+                        data: serde_json::to_value(results).unwrap(),
+                    },
+                    Err(e) => RiftResponse::error(e.code, e.message),
+                }
+            }
+
+            RiftRequest::Execute {
+                command: RiftCommand::Reactor(command),
+            } => match self.execute_reactor_command(command) {
+                Ok(effect) => RiftResponse::Success {
+                    data: serde_json::to_value(effect).unwrap(),
+                },
+                Err(e) => {
+                    error!("Failed to execute command: {}", e);
+                    RiftResponse::error(e.code, e.message)
+                }
+            },
+
+            RiftRequest::Execute {
+                command: RiftCommand::Config(cmd),
+            } => match self.apply_config_command(cmd.clone()) {
+                Ok(()) => RiftResponse::Success {
+                    data: serde_json::json!({ "config": cmd }),
+                },
+                Err(e) => {
+                    error!("Failed to apply config: {}", e);
+                    RiftResponse::error(e.code, e.message)
+                }
+            },
+
+            RiftRequest::ExecuteCommand { command, args } => {
+                match serde_json::from_str::<RiftCommand>(&command) {
+                    Ok(RiftCommand::Config(cmd)) => {
+                        let cmd = match args.as_slice() {
+                            [marker, cfg_json, ..] if marker == "__apply_config__" => {
+                                warn!(
+                                    "The __apply_config__ argument is deprecated; send an `execute` request instead"
+                                );
+                                match serde_json::from_str::<ConfigCommand>(cfg_json) {
+                                    Ok(cmd) => cmd,
+                                    Err(e) => {
+                                        error!("Failed to parse config command from args: {}", e);
+                                        return RiftResponse::error(
+                                            ErrorCode::InvalidCommand,
+                                            format!("Invalid config command in args: {}", e),
+                                        );
+                                    }
+                                }
+                            }
+                            _ => cmd,
+                        };
+                        match self.apply_config_command(cmd) {
+                            Ok(()) => RiftResponse::Success {
+                                data: serde_json::json!("Config applied successfully"),
+                            },
+                            Err(e) => {
+                                error!("Failed to apply config: {}", e);
+                                RiftResponse::error(e.code, e.message)
+                            }
+                        }
+                    }
+                    Ok(RiftCommand::Reactor(reactor_command)) => {
+                        let event = Event::Command(reactor_command);
+
+                        if let Err(e) = self.reactor_tx.try_send(event) {
+                            error!("Failed to send command to reactor: {}", e);
+                            return RiftResponse::error_with_details(
+                                ErrorCode::Unavailable,
+                                "Failed to execute command",
+                                e,
+                            );
+                        }
+
+                        RiftResponse::Success {
+                            data: serde_json::json!("Command executed successfully"),
+                        }
+                    }
+                    Err(e) => {
+                        error!("Failed to parse command: {}", e);
+                        RiftResponse::error(
+                            ErrorCode::InvalidCommand,
+                            format!("Invalid command format: {}", e),
+                        )
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl RequestHandler for RequestDispatcher {
+    fn handle_request(&self, request: RiftRequest, client: ClientId) -> RiftResponse {
+        self.dispatch(request, client)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader};
+    use std::os::unix::net::UnixStream;
+    use std::sync::{Arc, mpsc};
+
+    use parking_lot::{Mutex, RwLock};
+    use serde_json::json;
+
+    use super::*;
+    use crate::actor;
+    use crate::actor::app::WindowId;
+    use crate::actor::broadcast::BroadcastEvent;
+    use crate::actor::reactor::{Command, ReactorCommand};
+    use crate::ipc::socket::SocketEvents;
+    use crate::ipc::subscriptions::ServerState;
+    use crate::layout_engine::LayoutCommand;
+    use crate::model::server::ActiveWorkspaceData;
+    use crate::sys::screen::SpaceId;
+
+    const CLIENT: ClientId = ClientId::Socket(1);
+
+    /// Stands in for an actor: every event sent on the returned channel is
+    /// passed to `handle` on another thread.
+    fn fake_actor<E: Send + 'static>(
+        mut handle: impl FnMut(E) + Send + 'static,
+    ) -> actor::Sender<E> {
+        let (tx, mut rx) = actor::channel();
+        std::thread::spawn(move || {
+            while let Some((_, event)) = rx.blocking_recv() {
+                handle(event);
+            }
+        });
+        tx
+    }
+
+    fn dispatcher(reactor: impl FnMut(Event) + Send + 'static) -> RequestDispatcher {
+        RequestDispatcher::new(
+            fake_actor(reactor),
+            fake_actor(|_: config_actor::Event| {}),
+            Arc::new(RwLock::new(ServerState::new())),
+        )
+    }
+
+    fn effect() -> CommandEffect {
+        CommandEffect {
+            focused_window: Some(WindowId::new(1, 2)),
+            focus_changed: true,
+            workspace: Some(ActiveWorkspaceData {
+                id: "VirtualWorkspaceId(1v1)".into(),
+                index: Some(0),
+                name: "main".into(),
+            }),
+            workspace_changed: false,
+        }
+    }
+
+    fn data(response: RiftResponse) -> serde_json::Value {
+        match response {
+            RiftResponse::Success { data } => data,
+            other => panic!("unexpected response {other:?}"),
+        }
+    }
+
+    fn error(response: RiftResponse) -> serde_json::Value {
+        match response {
+            RiftResponse::Error { error } => error,
+            other => panic!("unexpected response {other:?}"),
+        }
+    }
+
+    #[test]
+    fn hello_reports_the_protocol() {
+        let dispatcher = dispatcher(|_| {});
+        let info = data(dispatcher.dispatch(RiftRequest::Hello { client_version: 1 }, CLIENT));
+        assert_eq!(info["protocol_version"], PROTOCOL_VERSION);
+        assert!(info["requests"].as_array().unwrap().contains(&json!("get_workspaces")));
+        assert!(info["events"].as_array().unwrap().contains(&json!("workspace_changed")));
+    }
+
+    #[test]
+    fn queries_are_answered_by_the_reactor() {
+        let dispatcher = dispatcher(|event| match event {
+            Event::QueryWorkspaces { space_id, response } => {
+                assert_eq!(space_id, Some(SpaceId::new(3)));
+                response.send(Vec::new());
+            }
+            Event::QueryLayoutTree { response, .. } => response.send(None),
+            _ => {}
+        });
+
+        let workspaces =
+            dispatcher.dispatch(RiftRequest::GetWorkspaces { space_id: Some(3) }, CLIENT);
+        assert_eq!(data(workspaces), json!([]));
+
+        let tree = dispatcher.dispatch(
+            RiftRequest::GetLayoutTree {
+                space_id: None,
+                workspace: Some(1),
+            },
+            CLIENT,
+        );
+        let tree = error(tree);
+        assert_eq!(tree["code"], "not_found");
+        assert_eq!(tree["message"], "Workspace not found");
+    }
+
+    #[test]
+    fn unanswered_queries_time_out() {
+        let mut pending = Vec::new();
+        let dispatcher =
+            dispatcher(move |event| pending.push(event)).with_timeout(Duration::from_millis(50));
+
+        let response = error(dispatcher.dispatch(RiftRequest::GetDisplays, CLIENT));
+        assert_eq!(response["code"], "timeout");
+        assert_eq!(response["message"], "Failed to get displays response");
+        assert!(response["details"].as_str().unwrap().contains("Timeout"));
+    }
+
+    #[test]
+    fn invalid_window_ids_are_rejected() {
+        let dispatcher = dispatcher(|_| panic!("the reactor should not be queried"));
+        let response = dispatcher.dispatch(
+            RiftRequest::GetWindowInfo {
+                window_id: "not a window".into(),
+            },
+            CLIENT,
+        );
+        assert_eq!(error(response)["code"], "invalid_argument");
+    }
+
+    #[test]
+    fn reactor_commands_are_forwarded() {
+        let (tx, rx) = mpsc::channel();
+        let dispatcher = dispatcher(move |event| {
+            if let Event::Command(command) = event {
+                tx.send(command).unwrap();
+            }
+        });
+
+        let command = RiftCommand::Reactor(Command::Reactor(ReactorCommand::Debug));
+        let response = dispatcher.dispatch(
+            RiftRequest::ExecuteCommand {
+                command: serde_json::to_string(&command).unwrap(),
+                args: Vec::new(),
+            },
+            CLIENT,
+        );
+        assert_eq!(data(response), json!("Command executed successfully"));
+        assert_eq!(
+            rx.recv_timeout(Duration::from_secs(1)).unwrap(),
+            Command::Reactor(ReactorCommand::Debug)
+        );
+
+        let response = dispatcher.dispatch(
+            RiftRequest::ExecuteCommand {
+                command: "{}".into(),
+                args: Vec::new(),
+            },
+            CLIENT,
+        );
+        assert_eq!(error(response)["code"], "invalid_command");
+    }
+
+    #[test]
+    fn config_commands_are_applied_by_the_config_actor() {
+        let dispatcher = RequestDispatcher::new(
+            fake_actor(|_: Event| {}),
+            fake_actor(|event| {
+                if let config_actor::Event::ApplyConfig { cmd, response } = event {
+                    match cmd {
+                        ConfigCommand::SetAnimate(false) => response.send(Ok(())),
+                        _ => response.send(Err("unexpected command".into())),
+                    }
+                }
+            }),
+            Arc::new(RwLock::new(ServerState::new())),
+        );
+
+        let cmd = ConfigCommand::SetAnimate(false);
+        let response = dispatcher.dispatch(
+            RiftRequest::ExecuteCommand {
+                command: serde_json::to_string(&RiftCommand::Config(cmd.clone())).unwrap(),
+                args: vec![
+                    "__apply_config__".into(),
+                    serde_json::to_string(&cmd).unwrap(),
+                ],
+            },
+            CLIENT,
+        );
+        assert_eq!(data(response), json!("Config applied successfully"));
+    }
+
+    #[test]
+    fn execute_answers_with_the_effect() {
+        let dispatcher = RequestDispatcher::new(
+            fake_actor(|event| {
+                if let Event::CommandBatch {
+                    commands,
+                    abort_on_error,
+                    response,
+                } = event
+                {
+                    assert_eq!(commands, vec![Command::Layout(LayoutCommand::NextWindow)]);
+                    assert!(abort_on_error);
+                    response.send(vec![Ok(effect())]);
+                }
+            }),
+            fake_actor(|event| {
+                if let config_actor::Event::ApplyConfig { response, .. } = event {
+                    response.send(Err("animations are locked".into()));
+                }
+            }),
+            Arc::new(RwLock::new(ServerState::new())),
+        );
+
+        let command = RiftCommand::Reactor(Command::Layout(LayoutCommand::NextWindow));
+        let response = dispatcher.dispatch(RiftRequest::Execute { command }, CLIENT);
+        assert_eq!(data(response), serde_json::to_value(effect()).unwrap());
+
+        let command = RiftCommand::Config(ConfigCommand::SetAnimate(true));
+        let response = error(dispatcher.dispatch(RiftRequest::Execute { command }, CLIENT));
+        assert_eq!(response["code"], "failed");
+        assert_eq!(response["message"], "animations are locked");
+    }
+
+    #[test]
+    fn batches_run_reactor_commands_together() {
+        let debug = || RiftCommand::Reactor(Command::Reactor(ReactorCommand::Debug));
+        let commands = vec![
+            debug(),
+            RiftCommand::Reactor(Command::Reactor(ReactorCommand::DeleteLayout {
+                name: "missing".into(),
+            })),
+            debug(),
+            debug(),
+        ];
+        let run = |abort_on_error| {
+            let (tx, rx) = mpsc::channel();
+            let dispatcher = RequestDispatcher::new(
+                fake_actor(move |event| {
+                    let Event::CommandBatch {
+                        commands,
+                        abort_on_error,
+                        response,
+                    } = event
+                    else {
+                        return;
+                    };
+                    tx.send(commands.len()).unwrap();
+                    let mut results = Vec::new();
+                    for command in commands {
+                        let failed = matches!(
+                            command,
+                            Command::Reactor(ReactorCommand::DeleteLayout { .. })
+                        );
+                        results.push(if failed {
+                            Err("no such layout".into())
+                        } else {
+                            Ok(effect())
+                        });
+                        if failed && abort_on_error {
+                            break;
+                        }
+                    }
+                    response.send(results);
+                }),
+                fake_actor(|_: config_actor::Event| {}),
+                Arc::new(RwLock::new(ServerState::new())),
+            );
+            let request = RiftRequest::ExecuteBatch {
+                commands: commands.clone(),
+                abort_on_error,
+            };
+            let results = data(dispatcher.dispatch(request, CLIENT));
+            drop(dispatcher);
+            (results, rx.iter().collect::<Vec<_>>())
+        };
+
+        let ok = json!({ "status": "ok", "effect": effect() });
+        let failed = json!({ "status": "failed", "code": "failed", "message": "no such layout" });
+        let (results, batches) = run(false);
+        assert_eq!(results, json!([ok, failed, ok, ok]));
+        assert_eq!(batches, vec![4]);
+
+        let (results, batches) = run(true);
+        assert_eq!(
+            results,
+            json!([ok, failed, { "status": "skipped" }, { "status": "skipped" }])
+        );
+        assert_eq!(batches, vec![4]);
+    }
+
+    #[test]
+    fn batches_reject_config_commands() {
+        let dispatcher = dispatcher(|_| panic!("nothing in the batch should run"));
+        let request = RiftRequest::ExecuteBatch {
+            commands: vec![
+                RiftCommand::Reactor(Command::Reactor(ReactorCommand::Debug)),
+                RiftCommand::Config(ConfigCommand::SetAnimate(false)),
+            ],
+            abort_on_error: false,
+        };
+        assert_eq!(
+            error(dispatcher.dispatch(request, CLIENT))["code"],
+            "invalid_request"
+        );
+    }
+
+    #[test]
+    fn subscriptions_deliver_events_to_the_client() {
+        let dispatcher = dispatcher(|_| {});
+        let (ours, theirs) = UnixStream::pair().unwrap();
+        let events =
+            SocketEvents::spawn(1, Arc::new(Mutex::new(ours)), dispatcher.server_state.clone())
+                .unwrap();
+        dispatcher.server_state.read().register_socket_client(1, events);
+
+        let response = dispatcher.dispatch(
+            RiftRequest::Subscribe {
+                event: "windows_changed".into(),
+            },
+            CLIENT,
+        );
+        assert_eq!(data(response), json!({ "subscribed": "windows_changed" }));
+
+        dispatcher.server_state.read().publish(BroadcastEvent::WindowsChanged {
+            workspace_id: Default::default(),
+            workspace_name: "main".into(),
+            windows: Vec::new(),
+            space_id: SpaceId::new(1),
+            display_uuid: None,
+        });
+        let mut line = String::new();
+        BufReader::new(theirs).read_line(&mut line).unwrap();
+        let message: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(message["event"]["type"], "windows_changed");
+        assert_eq!(message["event"]["workspace_name"], "main");
+    }
+}
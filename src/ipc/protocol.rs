@@ -1,10 +1,30 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use strum::VariantNames;
+
+use crate::actor::broadcast::BroadcastEvent;
+use crate::actor::reactor::ReactorCommand;
+use crate::common::config::ConfigCommand;
+use crate::common::log::MetricsCommand;
+use crate::layout_engine::LayoutCommand;
+use crate::model::server::CommandEffect;
+
+/// Version of the IPC protocol. It is raised when existing requests,
+/// responses or events change incompatibly; additions are reported by
+/// `hello` instead.
+pub const PROTOCOL_VERSION: u32 = 1;
 
 #[non_exhaustive]
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, strum_macros::VariantNames)]
 #[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
 pub enum RiftRequest {
+    /// Reports the protocol version and what the server supports as a
+    /// [`ServerInfo`]. `client_version` is the protocol version the client
+    /// speaks.
+    Hello {
+        client_version: u32,
+    },
     GetWorkspaces {
         space_id: Option<u64>,
     },
@@ -18,13 +38,50 @@ pub enum RiftRequest {
     GetLayoutState {
         space_id: u64,
     },
+    /// Layout of a workspace as a [`LayoutTree`](crate::layout_engine::LayoutTree).
+    /// `workspace` is the workspace's index on the space; both default to the
+    /// active one.
+    GetLayoutTree {
+        space_id: Option<u64>,
+        workspace: Option<usize>,
+    },
+    /// Rebuilds the layout of a workspace from a tree returned by
+    /// `get_layout_tree`, possibly edited.
+    ApplyLayoutTree {
+        space_id: Option<u64>,
+        workspace: Option<usize>,
+        tree: crate::layout_engine::LayoutTree,
+    },
     GetApplications,
+    /// Names of the layouts saved with the `save_layout` command.
+    ListSavedLayouts,
     GetMetrics,
     GetConfig,
+    /// Runs `command` and answers once it has run. Reactor commands answer
+    /// with a [`CommandEffect`](crate::model::server::CommandEffect) telling
+    /// which window is focused and which workspace is active afterwards;
+    /// config commands answer with the command that was applied.
+    Execute {
+        command: RiftCommand,
+    },
+    /// Deprecated: use `execute`. `command` is a JSON-encoded [`RiftCommand`].
+    /// Reactor commands are queued without waiting for them to run. Config
+    /// commands used to be repeated in `args` after an `__apply_config__`
+    /// marker, which is still accepted.
     ExecuteCommand {
         command: String,
         args: Vec<String>,
     },
+    /// Runs `commands` in order and answers with a [`CommandResult`] for
+    /// each. They run together, so the layout is updated once after the last
+    /// of them. With `abort_on_error`, the commands after the first failure
+    /// are skipped. Config commands cannot be batched and make the whole
+    /// request fail with `invalid_request`.
+    ExecuteBatch {
+        commands: Vec<RiftCommand>,
+        #[serde(default)]
+        abort_on_error: bool,
+    },
     Subscribe {
         event: String,
     },
@@ -46,12 +103,157 @@ pub enum RiftRequest {
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(untagged)]
 pub enum RiftResponse {
-    Success { data: Value },
-    Error { error: Value },
+    Success {
+        data: Value,
+    },
+    /// `error` holds a `code` (see [`ErrorCode`]) and a human-readable
+    /// `message`, and may hold `details` or other fields describing it.
+    Error {
+        error: Value,
+    },
+}
+
+impl RiftResponse {
+    pub fn error(code: ErrorCode, message: impl Into<String>) -> Self {
+        RiftResponse::Error {
+            error: serde_json::json!({ "code": code, "message": message.into() }),
+        }
+    }
+
+    pub fn error_with_details(
+        code: ErrorCode,
+        message: impl Into<String>,
+        details: impl std::fmt::Display,
+    ) -> Self {
+        RiftResponse::Error {
+            error: serde_json::json!({
+                "code": code,
+                "message": message.into(),
+                "details": details.to_string(),
+            }),
+        }
+    }
+}
+
+/// Machine-readable reason a request failed, sent as `error.code`. Unlike
+/// the message, codes do not change between releases.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// The request could not be parsed.
+    InvalidRequest,
+    /// The command of an `execute_command` request could not be parsed.
+    InvalidCommand,
+    /// An argument of the request is malformed.
+    InvalidArgument,
+    /// The window, workspace or space the request refers to does not exist.
+    NotFound,
+    /// Rift did not answer in time.
+    Timeout,
+    /// The part of rift that handles the request is not running.
+    Unavailable,
+    /// The request was understood but could not be carried out.
+    Failed,
+}
+
+/// Outcome of one command of an `execute_batch` request.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum CommandResult {
+    /// Focus and workspace after the command ran, as for `execute`.
+    Ok {
+        effect: CommandEffect,
+    },
+    Failed {
+        code: ErrorCode,
+        message: String,
+    },
+    /// Not run because an earlier command failed.
+    Skipped,
+}
+
+/// Answer to a `hello` request.
+#[derive(Serialize, Debug)]
+pub struct ServerInfo {
+    pub protocol_version: u32,
+    pub server_version: &'static str,
+    pub requests: &'static [&'static str],
+    pub commands: CommandNames,
+    pub events: &'static [&'static str],
+}
+
+/// Names of the commands accepted by `execute_command`, by [`RiftCommand`]
+/// variant.
+#[derive(Serialize, Debug)]
+pub struct CommandNames {
+    pub reactor: Vec<&'static str>,
+    pub config: &'static [&'static str],
+}
+
+impl ServerInfo {
+    pub fn current() -> Self {
+        ServerInfo {
+            protocol_version: PROTOCOL_VERSION,
+            server_version: env!("CARGO_PKG_VERSION"),
+            requests: RiftRequest::VARIANTS,
+            commands: CommandNames {
+                reactor: [
+                    LayoutCommand::VARIANTS,
+                    MetricsCommand::VARIANTS,
+                    ReactorCommand::VARIANTS,
+                ]
+                .concat(),
+                config: ConfigCommand::VARIANTS,
+            },
+            events: BroadcastEvent::VARIANTS,
+        }
+    }
+}
+
+/// One line written to a Unix socket client: either the response to its last
+/// request or an event it subscribed to.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(untagged)]
+pub enum SocketMessage {
+    Event { event: BroadcastEvent },
+    Response(RiftResponse),
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum RiftCommand {
     Reactor(crate::actor::reactor::Command),
-    Config(crate::common::config::ConfigCommand),
+    Config(ConfigCommand),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn server_info_uses_wire_names() {
+        let info = ServerInfo::current();
+        let hello = serde_json::to_value(RiftRequest::Hello { client_version: 1 }).unwrap();
+        assert!(hello.get("hello").is_some());
+        assert!(info.requests.contains(&"hello"));
+        assert!(info.requests.contains(&"get_layout_tree"));
+        assert!(info.requests.contains(&"list_cli_subscriptions"));
+        assert!(info.commands.reactor.contains(&"move_focus"));
+        assert!(info.commands.reactor.contains(&"show_timing"));
+        assert!(info.commands.reactor.contains(&"save_layout"));
+        assert!(info.commands.config.contains(&"set_animate"));
+        assert!(info.events.contains(&"windows_changed"));
+    }
+
+    #[test]
+    fn errors_carry_a_code() {
+        let RiftResponse::Error { error } =
+            RiftResponse::error_with_details(ErrorCode::NotFound, "Window not found", "gone")
+        else {
+            panic!()
+        };
+        assert_eq!(
+            error,
+            serde_json::json!({ "code": "not_found", "message": "Window not found", "details": "gone" })
+        );
+    }
 }
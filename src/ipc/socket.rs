@@ -0,0 +1,418 @@
+//! Newline-delimited JSON transport over a Unix domain socket.
+//!
+//! Each line a client writes is one [`RiftRequest`] and is answered with one
+//! line holding a [`RiftResponse`]. Clients that subscribed to events with a
+//! `subscribe` request also receive `{"event": {...}}` lines, written as the
+//! events happen; see [`SocketMessage`]. Events are written by a thread of
+//! each client's own, so a client that stops reading only holds up itself and
+//! is disconnected once it falls too far behind.
+
+use std::collections::VecDeque;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::Shutdown;
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use tracing::{debug, error, info, warn};
+
+use crate::actor::broadcast::BroadcastEvent;
+use crate::ipc::protocol::{ErrorCode, RiftRequest, RiftResponse, SocketMessage};
+use crate::ipc::subscriptions::{ClientId, SharedServerState};
+
+/// Write half of a socket client's connection, shared by the thread answering
+/// its requests and the one writing its events.
+pub type SocketWriter = Arc<Mutex<UnixStream>>;
+
+/// How long writing to a client may block before the client is considered gone.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Events that may wait to be written to a client before it is disconnected.
+const EVENT_QUEUE_LEN: usize = 256;
+
+pub trait RequestHandler: Send + Sync + 'static {
+    fn handle_request(&self, request: RiftRequest, client: ClientId) -> RiftResponse;
+}
+
+fn write_socket_message(writer: &SocketWriter, message: &SocketMessage) -> io::Result<()> {
+    let mut line = serde_json::to_vec(message)?;
+    line.push(b'\n');
+    writer.lock().write_all(&line)
+}
+
+/// Queue of events for one socket client, emptied by a thread writing them to
+/// the client. Sending never blocks the publisher.
+#[derive(Clone)]
+pub struct SocketEvents {
+    queue: SyncSender<BroadcastEvent>,
+    stream: Arc<UnixStream>,
+}
+
+impl SocketEvents {
+    /// Starts the thread writing events for client `id` to `writer`. When a
+    /// write fails or times out, the client is removed from `state`.
+    pub fn spawn(id: u64, writer: SocketWriter, state: SharedServerState) -> io::Result<Self> {
+        let stream = Arc::new(writer.lock().try_clone()?);
+        let (queue, events) = mpsc::sync_channel(EVENT_QUEUE_LEN);
+        std::thread::spawn(move || write_events(id, events, writer, state));
+        Ok(SocketEvents { queue, stream })
+    }
+
+    /// Queues `event`. If the client has fallen too far behind, its
+    /// connection is closed instead and an error is returned; the caller
+    /// should then remove the client.
+    pub fn send(&self, event: BroadcastEvent) -> Result<(), String> {
+        match self.queue.try_send(event) {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Full(_)) => {
+                self.disconnect();
+                Err(format!("more than {EVENT_QUEUE_LEN} events are waiting"))
+            }
+            Err(TrySendError::Disconnected(_)) => Err("the connection is closed".to_string()),
+        }
+    }
+
+    /// Closes the connection, which also ends the thread reading requests.
+    fn disconnect(&self) { let _ = self.stream.shutdown(Shutdown::Both); }
+}
+
+fn write_events(
+    id: u64,
+    events: Receiver<BroadcastEvent>,
+    writer: SocketWriter,
+    state: SharedServerState,
+) {
+    // Ends once the client is removed and its queue dropped.
+    for event in events {
+        if let Err(e) = write_socket_message(&writer, &SocketMessage::Event { event }) {
+            warn!("Failed to send event to socket client {}: {}", id, e);
+            state.read().remove_client(ClientId::Socket(id));
+            let _ = writer.lock().shutdown(Shutdown::Both);
+            break;
+        }
+    }
+}
+
+/// Listens on `path` and answers every connection on its own thread.
+///
+/// Fails if another server is already listening on `path`; a socket file
+/// left behind by a server that exited is replaced.
+pub fn listen(
+    path: &Path,
+    handler: Arc<dyn RequestHandler>,
+    state: SharedServerState,
+) -> io::Result<()> {
+    if path.exists() {
+        if UnixStream::connect(path).is_ok() {
+            return Err(io::Error::new(
+                io::ErrorKind::AddrInUse,
+                format!("Another server is listening on {}", path.display()),
+            ));
+        }
+        fs::remove_file(path)?;
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let listener = UnixListener::bind(path)?;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+    info!("Listening for IPC connections on {}", path.display());
+
+    std::thread::spawn(move || {
+        let next_id = AtomicU64::new(1);
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    let id = next_id.fetch_add(1, Ordering::Relaxed);
+                    let handler = handler.clone();
+                    let state = state.clone();
+                    std::thread::spawn(move || serve_client(id, stream, handler, state));
+                }
+                Err(e) => error!("Failed to accept IPC connection: {}", e),
+            }
+        }
+    });
+    Ok(())
+}
+
+fn serve_client(
+    id: u64,
+    stream: UnixStream,
+    handler: Arc<dyn RequestHandler>,
+    state: SharedServerState,
+) {
+    let writer = match stream.try_clone() {
+        Ok(writer) => writer,
+        Err(e) => {
+            error!("Failed to set up IPC connection {}: {}", id, e);
+            return;
+        }
+    };
+    let _ = writer.set_write_timeout(Some(WRITE_TIMEOUT));
+    let writer: SocketWriter = Arc::new(Mutex::new(writer));
+    let events = match SocketEvents::spawn(id, writer.clone(), state.clone()) {
+        Ok(events) => events,
+        Err(e) => {
+            error!("Failed to set up IPC connection {}: {}", id, e);
+            return;
+        }
+    };
+    let client = ClientId::Socket(id);
+    state.read().register_socket_client(id, events);
+    debug!("IPC client {} connected", client);
+
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else { break };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str::<RiftRequest>(&line) {
+            Ok(request) => handler.handle_request(request, client),
+            Err(e) => RiftResponse::error(
+                ErrorCode::InvalidRequest,
+                format!("Invalid request format: {}", e),
+            ),
+        };
+        if let Err(e) = write_socket_message(&writer, &SocketMessage::Response(response)) {
+            debug!("Failed to respond to IPC client {}: {}", client, e);
+            break;
+        }
+    }
+
+    state.read().remove_client(client);
+    debug!("IPC client {} disconnected", client);
+}
+
+pub struct RiftSocketClient {
+    reader: BufReader<UnixStream>,
+    writer: UnixStream,
+    events: VecDeque<BroadcastEvent>,
+}
+
+impl RiftSocketClient {
+    pub fn connect(path: &Path) -> Result<Self, String> {
+        let writer = UnixStream::connect(path)
+            .map_err(|e| format!("Failed to connect to {}: {}", path.display(), e))?;
+        let reader = writer.try_clone().map_err(|e| e.to_string())?;
+        Ok(RiftSocketClient {
+            reader: BufReader::new(reader),
+            writer,
+            events: VecDeque::new(),
+        })
+    }
+
+    /// Sends `request` and waits for its response. Events that arrive in the
+    /// meantime are kept for [`next_event`](Self::next_event).
+    pub fn send_request(&mut self, request: &RiftRequest) -> Result<RiftResponse, String> {
+        let mut line = serde_json::to_vec(request)
+            .map_err(|e| format!("Failed to serialize request: {}", e))?;
+        line.push(b'\n');
+        self.writer
+            .write_all(&line)
+            .map_err(|e| format!("Failed to send request: {}", e))?;
+
+        loop {
+            match self.read_message()? {
+                SocketMessage::Response(response) => return Ok(response),
+                SocketMessage::Event { event } => self.events.push_back(event),
+            }
+        }
+    }
+
+    /// Waits for the next event the client subscribed to.
+    pub fn next_event(&mut self) -> Result<BroadcastEvent, String> {
+        if let Some(event) = self.events.pop_front() {
+            return Ok(event);
+        }
+        loop {
+            if let SocketMessage::Event { event } = self.read_message()? {
+                return Ok(event);
+            }
+        }
+    }
+
+    fn read_message(&mut self) -> Result<SocketMessage, String> {
+        let mut line = String::new();
+        match self.reader.read_line(&mut line) {
+            Ok(0) => Err("Connection closed by rift".to_string()),
+            Ok(_) => serde_json::from_str(&line)
+                .map_err(|e| format!("Failed to parse response JSON: {}", e)),
+            Err(e) => Err(format!("Failed to read response: {}", e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ipc::subscriptions::ServerState;
+    use crate::sys::screen::SpaceId;
+
+    /// Answers `get_displays` with the client it came from and handles
+    /// subscriptions like the real server.
+    struct StubHandler {
+        state: SharedServerState,
+    }
+
+    impl RequestHandler for StubHandler {
+        fn handle_request(&self, request: RiftRequest, client: ClientId) -> RiftResponse {
+            match request {
+                RiftRequest::GetDisplays => RiftResponse::Success {
+                    data: serde_json::json!(client.to_string()),
+                },
+                RiftRequest::Subscribe { event } => {
+                    self.state.read().subscribe_client(client, event.clone());
+                    RiftResponse::Success {
+                        data: serde_json::json!({ "subscribed": event }),
+                    }
+                }
+                _ => RiftResponse::error(ErrorCode::Failed, "unsupported"),
+            }
+        }
+    }
+
+    fn start() -> (tempfile::TempDir, std::path::PathBuf, SharedServerState) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rift.sock");
+        let state: SharedServerState = Arc::new(parking_lot::RwLock::new(ServerState::new()));
+        let handler = Arc::new(StubHandler { state: state.clone() });
+        listen(&path, handler, state.clone()).unwrap();
+        (dir, path, state)
+    }
+
+    fn data(response: RiftResponse) -> serde_json::Value {
+        match response {
+            RiftResponse::Success { data } => data,
+            other => panic!("unexpected response {other:?}"),
+        }
+    }
+
+    #[test]
+    fn answers_each_request_line() {
+        let (_dir, path, _state) = start();
+        let mut first = RiftSocketClient::connect(&path).unwrap();
+        let mut second = RiftSocketClient::connect(&path).unwrap();
+
+        assert_eq!(
+            data(first.send_request(&RiftRequest::GetDisplays).unwrap()),
+            "socket:1"
+        );
+        assert_eq!(
+            data(second.send_request(&RiftRequest::GetDisplays).unwrap()),
+            "socket:2"
+        );
+        assert_eq!(
+            data(first.send_request(&RiftRequest::GetDisplays).unwrap()),
+            "socket:1"
+        );
+        assert!(matches!(
+            first.send_request(&RiftRequest::GetMetrics).unwrap(),
+            RiftResponse::Error { .. }
+        ));
+    }
+
+    #[test]
+    fn rejects_malformed_requests_without_closing() {
+        let (_dir, path, _state) = start();
+        let stream = UnixStream::connect(&path).unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut writer = stream;
+
+        writer.write_all(b"{\"no_such_request\": {}}\n\"get_displays\"\n").unwrap();
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        let error: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(error["error"]["code"], "invalid_request");
+        line.clear();
+        reader.read_line(&mut line).unwrap();
+        assert_eq!(line, "{\"data\":\"socket:1\"}\n");
+    }
+
+    #[test]
+    fn delivers_subscribed_events() {
+        let (_dir, path, state) = start();
+        let mut client = RiftSocketClient::connect(&path).unwrap();
+        let subscribe = RiftRequest::Subscribe {
+            event: "workspace_changed".into(),
+        };
+        assert_eq!(
+            data(client.send_request(&subscribe).unwrap()),
+            serde_json::json!({ "subscribed": "workspace_changed" })
+        );
+
+        state.read().publish(BroadcastEvent::WorkspaceChanged {
+            space_id: SpaceId::new(1),
+            workspace_id: Default::default(),
+            workspace_name: "main".into(),
+            display_uuid: None,
+        });
+        match client.next_event().unwrap() {
+            BroadcastEvent::WorkspaceChanged { workspace_name, .. } => {
+                assert_eq!(workspace_name, "main")
+            }
+            other => panic!("unexpected event {other:?}"),
+        }
+    }
+
+    #[test]
+    fn drops_subscribers_that_stop_reading() {
+        let (_dir, path, state) = start();
+        let subscribe = RiftRequest::Subscribe {
+            event: "workspace_changed".into(),
+        };
+        let event = |name: String| BroadcastEvent::WorkspaceChanged {
+            space_id: SpaceId::new(1),
+            workspace_id: Default::default(),
+            workspace_name: name,
+            display_uuid: None,
+        };
+        let mut stalled = RiftSocketClient::connect(&path).unwrap();
+        data(stalled.send_request(&subscribe).unwrap());
+
+        // Far more than the socket buffers and the event queue hold.
+        let started = std::time::Instant::now();
+        for _ in 0..2000 {
+            state.read().publish(event("x".repeat(4096)));
+        }
+        assert!(started.elapsed() < WRITE_TIMEOUT);
+
+        // The connection is closed after the events that were already sent.
+        let mut stream = stalled.reader.into_inner();
+        stream.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        io::Read::read_to_end(&mut stream, &mut Vec::new()).unwrap();
+
+        let mut client = RiftSocketClient::connect(&path).unwrap();
+        data(client.send_request(&subscribe).unwrap());
+        state.read().publish(event("main".into()));
+        match client.next_event().unwrap() {
+            BroadcastEvent::WorkspaceChanged { workspace_name, .. } => {
+                assert_eq!(workspace_name, "main")
+            }
+            other => panic!("unexpected event {other:?}"),
+        }
+    }
+
+    #[test]
+    fn replaces_stale_socket_files_but_not_live_servers() {
+        let (_dir, path, state) = start();
+        let handler = Arc::new(StubHandler { state: state.clone() });
+        let err = listen(&path, handler.clone(), state.clone()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::AddrInUse);
+
+        let dir = tempfile::tempdir().unwrap();
+        let stale = dir.path().join("rift.sock");
+        drop(UnixListener::bind(&stale).unwrap());
+        listen(&stale, handler, state).unwrap();
+        let mut client = RiftSocketClient::connect(&stale).unwrap();
+        assert_eq!(
+            data(client.send_request(&RiftRequest::GetDisplays).unwrap()),
+            "socket:1"
+        );
+    }
+}
@@ -11,11 +11,30 @@ use tracing::{debug, error, info, warn};
 
 use crate::actor::broadcast::BroadcastEvent;
 use crate::common::collections::{HashMap, HashSet};
+use crate::ipc::socket::SocketEvents;
 use crate::sys::dispatch::DispatchExt;
 use crate::sys::mach::mach_send_message;
 
 pub type ClientPort = u32;
 
+/// A client that can subscribe to events, identified per transport.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ClientId {
+    /// Reply port of a Mach client
+    Mach(ClientPort),
+    /// Connection number of a Unix socket client
+    Socket(u64),
+}
+
+impl std::fmt::Display for ClientId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClientId::Mach(port) => write!(f, "mach:{port}"),
+            ClientId::Socket(id) => write!(f, "socket:{id}"),
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct CliSubscription {
     pub command: String,
@@ -23,9 +42,10 @@ pub struct CliSubscription {
 }
 
 pub struct ServerState {
-    subscriptions_by_client: DashMap<ClientPort, Vec<String>>,
-    subscriptions_by_event: DashMap<String, Vec<ClientPort>>,
+    subscriptions_by_client: DashMap<ClientId, Vec<String>>,
+    subscriptions_by_event: DashMap<String, Vec<ClientId>>,
     cli_subscriptions: Mutex<HashMap<String, Vec<CliSubscription>>>,
+    socket_clients: DashMap<u64, SocketEvents>,
 }
 
 pub type SharedServerState = Arc<RwLock<ServerState>>;
@@ -36,10 +56,16 @@ impl ServerState {
             subscriptions_by_client: DashMap::new(),
             subscriptions_by_event: DashMap::new(),
             cli_subscriptions: Mutex::new(HashMap::default()),
+            socket_clients: DashMap::new(),
         }
     }
 
-    pub fn subscribe_client(&self, client_port: ClientPort, event: String) {
+    /// Registers the queue events for socket client `id` are sent to.
+    pub fn register_socket_client(&self, id: u64, events: SocketEvents) {
+        self.socket_clients.insert(id, events);
+    }
+
+    pub fn subscribe_client(&self, client_port: ClientId, event: String) {
         info!("Client {} subscribing to event: {}", client_port, event);
         let mut added = false;
         self.subscriptions_by_client
@@ -68,7 +94,7 @@ impl ServerState {
         }
     }
 
-    pub fn unsubscribe_client(&self, client_port: ClientPort, event: String) {
+    pub fn unsubscribe_client(&self, client_port: ClientId, event: String) {
         info!("Client {} unsubscribing from event: {}", client_port, event);
         let mut removed = false;
 
@@ -150,7 +176,7 @@ impl ServerState {
             BroadcastEvent::WindowTitleChanged { .. } => "window_title_changed",
         };
 
-        let mut targets: HashSet<ClientPort> = HashSet::default();
+        let mut targets: HashSet<ClientId> = HashSet::default();
         if let Some(clients) = self.subscriptions_by_event.get(event_name) {
             targets.extend(clients.iter().copied());
         }
@@ -170,8 +196,11 @@ impl ServerState {
             }
         };
 
-        for client_port in targets {
-            schedule_event_send(client_port, event_json.clone());
+        for client in targets {
+            match client {
+                ClientId::Mach(client_port) => schedule_event_send(client_port, event_json.clone()),
+                ClientId::Socket(id) => self.send_event_to_socket_client(id, &event),
+            }
         }
     }
 
@@ -218,7 +247,20 @@ impl ServerState {
         }
     }
 
-    pub fn remove_client(&self, client_port: ClientPort) {
+    fn send_event_to_socket_client(&self, id: u64, event: &BroadcastEvent) {
+        let Some(events) = self.socket_clients.get(&id).map(|e| e.clone()) else {
+            return;
+        };
+        if let Err(e) = events.send(event.clone()) {
+            warn!("Dropping socket client {}: {}", id, e);
+            self.remove_client(ClientId::Socket(id));
+        }
+    }
+
+    pub fn remove_client(&self, client_port: ClientId) {
+        if let ClientId::Socket(id) = client_port {
+            self.socket_clients.remove(&id);
+        }
         if let Some((_k, events)) = self.subscriptions_by_client.remove(&client_port) {
             for event in events {
                 if let Some(mut entry) = self.subscriptions_by_event.get_mut(&event) {
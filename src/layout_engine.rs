@@ -1,15 +1,28 @@
+mod dock;
 pub mod engine;
 mod floating;
 pub(crate) mod graph;
+mod layout_tree;
+mod placement;
+mod saved_layout;
 pub mod systems;
 pub mod utils;
 mod workspaces;
 
+pub(crate) use dock::DockManager;
 pub use engine::{EventResponse, LayoutCommand, LayoutEngine, LayoutEvent};
 pub(crate) use floating::FloatingManager;
 pub use graph::{Direction, LayoutKind, Orientation};
+pub use layout_tree::{ContainerKind, LAYOUT_TREE_VERSION, LayoutTree, LayoutTreeNode};
+pub use placement::SnapRegion;
+pub use saved_layout::{
+    SavedLayout, SavedLayoutNode, SavedLayoutStore, SavedWindow, WindowCandidate,
+};
 pub(crate) use systems::LayoutId;
-pub use systems::{BspLayoutSystem, LayoutSystem, LayoutSystemKind, TraditionalLayoutSystem};
+pub use systems::{
+    BspLayoutSystem, GridLayoutSystem, LayoutSystem, LayoutSystemKind, MasterStackLayoutSystem,
+    MonocleLayoutSystem, MultiLayoutSystem, SizeTarget, TraditionalLayoutSystem,
+};
 pub(crate) use workspaces::WorkspaceLayouts;
 
 pub use crate::model::virtual_workspace::{
@@ -0,0 +1,165 @@
+use objc2_core_foundation::{CGPoint, CGRect, CGSize};
+use serde::{Deserialize, Serialize};
+
+use crate::actor::app::{WindowId, pid_t};
+use crate::common::collections::HashMap;
+use crate::layout_engine::Direction;
+
+/// How much of a docked window stays on screen while it is tucked away.
+const DOCK_PEEK: f64 = 10.0;
+
+/// Floating windows parked mostly off-screen on one edge of their display.
+/// At most one docked window is revealed at a time; it slides back out as soon
+/// as the pointer moves off it.
+#[derive(Serialize, Deserialize, Default)]
+pub(crate) struct DockManager {
+    docked: HashMap<WindowId, Direction>,
+    #[serde(skip)]
+    revealed: Option<WindowId>,
+}
+
+impl DockManager {
+    pub(crate) fn dock(&mut self, wid: WindowId, edge: Direction) {
+        self.docked.insert(wid, edge);
+        if self.revealed == Some(wid) {
+            self.revealed = None;
+        }
+    }
+
+    pub(crate) fn undock(&mut self, wid: WindowId) -> bool {
+        if self.revealed == Some(wid) {
+            self.revealed = None;
+        }
+        self.docked.remove(&wid).is_some()
+    }
+
+    pub(crate) fn is_docked(&self, wid: WindowId) -> bool { self.docked.contains_key(&wid) }
+
+    pub(crate) fn is_revealed(&self, wid: WindowId) -> bool { self.revealed == Some(wid) }
+
+    pub(crate) fn revealed(&self) -> Option<WindowId> { self.revealed }
+
+    /// Reveals `wid` if it is docked and tucks every other docked window away.
+    /// Returns whether anything changed.
+    pub(crate) fn reveal(&mut self, wid: Option<WindowId>) -> bool {
+        let next = wid.filter(|wid| self.is_docked(*wid));
+        if next == self.revealed {
+            return false;
+        }
+        self.revealed = next;
+        true
+    }
+
+    pub(crate) fn windows(&self) -> impl Iterator<Item = (WindowId, Direction)> + '_ {
+        self.docked.iter().map(|(&wid, &edge)| (wid, edge))
+    }
+
+    pub(crate) fn remove_all_for_pid(&mut self, pid: pid_t) {
+        self.docked.retain(|wid, _| wid.pid != pid);
+        if self.revealed.is_some_and(|wid| wid.pid == pid) {
+            self.revealed = None;
+        }
+    }
+}
+
+/// Frame of a window docked on `edge` of `screen`. The window keeps its size
+/// and its position along the edge; when tucked away only [`DOCK_PEEK`] points
+/// of it remain on screen.
+pub(crate) fn docked_frame(
+    screen: CGRect,
+    frame: CGRect,
+    edge: Direction,
+    revealed: bool,
+) -> CGRect {
+    let size = CGSize::new(
+        frame.size.width.min(screen.size.width),
+        frame.size.height.min(screen.size.height),
+    );
+    let (min, max) = (screen.min(), screen.max());
+    let along_x = frame.origin.x.clamp(min.x, max.x - size.width);
+    let along_y = frame.origin.y.clamp(min.y, max.y - size.height);
+
+    let origin = match (edge, revealed) {
+        (Direction::Left, true) => CGPoint::new(min.x, along_y),
+        (Direction::Left, false) => CGPoint::new(min.x - size.width + DOCK_PEEK, along_y),
+        (Direction::Right, true) => CGPoint::new(max.x - size.width, along_y),
+        (Direction::Right, false) => CGPoint::new(max.x - DOCK_PEEK, along_y),
+        (Direction::Up, true) => CGPoint::new(along_x, min.y),
+        (Direction::Up, false) => CGPoint::new(along_x, min.y - size.height + DOCK_PEEK),
+        (Direction::Down, true) => CGPoint::new(along_x, max.y - size.height),
+        (Direction::Down, false) => CGPoint::new(along_x, max.y - DOCK_PEEK),
+    };
+    CGRect::new(origin, size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn screen() -> CGRect { CGRect::new(CGPoint::new(0.0, 0.0), CGSize::new(1000.0, 800.0)) }
+
+    fn frame() -> CGRect { CGRect::new(CGPoint::new(300.0, 200.0), CGSize::new(400.0, 300.0)) }
+
+    #[test]
+    fn tucked_windows_only_peek_onto_the_screen() {
+        for edge in [
+            Direction::Left,
+            Direction::Right,
+            Direction::Up,
+            Direction::Down,
+        ] {
+            let rect = docked_frame(screen(), frame(), edge, false);
+            let visible = match edge {
+                Direction::Left => rect.max().x - screen().min().x,
+                Direction::Right => screen().max().x - rect.min().x,
+                Direction::Up => rect.max().y - screen().min().y,
+                Direction::Down => screen().max().y - rect.min().y,
+            };
+            assert_eq!(visible, DOCK_PEEK, "{edge:?}");
+            assert_eq!(rect.size, frame().size);
+        }
+    }
+
+    #[test]
+    fn revealed_windows_sit_flush_with_the_edge() {
+        assert_eq!(
+            docked_frame(screen(), frame(), Direction::Left, true).origin,
+            CGPoint::new(0.0, 200.0)
+        );
+        assert_eq!(
+            docked_frame(screen(), frame(), Direction::Right, true).origin,
+            CGPoint::new(600.0, 200.0)
+        );
+        assert_eq!(
+            docked_frame(screen(), frame(), Direction::Down, true).origin,
+            CGPoint::new(300.0, 500.0)
+        );
+    }
+
+    #[test]
+    fn position_along_the_edge_is_clamped_to_the_screen() {
+        let offscreen = CGRect::new(CGPoint::new(-500.0, 700.0), CGSize::new(400.0, 300.0));
+        let rect = docked_frame(screen(), offscreen, Direction::Right, true);
+        assert_eq!(rect.origin, CGPoint::new(600.0, 500.0));
+    }
+
+    #[test]
+    fn only_docked_windows_are_revealed() {
+        let mut dock = DockManager::default();
+        let docked = WindowId::new(1, 1);
+        let other = WindowId::new(1, 2);
+        dock.dock(docked, Direction::Left);
+
+        assert!(!dock.reveal(Some(other)));
+        assert!(dock.reveal(Some(docked)));
+        assert!(dock.is_revealed(docked));
+        assert!(!dock.reveal(Some(docked)));
+        assert!(dock.reveal(Some(other)));
+        assert_eq!(dock.revealed(), None);
+
+        dock.reveal(Some(docked));
+        assert!(dock.undock(docked));
+        assert_eq!(dock.revealed(), None);
+        assert!(!dock.is_docked(docked));
+    }
+}
@@ -7,11 +7,16 @@ use objc2_core_foundation::{CGPoint, CGRect, CGSize};
 use serde::{Deserialize, Serialize};
 use tracing::{debug, info, warn};
 
-use super::{Direction, FloatingManager, LayoutId, LayoutSystemKind, WorkspaceLayouts};
+use super::dock::docked_frame;
+use super::placement::{self, PlacementContext, PlacementMemory};
+use super::{
+    Direction, DockManager, FloatingManager, LAYOUT_TREE_VERSION, LayoutId, LayoutSystemKind,
+    LayoutTree, MultiLayoutSystem, Orientation, SizeTarget, SnapRegion, WorkspaceLayouts,
+};
 use crate::actor::app::{AppInfo, WindowId, pid_t};
 use crate::actor::broadcast::{BroadcastEvent, BroadcastSender};
 use crate::common::collections::HashMap;
-use crate::common::config::LayoutSettings;
+use crate::common::config::{LayoutMode, LayoutSettings};
 use crate::layout_engine::LayoutSystem;
 use crate::model::{VirtualWorkspaceId, VirtualWorkspaceManager};
 use crate::sys::screen::SpaceId;
@@ -26,8 +31,9 @@ pub struct GroupContainerInfo {
 }
 
 #[non_exhaustive]
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, strum_macros::VariantNames)]
 #[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
 pub enum LayoutCommand {
     NextWindow,
     PrevWindow,
@@ -39,6 +45,11 @@ pub enum LayoutCommand {
     JoinWindow(Direction),
     ToggleStack,
     ToggleOrientation,
+    RotateTree {
+        degrees: i32,
+    },
+    FlipTree(Orientation),
+    BalanceTree,
     UnjoinWindows,
     ToggleFocusFloating,
     ToggleWindowFloating,
@@ -50,6 +61,55 @@ pub enum LayoutCommand {
     ResizeWindowBy {
         amount: f64,
     },
+    ResizeEdge {
+        direction: Direction,
+        amount: f64,
+    },
+    SetWindowSize(SizeTarget),
+
+    IncrementMasterCount,
+    DecrementMasterCount,
+    PromoteToMaster,
+
+    SetLayoutMode(LayoutMode),
+    SetWorkspaceLayoutMode(LayoutMode),
+
+    SendToScratchpad {
+        name: Option<String>,
+    },
+    ToggleScratchpad {
+        name: Option<String>,
+    },
+    ToggleSticky,
+    DockWindow(Direction),
+    UndockWindow,
+
+    Preselect(Direction),
+    CancelPreselect,
+
+    MoveFloatingWindow {
+        dx: f64,
+        dy: f64,
+    },
+    ResizeFloatingWindow {
+        dw: f64,
+        dh: f64,
+    },
+    SnapFloatingWindow(SnapRegion),
+    CenterFloatingWindow,
+
+    SetMark {
+        name: String,
+    },
+    UnsetMark {
+        name: Option<String>,
+    },
+    FocusMark {
+        name: String,
+    },
+    SwapWithMark {
+        name: String,
+    },
 
     NextWorkspace(Option<bool>),
     PrevWorkspace(Option<bool>),
@@ -95,9 +155,23 @@ pub struct EventResponse {
 
 #[derive(Serialize, Deserialize)]
 pub struct LayoutEngine {
-    tree: LayoutSystemKind,
+    tree: MultiLayoutSystem,
     workspace_layouts: WorkspaceLayouts,
     floating: FloatingManager,
+    #[serde(default)]
+    docked: DockManager,
+    #[serde(default)]
+    marks: HashMap<String, WindowId>,
+    /// Swallowing window -> the window it replaced in the layout.
+    #[serde(default)]
+    swallowed: HashMap<WindowId, WindowId>,
+    #[serde(default)]
+    placement_memory: PlacementMemory,
+    #[serde(skip)]
+    app_bundle_ids: HashMap<pid_t, String>,
+    /// Screen frame of each space as of the last layout calculation.
+    #[serde(skip)]
+    screen_frames: HashMap<SpaceId, CGRect>,
     #[serde(skip)]
     focused_window: Option<WindowId>,
     virtual_workspace_manager: VirtualWorkspaceManager,
@@ -111,7 +185,27 @@ pub struct LayoutEngine {
 
 impl LayoutEngine {
     pub fn set_layout_settings(&mut self, settings: &LayoutSettings) {
+        // `layout_settings.mode` is the configured mode, not the current one:
+        // a mode picked with `SetLayoutMode` is only replaced when the
+        // configured mode itself changes.
+        let configured_mode = self.layout_settings.mode;
         self.layout_settings = settings.clone();
+        self.tree.set_settings(settings);
+        if settings.mode != configured_mode {
+            self.set_layout_mode(settings.mode);
+        }
+    }
+
+    /// Switches the global layout mode, converting every layout whose workspace
+    /// does not have a mode of its own. Window order and selection are kept;
+    /// floating windows are not part of any layout and are left alone.
+    pub fn set_layout_mode(&mut self, mode: LayoutMode) {
+        self.tree.set_default_mode(mode);
+        for (workspace_id, layout) in self.workspace_layouts.all_layouts() {
+            if self.virtual_workspace_manager.layout_mode(workspace_id).is_none() {
+                self.tree.convert_layout(layout, mode);
+            }
+        }
     }
 
     pub fn update_virtual_workspace_settings(
@@ -522,19 +616,18 @@ impl LayoutEngine {
         let virtual_workspace_manager =
             VirtualWorkspaceManager::new_with_config(virtual_workspace_config);
 
-        let tree = match layout_settings.mode {
-            crate::common::config::LayoutMode::Traditional => LayoutSystemKind::Traditional(
-                crate::layout_engine::TraditionalLayoutSystem::default(),
-            ),
-            crate::common::config::LayoutMode::Bsp => {
-                LayoutSystemKind::Bsp(crate::layout_engine::BspLayoutSystem::default())
-            }
-        };
+        let tree = MultiLayoutSystem::new(layout_settings);
 
         LayoutEngine {
             tree,
             workspace_layouts: WorkspaceLayouts::default(),
             floating: FloatingManager::new(),
+            docked: DockManager::default(),
+            marks: HashMap::default(),
+            swallowed: HashMap::default(),
+            placement_memory: PlacementMemory::default(),
+            app_bundle_ids: HashMap::default(),
+            screen_frames: HashMap::default(),
             focused_window: None,
             virtual_workspace_manager,
             layout_settings: layout_settings.clone(),
@@ -567,12 +660,16 @@ impl LayoutEngine {
             LayoutEvent::SpaceExposed(space, size) => {
                 self.debug_tree(space);
 
-                let workspaces =
-                    self.virtual_workspace_manager_mut().list_workspaces(space).to_vec();
+                let workspaces: Vec<_> = self
+                    .virtual_workspace_manager_mut()
+                    .list_workspaces(space)
+                    .into_iter()
+                    .map(|(id, _)| (id, self.virtual_workspace_manager.layout_mode(id)))
+                    .collect();
                 self.workspace_layouts.ensure_active_for_space(
                     space,
                     size,
-                    workspaces.into_iter().map(|(id, _)| id),
+                    workspaces,
                     &mut self.tree,
                 );
             }
@@ -589,6 +686,9 @@ impl LayoutEngine {
                     Some(info) => (info.bundle_id.as_deref(), info.localized_name.as_deref()),
                     None => (None, None),
                 };
+                if let Some(bundle_id) = app_bundle_id {
+                    self.app_bundle_ids.insert(pid, bundle_id.to_owned());
+                }
 
                 for (wid, title_opt, ax_role_opt, ax_subrole_opt) in windows_with_titles {
                     let title_ref = title_opt.as_deref();
@@ -661,6 +761,9 @@ impl LayoutEngine {
                         desired.push(wid);
                     }
 
+                    // Swallowed windows stay out of the layout until released.
+                    desired.retain(|wid| !self.is_window_swallowed(*wid));
+
                     if desired.is_empty() && total_tiled_count == 0 {
                         if self.tree.has_windows_for_app(layout, pid) {
                             continue;
@@ -675,8 +778,26 @@ impl LayoutEngine {
                 self.rebalance_all_layouts();
             }
             LayoutEvent::AppClosed(pid) => {
+                let swallowing: Vec<_> =
+                    self.swallowed.keys().filter(|wid| wid.pid == pid).copied().collect();
+                for wid in swallowing {
+                    self.release_swallowed_window(wid);
+                }
+                self.swallowed.retain(|_, parent| parent.pid != pid);
+
+                let floating_frames: Vec<_> = self
+                    .virtual_workspace_manager
+                    .app_floating_positions(pid)
+                    .into_iter()
+                    .filter(|(wid, _)| self.floating.is_floating(*wid))
+                    .collect();
+                self.remember_floating_frames(&floating_frames);
+                self.app_bundle_ids.remove(&pid);
+
                 self.tree.remove_windows_for_app(pid);
                 self.floating.remove_all_for_pid(pid);
+                self.docked.remove_all_for_pid(pid);
+                self.marks.retain(|_, wid| wid.pid != pid);
 
                 self.virtual_workspace_manager.remove_windows_for_app(pid);
                 self.virtual_workspace_manager.remove_app_floating_positions(pid);
@@ -721,9 +842,22 @@ impl LayoutEngine {
             LayoutEvent::WindowRemoved(wid) => {
                 let affected_space: Option<SpaceId> = self.space_with_window(wid);
 
+                self.release_swallowed_window(wid);
+                self.swallowed.retain(|_, parent| *parent != wid);
                 self.tree.remove_window(wid);
 
+                if self.floating.is_floating(wid) {
+                    let floating_frame: Vec<_> = self
+                        .virtual_workspace_manager
+                        .app_floating_positions(wid.pid)
+                        .into_iter()
+                        .filter(|(other, _)| *other == wid)
+                        .collect();
+                    self.remember_floating_frames(&floating_frame);
+                }
                 self.floating.remove_floating(wid);
+                self.docked.undock(wid);
+                self.marks.retain(|_, marked| *marked != wid);
 
                 self.virtual_workspace_manager.remove_window(wid);
 
@@ -833,6 +967,7 @@ impl LayoutEngine {
                 }
                 self.floating.remove_floating(wid);
                 self.floating.set_last_focus(None);
+                self.docked.undock(wid);
             } else {
                 if let Some(space) = space {
                     self.floating.add_active(space, wid.pid, wid);
@@ -845,6 +980,20 @@ impl LayoutEngine {
             return EventResponse::default();
         }
 
+        if let LayoutCommand::SetLayoutMode(mode) = &command {
+            self.set_layout_mode(*mode);
+            return EventResponse::default();
+        }
+
+        // The reactor switches to the marked window's workspace beforehand, so
+        // this only has to hand out focus.
+        if let LayoutCommand::FocusMark { name } = &command {
+            return EventResponse {
+                raise_windows: vec![],
+                focus_window: self.marked_window(name),
+            };
+        }
+
         let Some(space) = space else {
             return EventResponse::default();
         };
@@ -892,6 +1041,8 @@ impl LayoutEngine {
         match command {
             LayoutCommand::ToggleWindowFloating => unreachable!(),
             LayoutCommand::ToggleFocusFloating => unreachable!(),
+            LayoutCommand::FocusMark { .. } => unreachable!(),
+            LayoutCommand::SetLayoutMode(_) => unreachable!(),
 
             LayoutCommand::SwapWindows(a, b) => {
                 let layout = self.layout(space);
@@ -1025,10 +1176,28 @@ impl LayoutEngine {
                 self.tree.unjoin_selection(layout);
                 EventResponse::default()
             }
+            LayoutCommand::RotateTree { degrees } => {
+                self.workspace_layouts.mark_last_saved(space, workspace_id, layout);
+                self.tree.rotate_selection(layout, degrees);
+                EventResponse::default()
+            }
+            LayoutCommand::FlipTree(axis) => {
+                self.workspace_layouts.mark_last_saved(space, workspace_id, layout);
+                self.tree.flip_selection(layout, axis);
+                EventResponse::default()
+            }
+            LayoutCommand::BalanceTree => {
+                self.workspace_layouts.mark_last_saved(space, workspace_id, layout);
+                self.tree.rebalance(layout);
+                EventResponse::default()
+            }
             LayoutCommand::ToggleOrientation => {
                 self.workspace_layouts.mark_last_saved(space, workspace_id, layout);
 
-                let resp = match &mut self.tree {
+                let Some((system, layout)) = self.tree.system_for_layout_mut(layout) else {
+                    return EventResponse::default();
+                };
+                let resp = match system {
                     LayoutSystemKind::Traditional(s) => {
                         if s.parent_of_selection_is_stacked(layout) {
                             let default_orientation: crate::common::config::StackDefaultOrientation =
@@ -1052,6 +1221,9 @@ impl LayoutEngine {
                         s.toggle_tile_orientation(layout);
                         EventResponse::default()
                     }
+                    LayoutSystemKind::Monocle(_)
+                    | LayoutSystemKind::Grid(_)
+                    | LayoutSystemKind::MasterStack(_) => EventResponse::default(),
                 };
 
                 resp
@@ -1085,6 +1257,236 @@ impl LayoutEngine {
                 self.tree.resize_selection_by(layout, amount);
                 EventResponse::default()
             }
+            LayoutCommand::ResizeEdge { direction, amount } => {
+                if is_floating {
+                    return EventResponse::default();
+                }
+
+                self.workspace_layouts.mark_last_saved(space, workspace_id, layout);
+                self.tree.resize_selection_edge(layout, direction, amount);
+                EventResponse::default()
+            }
+            LayoutCommand::SetWindowSize(target) => {
+                if is_floating {
+                    return EventResponse::default();
+                }
+                let Some(screen) = self.screen_frames.get(&space).copied() else {
+                    return EventResponse::default();
+                };
+                let display_uuid = self.display_uuid_for_space(space);
+                let gaps = self.layout_settings.gaps.effective_for_display(display_uuid.as_deref());
+
+                self.workspace_layouts.mark_last_saved(space, workspace_id, layout);
+                self.tree.set_selection_size(layout, target, screen, &gaps);
+                EventResponse::default()
+            }
+            LayoutCommand::IncrementMasterCount | LayoutCommand::DecrementMasterCount => {
+                let Some((LayoutSystemKind::MasterStack(s), inner)) =
+                    self.tree.system_for_layout_mut(layout)
+                else {
+                    return EventResponse::default();
+                };
+                self.workspace_layouts.mark_last_saved(space, workspace_id, layout);
+                let delta = if command == LayoutCommand::IncrementMasterCount {
+                    1
+                } else {
+                    -1
+                };
+                s.adjust_master_count(inner, delta);
+                EventResponse::default()
+            }
+            LayoutCommand::PromoteToMaster => {
+                if is_floating {
+                    return EventResponse::default();
+                }
+                let Some((LayoutSystemKind::MasterStack(s), inner)) =
+                    self.tree.system_for_layout_mut(layout)
+                else {
+                    return EventResponse::default();
+                };
+                self.workspace_layouts.mark_last_saved(space, workspace_id, layout);
+                EventResponse {
+                    raise_windows: vec![],
+                    focus_window: s.promote_selection_to_master(inner),
+                }
+            }
+            LayoutCommand::SendToScratchpad { name } => {
+                let Some(wid) = self.focused_window else {
+                    return EventResponse::default();
+                };
+                if !self.virtual_workspace_manager.send_to_scratchpad(space, wid, name) {
+                    return EventResponse::default();
+                }
+                self.docked.undock(wid);
+                self.tree.remove_window(wid);
+                self.floating.add_floating(wid);
+                self.floating.add_active(space, wid.pid, wid);
+                self.focused_window = None;
+                self.refocus_workspace(space, workspace_id)
+            }
+            LayoutCommand::ToggleScratchpad { name } => {
+                let shown = self.virtual_workspace_manager.shown_scratchpad_window(space);
+                let target =
+                    self.virtual_workspace_manager.scratchpad_window(space, name.as_deref());
+                if shown.is_some() && (name.is_none() || shown == target) {
+                    self.virtual_workspace_manager.set_shown_scratchpad_window(space, None);
+                    return self.refocus_workspace(space, workspace_id);
+                }
+                let Some(wid) = target else {
+                    return EventResponse::default();
+                };
+                self.virtual_workspace_manager.set_shown_scratchpad_window(space, Some(wid));
+                self.floating.add_floating(wid);
+                self.floating.add_active(space, wid.pid, wid);
+                self.floating.set_last_focus(Some(wid));
+                self.focused_window = Some(wid);
+                EventResponse {
+                    raise_windows: vec![wid],
+                    focus_window: Some(wid),
+                }
+            }
+            LayoutCommand::ToggleSticky => {
+                let Some(wid) = self.focused_window else {
+                    return EventResponse::default();
+                };
+                if self.virtual_workspace_manager.is_scratchpad_window(wid) {
+                    return EventResponse::default();
+                }
+                if !is_floating {
+                    self.workspace_layouts.mark_last_saved(space, workspace_id, layout);
+                    self.tree.remove_window(wid);
+                    self.floating.add_floating(wid);
+                    self.floating.add_active(space, wid.pid, wid);
+                    self.floating.set_last_focus(Some(wid));
+                }
+                self.virtual_workspace_manager.toggle_sticky(wid);
+                EventResponse {
+                    raise_windows: vec![wid],
+                    focus_window: Some(wid),
+                }
+            }
+            LayoutCommand::Preselect(direction) => {
+                if !is_floating {
+                    self.tree.preselect(layout, Some(direction));
+                }
+                EventResponse::default()
+            }
+            LayoutCommand::CancelPreselect => {
+                self.tree.preselect(layout, None);
+                EventResponse::default()
+            }
+            LayoutCommand::DockWindow(edge) => {
+                let Some(wid) = self.focused_window else {
+                    return EventResponse::default();
+                };
+                if self.virtual_workspace_manager.is_scratchpad_window(wid) {
+                    return EventResponse::default();
+                }
+                if !is_floating {
+                    self.workspace_layouts.mark_last_saved(space, workspace_id, layout);
+                    self.tree.remove_window(wid);
+                    self.floating.add_floating(wid);
+                    self.floating.add_active(space, wid.pid, wid);
+                }
+                self.docked.dock(wid, edge);
+
+                // Hand focus back to the tiled windows; the docked window gets
+                // it again when it is hovered.
+                if self.floating.last_focus() == Some(wid) {
+                    self.floating.set_last_focus(None);
+                }
+                self.virtual_workspace_manager
+                    .set_last_focused_window(space, workspace_id, None);
+                self.focused_window = None;
+                EventResponse {
+                    raise_windows: vec![],
+                    focus_window: self.tree.selected_window(layout),
+                }
+            }
+            LayoutCommand::UndockWindow => {
+                let Some(wid) = self
+                    .focused_window
+                    .filter(|wid| self.docked.is_docked(*wid))
+                    .or(self.docked.revealed())
+                else {
+                    return EventResponse::default();
+                };
+                self.docked.undock(wid);
+                // Forget the docked frame so the window reappears centered.
+                self.virtual_workspace_manager.remove_floating_position(wid);
+                self.floating.set_last_focus(Some(wid));
+                self.focused_window = Some(wid);
+                EventResponse {
+                    raise_windows: vec![wid],
+                    focus_window: Some(wid),
+                }
+            }
+            LayoutCommand::SetMark { name } => {
+                if let Some(wid) = self.focused_window
+                    && !name.is_empty()
+                {
+                    self.marks.insert(name, wid);
+                }
+                EventResponse::default()
+            }
+            LayoutCommand::UnsetMark { name } => {
+                match name {
+                    Some(name) => {
+                        self.marks.remove(&name);
+                    }
+                    None => {
+                        if let Some(wid) = self.focused_window {
+                            self.marks.retain(|_, marked| *marked != wid);
+                        }
+                    }
+                }
+                EventResponse::default()
+            }
+            LayoutCommand::SwapWithMark { name } => {
+                let (Some(focused), Some(marked)) =
+                    (self.focused_window, self.marked_window(&name))
+                else {
+                    return EventResponse::default();
+                };
+                if focused != marked && self.tree.swap_windows(layout, focused, marked) {
+                    self.workspace_layouts.mark_last_saved(space, workspace_id, layout);
+                }
+                EventResponse::default()
+            }
+            LayoutCommand::MoveFloatingWindow { dx, dy } => {
+                self.update_focused_floating_frame(space, |screen, frame| {
+                    placement::offset_frame(screen, frame, dx, dy)
+                });
+                EventResponse::default()
+            }
+            LayoutCommand::ResizeFloatingWindow { dw, dh } => {
+                self.update_focused_floating_frame(space, |screen, frame| {
+                    placement::resize_frame(screen, frame, dw, dh)
+                });
+                EventResponse::default()
+            }
+            LayoutCommand::SnapFloatingWindow(region) => {
+                self.update_focused_floating_frame(space, |screen, _| {
+                    placement::snap_frame(screen, region)
+                });
+                EventResponse::default()
+            }
+            LayoutCommand::CenterFloatingWindow => {
+                self.update_focused_floating_frame(space, placement::center_frame);
+                EventResponse::default()
+            }
+            LayoutCommand::SetWorkspaceLayoutMode(mode) => {
+                self.virtual_workspace_manager.set_layout_mode(workspace_id, mode);
+                for workspace_layout in
+                    self.workspace_layouts.layouts_for_workspace(space, workspace_id)
+                {
+                    self.tree.convert_layout(workspace_layout, mode);
+                }
+                EventResponse {
+                    raise_windows: self.tree.visible_windows_in_layout(layout),
+                    focus_window: self.tree.selected_window(layout),
+                }
+            }
         }
     }
 
@@ -1117,6 +1519,7 @@ impl LayoutEngine {
         stack_line_thickness: f64,
         stack_line_horiz: crate::common::config::HorizontalPlacement,
         stack_line_vert: crate::common::config::VerticalPlacement,
+        cursor: Option<CGPoint>,
         get_window_frame: F,
     ) -> Vec<(WindowId, CGRect)>
     where
@@ -1124,6 +1527,7 @@ impl LayoutEngine {
     {
         use crate::model::HideCorner;
 
+        self.screen_frames.insert(space, screen);
         let mut positions = HashMap::default();
         let window_size = |wid| {
             get_window_frame(wid)
@@ -1145,7 +1549,7 @@ impl LayoutEngine {
             candidate: Option<CGRect>,
             store_if_absent: bool,
             screen: &CGRect,
-            center_rect: &impl Fn(CGSize) -> CGRect,
+            cursor: Option<CGPoint>,
             window_size: &impl Fn(WindowId) -> CGSize,
         ) {
             let existing = positions.get(&wid).copied();
@@ -1157,7 +1561,9 @@ impl LayoutEngine {
                     bundle_id.as_deref(),
                 )
             });
-            let rect = visible.unwrap_or_else(|| center_rect(window_size(wid)));
+            let rect = visible.unwrap_or_else(|| {
+                engine.initial_floating_frame(wid, window_size(wid), *screen, cursor, positions)
+            });
             positions.insert(wid, rect);
             if store_if_absent {
                 engine.virtual_workspace_manager.store_floating_position_if_absent(
@@ -1196,7 +1602,7 @@ impl LayoutEngine {
                 .virtual_workspace_manager
                 .get_workspace_floating_positions(space, active_workspace_id);
             for (window_id, stored_position) in floating_positions {
-                if self.floating.is_floating(window_id) {
+                if self.floating.is_floating(window_id) && !self.docked.is_docked(window_id) {
                     ensure_visible_floating(
                         self,
                         &mut positions,
@@ -1206,14 +1612,21 @@ impl LayoutEngine {
                         Some(stored_position),
                         false,
                         &screen,
-                        &center_rect,
+                        cursor,
                         &window_size,
                     );
                 }
             }
 
+            let shown_scratchpad = self.virtual_workspace_manager.shown_scratchpad_window(space);
             let floating_windows = self.active_floating_windows_in_workspace(space);
             for wid in floating_windows {
+                // The scratchpad window is placed below and must not have its
+                // position stored with the workspace it happens to be shown over.
+                // Docked windows are placed against their edge instead.
+                if Some(wid) == shown_scratchpad || self.docked.is_docked(wid) {
+                    continue;
+                }
                 ensure_visible_floating(
                     self,
                     &mut positions,
@@ -1223,17 +1636,44 @@ impl LayoutEngine {
                     None,
                     false,
                     &screen,
-                    &center_rect,
+                    cursor,
                     &window_size,
                 );
             }
+
+            if let Some(wid) = shown_scratchpad {
+                positions.insert(wid, center_rect(window_size(wid)));
+            }
+
+            let docked: Vec<_> = self
+                .docked
+                .windows()
+                .filter(|(wid, _)| {
+                    self.virtual_workspace_manager.workspace_for_window(space, *wid)
+                        == Some(active_workspace_id)
+                })
+                .collect();
+            for (wid, edge) in docked {
+                let frame = get_window_frame(wid).unwrap_or_else(|| center_rect(window_size(wid)));
+                let revealed = self.docked.is_revealed(wid);
+                positions.insert(wid, docked_frame(screen, frame, edge, revealed));
+            }
         }
 
-        let hidden_windows = self.virtual_workspace_manager.windows_in_inactive_workspaces(space);
+        let mut hidden_windows =
+            self.virtual_workspace_manager.windows_in_inactive_workspaces(space);
+        // Swallowed windows are parked with the hidden ones wherever they live.
+        for &wid in self.swallowed.values() {
+            if self.virtual_workspace_manager.workspace_for_window(space, wid).is_some()
+                && !hidden_windows.contains(&wid)
+            {
+                hidden_windows.push(wid);
+            }
+        }
         for (index, wid) in hidden_windows.into_iter().enumerate() {
             let original_frame = get_window_frame(wid);
 
-            if self.floating.is_floating(wid) {
+            if self.floating.is_floating(wid) && !self.docked.is_docked(wid) {
                 if let Some(workspace_id) =
                     self.virtual_workspace_manager.workspace_for_window(space, wid)
                 {
@@ -1246,10 +1686,15 @@ impl LayoutEngine {
                         original_frame,
                         true,
                         &screen,
-                        &center_rect,
+                        cursor,
                         &window_size,
                     );
                 }
+                // Sticky windows are normally carried along on workspace switches;
+                // one that has not been carried yet still stays where it is.
+                if self.virtual_workspace_manager.is_sticky(wid) {
+                    continue;
+                }
             }
 
             let original_size =
@@ -1277,17 +1722,18 @@ impl LayoutEngine {
         stack_line_horiz: crate::common::config::HorizontalPlacement,
         stack_line_vert: crate::common::config::VerticalPlacement,
     ) -> Vec<GroupContainerInfo> {
-        let layout_id = self.layout(space);
-        match &self.tree {
-            LayoutSystemKind::Traditional(s) => s.collect_group_containers_in_selection_path(
-                layout_id,
-                screen,
-                self.layout_settings.stack.stack_offset,
-                gaps,
-                stack_line_thickness,
-                stack_line_horiz,
-                stack_line_vert,
-            ),
+        let layout = self.layout(space);
+        match self.tree.system_for_layout(layout) {
+            Some((LayoutSystemKind::Traditional(s), layout_id)) => s
+                .collect_group_containers_in_selection_path(
+                    layout_id,
+                    screen,
+                    self.layout_settings.stack.stack_offset,
+                    gaps,
+                    stack_line_thickness,
+                    stack_line_horiz,
+                    stack_line_vert,
+                ),
             _ => Vec::new(),
         }
     }
@@ -1341,6 +1787,78 @@ impl LayoutEngine {
         None
     }
 
+    /// Frame for a floating window that has no position yet, following the
+    /// placement policy of its app rule or the global one.
+    fn initial_floating_frame(
+        &self,
+        wid: WindowId,
+        size: CGSize,
+        screen: CGRect,
+        cursor: Option<CGPoint>,
+        positions: &HashMap<WindowId, CGRect>,
+    ) -> CGRect {
+        let policy = self
+            .virtual_workspace_manager
+            .placement_for_window(wid)
+            .unwrap_or(self.layout_settings.floating.placement);
+        let occupied: Vec<CGRect> = positions
+            .iter()
+            .filter(|(other, _)| **other != wid && self.floating.is_floating(**other))
+            .map(|(_, rect)| *rect)
+            .collect();
+        let remembered = self
+            .app_bundle_ids
+            .get(&wid.pid)
+            .and_then(|bundle_id| self.placement_memory.recall(bundle_id));
+        placement::place(policy, screen, size, PlacementContext {
+            cursor,
+            occupied: &occupied,
+            remembered,
+        })
+    }
+
+    /// Replaces the stored frame of the focused floating window with
+    /// `update(screen, frame)`; the next layout moves the window there.
+    fn update_focused_floating_frame(
+        &mut self,
+        space: SpaceId,
+        update: impl FnOnce(CGRect, CGRect) -> CGRect,
+    ) {
+        let Some(wid) = self
+            .focused_window
+            .filter(|wid| self.floating.is_floating(*wid) && !self.docked.is_docked(*wid))
+        else {
+            return;
+        };
+        let Some(screen) = self.screen_frames.get(&space).copied() else {
+            return;
+        };
+        let Some(workspace_id) = self.virtual_workspace_manager.workspace_for_window(space, wid)
+        else {
+            return;
+        };
+        let Some(frame) =
+            self.virtual_workspace_manager.get_floating_position(space, workspace_id, wid)
+        else {
+            return;
+        };
+
+        let frame = update(screen, frame);
+        self.virtual_workspace_manager
+            .store_floating_position(space, workspace_id, wid, frame);
+        self.remember_floating_frames(&[(wid, frame)]);
+    }
+
+    /// Records where floating windows are so that new windows of the same app
+    /// can be placed there.
+    pub fn remember_floating_frames(&mut self, frames: &[(WindowId, CGRect)]) {
+        for &(wid, frame) in frames {
+            if let Some(bundle_id) = self.app_bundle_ids.get(&wid.pid) {
+                self.placement_memory.remember(bundle_id, frame);
+            }
+        }
+    }
+
     fn layout(&mut self, space: SpaceId) -> LayoutId {
         let workspace_id = match self.virtual_workspace_manager.active_workspace(space) {
             Some(ws) => ws,
@@ -1368,11 +1886,12 @@ impl LayoutEngine {
         } else {
             // Create active layouts for all workspaces on this space using a
             // reasonable default size so callers of `layout()` won't panic.
-            let workspaces = self
+            let workspaces: Vec<_> = self
                 .virtual_workspace_manager_mut()
                 .list_workspaces(space)
                 .into_iter()
-                .map(|(id, _)| id);
+                .map(|(id, _)| (id, self.virtual_workspace_manager.layout_mode(id)))
+                .collect();
             let default_size = CGSize::new(1000.0, 1000.0);
             self.workspace_layouts.ensure_active_for_space(
                 space,
@@ -1718,7 +2237,9 @@ impl LayoutEngine {
             self.workspace_layouts.ensure_active_for_space(
                 target_space,
                 target_screen_size,
-                workspace_ids.iter().map(|(id, _)| *id),
+                workspace_ids
+                    .iter()
+                    .map(|(id, _)| (*id, self.virtual_workspace_manager.layout_mode(*id))),
                 &mut self.tree,
             );
         }
@@ -1778,6 +2299,73 @@ impl LayoutEngine {
         self.virtual_workspace_manager.windows_in_active_workspace(space)
     }
 
+    /// The window the next tiled window on `space` will split, on which side, and
+    /// the share of the window's frame it will take.
+    pub fn preselection(&self, space: SpaceId) -> Option<(WindowId, Direction, f64)> {
+        let workspace_id = self.virtual_workspace_manager.active_workspace(space)?;
+        let layout = self.workspace_layouts.active(space, workspace_id)?;
+        self.tree.preselection(layout)
+    }
+
+    /// Workspace `index` of `space` in the order of `list_workspaces`, or the
+    /// active workspace when no index is given.
+    fn workspace_at(&mut self, space: SpaceId, index: Option<usize>) -> Option<VirtualWorkspaceId> {
+        match index {
+            Some(index) => self
+                .virtual_workspace_manager
+                .list_workspaces(space)
+                .get(index)
+                .map(|(id, _)| *id),
+            None => self.virtual_workspace_manager.active_workspace(space),
+        }
+    }
+
+    /// Describes the layout of a workspace on `space`, by default the active one.
+    pub fn layout_tree(&mut self, space: SpaceId, workspace: Option<usize>) -> Option<LayoutTree> {
+        let workspace_id = self.workspace_at(space, workspace)?;
+        let layout = self.workspace_layouts.active(space, workspace_id)?;
+        let mode = self.tree.mode_of(layout)?;
+        Some(LayoutTree::new(mode, self.tree.layout_tree(layout)))
+    }
+
+    /// Rebuilds the layout of a workspace on `space` from `tree`, switching the
+    /// workspace to the tree's layout mode first if it names one.
+    pub fn apply_layout_tree(
+        &mut self,
+        space: SpaceId,
+        workspace: Option<usize>,
+        tree: &LayoutTree,
+    ) -> Result<(), String> {
+        if tree.version != LAYOUT_TREE_VERSION {
+            return Err(format!(
+                "Unsupported layout tree version {} (expected {LAYOUT_TREE_VERSION})",
+                tree.version
+            ));
+        }
+        let Some(workspace_id) = self.workspace_at(space, workspace) else {
+            return Err(match workspace {
+                Some(index) => format!("Workspace {index} does not exist"),
+                None => "No active workspace".to_string(),
+            });
+        };
+        let Some(layout) = self.workspace_layouts.active(space, workspace_id) else {
+            return Err("Workspace has no layout".to_string());
+        };
+        if let Some(mode) = tree.mode
+            && self.tree.mode_of(layout) != Some(mode)
+        {
+            self.virtual_workspace_manager.set_layout_mode(workspace_id, mode);
+            for workspace_layout in
+                self.workspace_layouts.layouts_for_workspace(space, workspace_id)
+            {
+                self.tree.convert_layout(workspace_layout, mode);
+            }
+        }
+        self.workspace_layouts.mark_last_saved(space, workspace_id, layout);
+        self.tree.apply_layout_tree(layout, &tree.root);
+        Ok(())
+    }
+
     pub fn get_workspace_stats(&self) -> crate::model::virtual_workspace::WorkspaceStats {
         self.virtual_workspace_manager.get_stats()
     }
@@ -1786,6 +2374,99 @@ impl LayoutEngine {
         self.floating.is_floating(window_id)
     }
 
+    pub fn is_window_sticky(&self, window_id: WindowId) -> bool {
+        self.virtual_workspace_manager.is_sticky(window_id)
+    }
+
+    pub fn is_window_docked(&self, window_id: WindowId) -> bool { self.docked.is_docked(window_id) }
+
+    pub fn marked_window(&self, name: &str) -> Option<WindowId> { self.marks.get(name).copied() }
+
+    pub fn focused_window(&self) -> Option<WindowId> { self.focused_window }
+
+    /// Lets `child` take the place of `parent` in the active layout of `space`.
+    /// The parent is hidden until the child goes away. Returns whether the
+    /// parent was swallowed.
+    pub fn swallow_window(&mut self, space: SpaceId, parent: WindowId, child: WindowId) -> bool {
+        let Some(workspace_id) = self.virtual_workspace_manager.active_workspace(space) else {
+            return false;
+        };
+        let Some(layout) = self.workspace_layouts.active(space, workspace_id) else {
+            return false;
+        };
+        if parent == child
+            || self.floating.is_floating(child)
+            || self.virtual_workspace_manager.workspace_for_window(space, child)
+                != Some(workspace_id)
+            || !self.tree.contains_window(layout, parent)
+        {
+            return false;
+        }
+
+        self.tree.remove_window(child);
+        if !self.tree.replace_window(layout, parent, child) {
+            self.tree.add_window_after_selection(layout, child);
+            return false;
+        }
+        let _ = self.tree.select_window(layout, child);
+        self.swallowed.insert(child, parent);
+        true
+    }
+
+    pub fn is_window_swallowed(&self, window_id: WindowId) -> bool {
+        self.swallowed.values().any(|&parent| parent == window_id)
+    }
+
+    /// Gives the window swallowed by `child` its place back. If `child` is no
+    /// longer tiled, the window is added to its workspace's layout instead.
+    fn release_swallowed_window(&mut self, child: WindowId) {
+        let Some(parent) = self.swallowed.remove(&child) else {
+            return;
+        };
+
+        let mut restored = false;
+        for (_, layout) in self.workspace_layouts.all_layouts() {
+            if self.tree.contains_window(layout, child) {
+                restored |= self.tree.replace_window(layout, child, parent);
+            }
+        }
+        if restored {
+            return;
+        }
+
+        for space in self.workspace_layouts.spaces() {
+            if let Some(workspace_id) =
+                self.virtual_workspace_manager.workspace_for_window(space, parent)
+                && let Some(layout) = self.workspace_layouts.active(space, workspace_id)
+            {
+                self.tree.add_window_after_selection(layout, parent);
+                return;
+            }
+        }
+    }
+
+    /// Marks set on a window, sorted by name.
+    pub fn marks_for_window(&self, window_id: WindowId) -> Vec<String> {
+        let mut marks: Vec<String> = self
+            .marks
+            .iter()
+            .filter(|(_, wid)| **wid == window_id)
+            .map(|(name, _)| name.clone())
+            .collect();
+        marks.sort();
+        marks
+    }
+
+    /// Slides the hovered window into view if it is docked, and tucks any other
+    /// revealed docked window back against its edge. `None` means the pointer
+    /// is not over a managed window. Returns whether a docked window has to
+    /// move.
+    pub fn reveal_docked_window(&mut self, hovered: Option<WindowId>) -> bool {
+        self.docked.reveal(hovered)
+    }
+
+    pub fn has_docked_windows(&self) -> bool { self.docked.windows().next().is_some() }
+
     fn update_active_floating_windows(&mut self, space: SpaceId) {
         let windows_in_workspace =
             self.virtual_workspace_manager.windows_in_active_workspace(space);
@@ -1799,6 +2480,7 @@ impl LayoutEngine {
     ) {
         self.virtual_workspace_manager
             .store_current_floating_positions(space, floating_positions);
+        self.remember_floating_frames(floating_positions);
     }
 
     fn broadcast_workspace_changed(&self, space_id: SpaceId) {
@@ -1882,8 +2564,15 @@ impl LayoutEngine {
         }
     }
 
+    /// Repairs the sizes of traditional layouts after windows come and go. The
+    /// other systems keep their sizes valid on their own, and rebalancing them
+    /// would throw away sizes the user set.
     fn rebalance_all_layouts(&mut self) {
-        self.workspace_layouts.for_each_active(|layout| self.tree.rebalance(layout));
+        self.workspace_layouts.for_each_active(|layout| {
+            if self.tree.mode_of(layout) == Some(LayoutMode::Traditional) {
+                self.tree.rebalance(layout);
+            }
+        });
     }
 
     pub fn is_window_in_active_workspace(&self, space: SpaceId, window_id: WindowId) -> bool {
@@ -1898,6 +2587,7 @@ mod tests {
     use super::*;
     use crate::common::collections::HashMap;
     use crate::common::config::{LayoutSettings, VirtualWorkspaceSettings};
+    use crate::sys::geometry::CGRectExt;
 
     fn test_engine() -> LayoutEngine {
         LayoutEngine::new(
@@ -1944,4 +2634,296 @@ mod tests {
             None
         );
     }
+
+    #[test]
+    fn workspace_layout_mode_is_converted_and_restored() {
+        let mut engine = test_engine();
+        let space = SpaceId::new(1);
+        let windows: Vec<_> = (1..=3).map(|idx| WindowId::new(1, idx)).collect();
+
+        let _ = engine.handle_event(LayoutEvent::SpaceExposed(space, CGSize::new(1000.0, 1000.0)));
+        for &wid in &windows {
+            let _ = engine.handle_event(LayoutEvent::WindowAdded(space, wid));
+        }
+        let _ = engine.handle_command(
+            Some(space),
+            &[space],
+            &HashMap::default(),
+            LayoutCommand::SetWorkspaceLayoutMode(LayoutMode::Bsp),
+        );
+
+        let layout = engine.layout(space);
+        assert_eq!(engine.tree.mode_of(layout), Some(LayoutMode::Bsp));
+        assert_eq!(engine.tree.windows_in_layout(layout), windows);
+
+        let mut restored: LayoutEngine = ron::from_str(&engine.serialize_to_string()).unwrap();
+        let layout = restored.layout(space);
+        assert_eq!(restored.tree.mode_of(layout), Some(LayoutMode::Bsp));
+        assert_eq!(restored.tree.windows_in_layout(layout), windows);
+    }
+
+    #[test]
+    fn set_layout_mode_preserves_order_and_selection() {
+        let mut engine = test_engine();
+        let space = SpaceId::new(1);
+        let windows: Vec<_> = (1..=3).map(|idx| WindowId::new(1, idx)).collect();
+
+        let _ = engine.handle_event(LayoutEvent::SpaceExposed(space, CGSize::new(1000.0, 1000.0)));
+        for &wid in &windows {
+            let _ = engine.handle_event(LayoutEvent::WindowAdded(space, wid));
+        }
+        let _ = engine.handle_event(LayoutEvent::WindowFocused(space, windows[1]));
+
+        for mode in [LayoutMode::Bsp, LayoutMode::Traditional] {
+            let _ = engine.handle_command(
+                Some(space),
+                &[space],
+                &HashMap::default(),
+                LayoutCommand::SetLayoutMode(mode),
+            );
+            let layout = engine.layout(space);
+            assert_eq!(engine.tree.mode_of(layout), Some(mode));
+            assert_eq!(engine.tree.windows_in_layout(layout), windows);
+            assert_eq!(engine.selected_window(space), Some(windows[1]));
+        }
+    }
+
+    #[test]
+    fn new_floating_windows_follow_the_placement_policy() {
+        let mut settings = LayoutSettings::default();
+        settings.floating.placement = crate::common::config::FloatingPlacement::Cascade;
+        let mut engine = LayoutEngine::new(&VirtualWorkspaceSettings::default(), &settings, None);
+        let space = SpaceId::new(1);
+        let screen = CGRect::new(CGPoint::new(0.0, 0.0), CGSize::new(1000.0, 800.0));
+        let windows = [
+            WindowId::new(1, 1),
+            WindowId::new(1, 2),
+            WindowId::new(1, 3),
+        ];
+
+        let _ = engine.handle_event(LayoutEvent::SpaceExposed(space, screen.size));
+        for wid in windows {
+            let _ = engine.handle_event(LayoutEvent::WindowAdded(space, wid));
+        }
+        for wid in &windows[1..] {
+            let _ = engine.handle_event(LayoutEvent::WindowFocused(space, *wid));
+            let _ = engine.handle_command(
+                Some(space),
+                &[space],
+                &HashMap::default(),
+                LayoutCommand::ToggleWindowFloating,
+            );
+        }
+
+        let floating_origins = |engine: &mut LayoutEngine| {
+            let mut origins: Vec<_> = engine
+                .calculate_layout_with_virtual_workspaces(
+                    space,
+                    screen,
+                    &Default::default(),
+                    0.0,
+                    Default::default(),
+                    Default::default(),
+                    None,
+                    |_| Some(CGRect::new(CGPoint::new(0.0, 0.0), CGSize::new(300.0, 200.0))),
+                )
+                .into_iter()
+                .filter(|(wid, _)| *wid != windows[0])
+                .map(|(_, rect)| (rect.origin.x, rect.origin.y))
+                .collect();
+            origins.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            origins
+        };
+
+        assert_eq!(floating_origins(&mut engine), vec![(30.0, 30.0), (60.0, 60.0)]);
+        assert_eq!(floating_origins(&mut engine), vec![(30.0, 30.0), (60.0, 60.0)]);
+    }
+
+    #[test]
+    fn keyboard_commands_move_the_focused_floating_window() {
+        let mut engine = test_engine();
+        let space = SpaceId::new(1);
+        let screen = CGRect::new(CGPoint::new(0.0, 0.0), CGSize::new(1000.0, 800.0));
+        let wid = WindowId::new(1, 1);
+
+        let _ = engine.handle_event(LayoutEvent::SpaceExposed(space, screen.size));
+        let _ = engine.handle_event(LayoutEvent::WindowAdded(space, wid));
+        let _ = engine.handle_event(LayoutEvent::WindowFocused(space, wid));
+        let command = |engine: &mut LayoutEngine, cmd: LayoutCommand| {
+            let _ = engine.handle_command(Some(space), &[space], &HashMap::default(), cmd);
+        };
+        let frame_of = |engine: &mut LayoutEngine| {
+            engine
+                .calculate_layout_with_virtual_workspaces(
+                    space,
+                    screen,
+                    &Default::default(),
+                    0.0,
+                    Default::default(),
+                    Default::default(),
+                    None,
+                    |_| Some(CGRect::new(CGPoint::new(0.0, 0.0), CGSize::new(400.0, 300.0))),
+                )
+                .into_iter()
+                .find(|(other, _)| *other == wid)
+                .map(|(_, rect)| rect)
+                .unwrap()
+        };
+
+        command(&mut engine, LayoutCommand::ToggleWindowFloating);
+        assert_eq!(frame_of(&mut engine).origin, CGPoint::new(300.0, 250.0));
+
+        command(&mut engine, LayoutCommand::MoveFloatingWindow {
+            dx: -50.0,
+            dy: 20.0,
+        });
+        assert_eq!(frame_of(&mut engine).origin, CGPoint::new(250.0, 270.0));
+
+        command(&mut engine, LayoutCommand::ResizeFloatingWindow {
+            dw: 100.0,
+            dh: -100.0,
+        });
+        assert_eq!(frame_of(&mut engine).size, CGSize::new(500.0, 200.0));
+
+        command(&mut engine, LayoutCommand::CenterFloatingWindow);
+        assert_eq!(frame_of(&mut engine).origin, CGPoint::new(250.0, 300.0));
+
+        command(
+            &mut engine,
+            LayoutCommand::SnapFloatingWindow(SnapRegion::RightHalf),
+        );
+        assert_eq!(
+            frame_of(&mut engine),
+            CGRect::new(CGPoint::new(500.0, 0.0), CGSize::new(500.0, 800.0))
+        );
+
+        // The position is kept with the workspace.
+        let workspace = engine.active_workspace(space).unwrap();
+        assert_eq!(
+            engine.virtual_workspace_manager.get_floating_position(space, workspace, wid),
+            Some(CGRect::new(CGPoint::new(500.0, 0.0), CGSize::new(500.0, 800.0)))
+        );
+    }
+
+    #[test]
+    fn docked_window_peeks_and_slides_in_when_hovered() {
+        let mut engine = test_engine();
+        let space = SpaceId::new(1);
+        let screen = CGRect::new(CGPoint::new(0.0, 0.0), CGSize::new(1000.0, 800.0));
+        let (tiled, docked) = (WindowId::new(1, 1), WindowId::new(1, 2));
+
+        let _ = engine.handle_event(LayoutEvent::SpaceExposed(space, screen.size));
+        let _ = engine.handle_event(LayoutEvent::WindowAdded(space, tiled));
+        let _ = engine.handle_event(LayoutEvent::WindowAdded(space, docked));
+        let _ = engine.handle_event(LayoutEvent::WindowFocused(space, docked));
+        let response = engine.handle_command(
+            Some(space),
+            &[space],
+            &HashMap::default(),
+            LayoutCommand::DockWindow(Direction::Right),
+        );
+        assert_eq!(response.focus_window, Some(tiled));
+        assert_eq!(engine.tree.windows_in_layout(engine.layout(space)), vec![tiled]);
+
+        let frame = CGRect::new(CGPoint::new(100.0, 100.0), CGSize::new(300.0, 200.0));
+        let docked_frame_of = |engine: &mut LayoutEngine| {
+            engine
+                .calculate_layout_with_virtual_workspaces(
+                    space,
+                    screen,
+                    &Default::default(),
+                    0.0,
+                    Default::default(),
+                    Default::default(),
+                    None,
+                    |_| Some(frame),
+                )
+                .into_iter()
+                .find(|(wid, _)| *wid == docked)
+                .map(|(_, rect)| rect.origin)
+        };
+
+        assert_eq!(docked_frame_of(&mut engine), Some(CGPoint::new(990.0, 100.0)));
+        assert!(engine.reveal_docked_window(Some(docked)));
+        assert_eq!(docked_frame_of(&mut engine), Some(CGPoint::new(700.0, 100.0)));
+        assert!(engine.reveal_docked_window(Some(tiled)));
+        assert_eq!(docked_frame_of(&mut engine), Some(CGPoint::new(990.0, 100.0)));
+    }
+
+    #[test]
+    fn swallowed_window_comes_back_in_place() {
+        let mut engine = test_engine();
+        let space = SpaceId::new(1);
+        let (editor, terminal, viewer) =
+            (WindowId::new(1, 1), WindowId::new(2, 1), WindowId::new(3, 1));
+
+        let _ = engine.handle_event(LayoutEvent::SpaceExposed(space, CGSize::new(1000.0, 1000.0)));
+        for wid in [editor, terminal, viewer] {
+            let _ = engine.handle_event(LayoutEvent::WindowAdded(space, wid));
+        }
+        let layout = engine.layout(space);
+
+        assert!(engine.swallow_window(space, terminal, viewer));
+        assert_eq!(engine.tree.windows_in_layout(layout), vec![editor, viewer]);
+        assert!(engine.is_window_swallowed(terminal));
+        assert!(!engine.swallow_window(space, editor, viewer));
+
+        let screen = CGRect::new(CGPoint::new(0.0, 0.0), CGSize::new(1000.0, 1000.0));
+        let positions = engine.calculate_layout_with_virtual_workspaces(
+            space,
+            screen,
+            &Default::default(),
+            0.0,
+            Default::default(),
+            Default::default(),
+            None,
+            |_| None,
+        );
+        let terminal_frame = positions.iter().find(|(wid, _)| *wid == terminal).unwrap().1;
+        assert!(!screen.contains_rect(terminal_frame));
+
+        let _ = engine.handle_event(LayoutEvent::WindowRemoved(viewer));
+        assert_eq!(engine.tree.windows_in_layout(layout), vec![editor, terminal]);
+        assert!(!engine.is_window_swallowed(terminal));
+    }
+
+    #[test]
+    fn marks_follow_windows_and_survive_restore() {
+        let mut engine = test_engine();
+        let space = SpaceId::new(1);
+        let windows: Vec<_> = (1..=3).map(|idx| WindowId::new(1, idx)).collect();
+        let command = |engine: &mut LayoutEngine, command: LayoutCommand| {
+            let _ = engine.handle_command(Some(space), &[space], &HashMap::default(), command);
+        };
+
+        let _ = engine.handle_event(LayoutEvent::SpaceExposed(space, CGSize::new(1000.0, 1000.0)));
+        for &wid in &windows {
+            let _ = engine.handle_event(LayoutEvent::WindowAdded(space, wid));
+        }
+        let _ = engine.handle_event(LayoutEvent::WindowFocused(space, windows[0]));
+        command(&mut engine, LayoutCommand::SetMark { name: "editor".into() });
+        command(&mut engine, LayoutCommand::SetMark { name: "main".into() });
+        assert_eq!(engine.marks_for_window(windows[0]), vec!["editor", "main"]);
+
+        let _ = engine.handle_event(LayoutEvent::WindowFocused(space, windows[2]));
+        command(&mut engine, LayoutCommand::SetMark { name: "main".into() });
+        assert_eq!(engine.marked_window("main"), Some(windows[2]));
+        assert_eq!(engine.marks_for_window(windows[0]), vec!["editor"]);
+
+        command(&mut engine, LayoutCommand::SwapWithMark {
+            name: "editor".into(),
+        });
+        assert_eq!(engine.tree.windows_in_layout(engine.layout(space)), vec![
+            windows[2], windows[1], windows[0]
+        ]);
+
+        let mut restored: LayoutEngine = ron::from_str(&engine.serialize_to_string()).unwrap();
+        assert_eq!(restored.marked_window("editor"), Some(windows[0]));
+
+        let _ = restored.handle_event(LayoutEvent::WindowFocused(space, windows[2]));
+        command(&mut restored, LayoutCommand::UnsetMark { name: None });
+        assert_eq!(restored.marked_window("main"), None);
+        let _ = restored.handle_event(LayoutEvent::WindowRemoved(windows[0]));
+        assert_eq!(restored.marked_window("editor"), None);
+    }
 }
@@ -0,0 +1,227 @@
+//! Serializable description of a workspace's tiling layout.
+//!
+//! This is the document exchanged by the `get_layout_tree` and
+//! `apply_layout_tree` IPC requests. It looks like this:
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "mode": "traditional",
+//!   "root": {
+//!     "type": "container",
+//!     "kind": "split",
+//!     "orientation": "horizontal",
+//!     "proportions": [0.6, 0.4],
+//!     "children": [
+//!       { "type": "window", "window_id": { "pid": 501, "idx": 1 } },
+//!       {
+//!         "type": "container",
+//!         "kind": "stack",
+//!         "orientation": "vertical",
+//!         "proportions": [0.5, 0.5],
+//!         "children": [
+//!           { "type": "window", "window_id": { "pid": 502, "idx": 1 } },
+//!           { "type": "window", "window_id": { "pid": 502, "idx": 2 } }
+//!         ]
+//!       }
+//!     ]
+//!   }
+//! }
+//! ```
+//!
+//! `proportions` holds the share of the container each child gets, in child
+//! order. When applying a tree it may be omitted, in which case children are
+//! sized equally, and it does not have to sum to one.
+
+use serde::{Deserialize, Serialize};
+
+use crate::actor::app::WindowId;
+use crate::common::config::LayoutMode;
+use crate::layout_engine::{LayoutKind, Orientation};
+
+/// Version of the schema written by [`LayoutTree`] and the only one accepted
+/// when applying a tree.
+pub const LAYOUT_TREE_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LayoutTree {
+    pub version: u32,
+    /// Layout mode of the workspace. Applying a tree with a different mode
+    /// switches the workspace to it first; when omitted the current mode is
+    /// kept.
+    #[serde(default)]
+    pub mode: Option<LayoutMode>,
+    pub root: LayoutTreeNode,
+}
+
+impl LayoutTree {
+    pub fn new(mode: LayoutMode, root: LayoutTreeNode) -> Self {
+        LayoutTree {
+            version: LAYOUT_TREE_VERSION,
+            mode: Some(mode),
+            root,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LayoutTreeNode {
+    Container {
+        kind: ContainerKind,
+        orientation: Orientation,
+        #[serde(default)]
+        proportions: Vec<f64>,
+        children: Vec<LayoutTreeNode>,
+    },
+    Window {
+        window_id: WindowId,
+    },
+}
+
+/// How a container arranges its children along its orientation.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ContainerKind {
+    /// Children are placed side by side.
+    Split,
+    /// Children are stacked on top of each other with an offset.
+    Stack,
+}
+
+impl LayoutTreeNode {
+    /// A container that gives each of `children` an equal share.
+    pub fn split(orientation: Orientation, children: Vec<LayoutTreeNode>) -> Self {
+        let proportions = vec![1.0 / children.len().max(1) as f64; children.len()];
+        LayoutTreeNode::Container {
+            kind: ContainerKind::Split,
+            orientation,
+            proportions,
+            children,
+        }
+    }
+
+    /// Windows under this node in depth-first order.
+    pub fn windows(&self) -> Vec<WindowId> {
+        let mut windows = Vec::new();
+        self.collect_windows(&mut windows);
+        windows
+    }
+
+    /// Orders `windows` the way they appear under this node. Windows the node
+    /// does not mention keep their relative order at the end, and windows
+    /// that are not in `windows` are dropped.
+    pub fn arrange(&self, windows: &[WindowId]) -> Vec<WindowId> {
+        let mut arranged: Vec<WindowId> = Vec::with_capacity(windows.len());
+        for wid in self.windows() {
+            if windows.contains(&wid) && !arranged.contains(&wid) {
+                arranged.push(wid);
+            }
+        }
+        let rest: Vec<_> = windows.iter().filter(|wid| !arranged.contains(wid)).copied().collect();
+        arranged.extend(rest);
+        arranged
+    }
+
+    fn collect_windows(&self, windows: &mut Vec<WindowId>) {
+        match self {
+            LayoutTreeNode::Window { window_id } => windows.push(*window_id),
+            LayoutTreeNode::Container { children, .. } => {
+                for child in children {
+                    child.collect_windows(windows);
+                }
+            }
+        }
+    }
+
+    /// Shares of a container's children normalized to sum to one. Missing or
+    /// non-positive proportions fall back to an equal share.
+    pub fn shares(&self) -> Vec<f64> {
+        let LayoutTreeNode::Container { proportions, children, .. } = self else {
+            return Vec::new();
+        };
+        let equal = 1.0 / children.len().max(1) as f64;
+        let shares: Vec<f64> = (0..children.len())
+            .map(|i| {
+                proportions
+                    .get(i)
+                    .copied()
+                    .filter(|p| p.is_finite() && *p > 0.0)
+                    .unwrap_or(equal)
+            })
+            .collect();
+        let sum: f64 = shares.iter().sum();
+        shares.into_iter().map(|share| share / sum).collect()
+    }
+}
+
+impl ContainerKind {
+    pub fn of(kind: LayoutKind) -> Self {
+        if kind.is_group() {
+            ContainerKind::Stack
+        } else {
+            ContainerKind::Split
+        }
+    }
+
+    pub fn layout_kind(self, orientation: Orientation) -> LayoutKind {
+        match self {
+            ContainerKind::Split => LayoutKind::from(orientation),
+            ContainerKind::Stack => LayoutKind::stack_with_offset(orientation),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn w(idx: u32) -> WindowId { WindowId::new(1, idx) }
+
+    #[test]
+    fn parses_the_documented_schema() {
+        let json = r#"{
+            "version": 1,
+            "root": {
+                "type": "container",
+                "kind": "split",
+                "orientation": "horizontal",
+                "proportions": [3, 1],
+                "children": [
+                    { "type": "window", "window_id": { "pid": 1, "idx": 1 } },
+                    {
+                        "type": "container",
+                        "kind": "stack",
+                        "orientation": "vertical",
+                        "children": [
+                            { "type": "window", "window_id": { "pid": 1, "idx": 2 } },
+                            { "type": "window", "window_id": { "pid": 1, "idx": 3 } }
+                        ]
+                    }
+                ]
+            }
+        }"#;
+        let tree: LayoutTree = serde_json::from_str(json).unwrap();
+        assert_eq!(tree.version, LAYOUT_TREE_VERSION);
+        assert_eq!(tree.mode, None);
+        assert_eq!(tree.root.windows(), vec![w(1), w(2), w(3)]);
+        assert_eq!(tree.root.shares(), vec![0.75, 0.25]);
+        let LayoutTreeNode::Container { children, .. } = &tree.root else {
+            panic!()
+        };
+        assert_eq!(children[1].shares(), vec![0.5, 0.5]);
+    }
+
+    #[test]
+    fn round_trips_through_json() {
+        let tree = LayoutTree::new(
+            LayoutMode::Bsp,
+            LayoutTreeNode::split(Orientation::Vertical, vec![
+                LayoutTreeNode::Window { window_id: w(1) },
+                LayoutTreeNode::Window { window_id: w(2) },
+            ]),
+        );
+        let json = serde_json::to_string(&tree).unwrap();
+        assert_eq!(serde_json::from_str::<LayoutTree>(&json).unwrap(), tree);
+    }
+}
@@ -0,0 +1,394 @@
+use objc2_core_foundation::{CGPoint, CGRect, CGSize};
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+
+use crate::common::collections::HashMap;
+use crate::common::config::FloatingPlacement;
+use crate::sys::geometry::{CGRectDef, CGRectExt};
+
+/// Offset between successive cascaded windows.
+const CASCADE_STEP: f64 = 30.0;
+
+/// Smallest size keyboard resizing shrinks a floating window to.
+const MIN_FLOATING_SIZE: f64 = 100.0;
+
+/// Part of the screen a floating window can be snapped to.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SnapRegion {
+    LeftHalf,
+    RightHalf,
+    TopHalf,
+    BottomHalf,
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+    LeftThird,
+    CenterThird,
+    RightThird,
+}
+
+/// What a placement policy gets to look at besides the screen and window size.
+#[derive(Default, Clone, Copy)]
+pub(crate) struct PlacementContext<'a> {
+    pub(crate) cursor: Option<CGPoint>,
+    /// Frames of the other floating windows on the same screen.
+    pub(crate) occupied: &'a [CGRect],
+    /// Last frame of a window of the same app, for [`FloatingPlacement::Remember`].
+    pub(crate) remembered: Option<CGRect>,
+}
+
+/// Frame for a floating window of `size` that has no position yet. The result
+/// always lies within `screen`, shrinking the window if it does not fit.
+pub(crate) fn place(
+    policy: FloatingPlacement,
+    screen: CGRect,
+    size: CGSize,
+    ctx: PlacementContext,
+) -> CGRect {
+    let rect = match policy {
+        FloatingPlacement::Center => centered(screen, size),
+        FloatingPlacement::UnderCursor => match ctx.cursor {
+            Some(cursor) => CGRect::new(
+                CGPoint::new(cursor.x - size.width / 2.0, cursor.y - size.height / 2.0),
+                size,
+            ),
+            None => centered(screen, size),
+        },
+        FloatingPlacement::Cascade => cascade(screen, size, ctx.occupied),
+        FloatingPlacement::Smart => smart(screen, size, ctx.occupied),
+        FloatingPlacement::Remember => ctx.remembered.unwrap_or_else(|| centered(screen, size)),
+    };
+    clamp_to_screen(screen, rect)
+}
+
+fn centered(screen: CGRect, size: CGSize) -> CGRect {
+    let center = screen.mid();
+    CGRect::new(
+        CGPoint::new(center.x - size.width / 2.0, center.y - size.height / 2.0),
+        size,
+    )
+}
+
+fn clamp_to_screen(screen: CGRect, rect: CGRect) -> CGRect {
+    let size = CGSize::new(
+        rect.size.width.min(screen.size.width),
+        rect.size.height.min(screen.size.height),
+    );
+    let (min, max) = (screen.min(), screen.max());
+    CGRect::new(
+        CGPoint::new(
+            rect.origin.x.clamp(min.x, max.x - size.width),
+            rect.origin.y.clamp(min.y, max.y - size.height),
+        ),
+        size,
+    )
+}
+
+/// Steps down and to the right from the top-left corner until no other window
+/// starts at the same spot, wrapping around when the window would leave the
+/// screen.
+fn cascade(screen: CGRect, size: CGSize, occupied: &[CGRect]) -> CGRect {
+    let start = CGPoint::new(screen.min().x + CASCADE_STEP, screen.min().y + CASCADE_STEP);
+    let taken = |origin: CGPoint| {
+        occupied.iter().any(|rect| {
+            (rect.origin.x - origin.x).abs() < CASCADE_STEP / 2.0
+                && (rect.origin.y - origin.y).abs() < CASCADE_STEP / 2.0
+        })
+    };
+
+    let mut origin = start;
+    for _ in 0..=occupied.len() {
+        if !taken(origin) {
+            break;
+        }
+        origin = CGPoint::new(origin.x + CASCADE_STEP, origin.y + CASCADE_STEP);
+        if origin.x + size.width > screen.max().x || origin.y + size.height > screen.max().y {
+            origin = start;
+            break;
+        }
+    }
+    CGRect::new(origin, size)
+}
+
+/// Tries the screen edges, the center and the spots right next to every
+/// occupied frame, and picks the one overlapping the occupied frames the
+/// least. Ties go to the spot closest to the center of the screen.
+fn smart(screen: CGRect, size: CGSize, occupied: &[CGRect]) -> CGRect {
+    let center = centered(screen, size);
+    let mut xs = vec![screen.min().x, screen.max().x - size.width, center.origin.x];
+    let mut ys = vec![
+        screen.min().y,
+        screen.max().y - size.height,
+        center.origin.y,
+    ];
+    for rect in occupied {
+        xs.extend([rect.max().x, rect.min().x - size.width]);
+        ys.extend([rect.max().y, rect.min().y - size.height]);
+    }
+
+    let overlap = |candidate: &CGRect| -> f64 {
+        occupied.iter().map(|rect| candidate.intersection(rect).area()).sum()
+    };
+    let distance = |candidate: &CGRect| {
+        let (a, b) = (candidate.mid(), screen.mid());
+        (a.x - b.x).powi(2) + (a.y - b.y).powi(2)
+    };
+
+    let mut best = clamp_to_screen(screen, center);
+    let mut best_score = (overlap(&best), distance(&best));
+    for &x in &xs {
+        for &y in &ys {
+            let candidate = clamp_to_screen(screen, CGRect::new(CGPoint::new(x, y), size));
+            let score = (overlap(&candidate), distance(&candidate));
+            if score.0 < best_score.0 || (score.0 == best_score.0 && score.1 < best_score.1) {
+                best = candidate;
+                best_score = score;
+            }
+        }
+    }
+    best
+}
+
+/// `frame` moved by `dx`/`dy`, stopping at the edges of `screen`.
+pub(crate) fn offset_frame(screen: CGRect, frame: CGRect, dx: f64, dy: f64) -> CGRect {
+    let moved = CGRect::new(
+        CGPoint::new(frame.origin.x + dx, frame.origin.y + dy),
+        frame.size,
+    );
+    clamp_to_screen(screen, moved)
+}
+
+/// `frame` grown by `dw`/`dh` (shrunk when negative) with its top-left corner
+/// fixed, as far as `screen` and [`MIN_FLOATING_SIZE`] allow.
+pub(crate) fn resize_frame(screen: CGRect, frame: CGRect, dw: f64, dh: f64) -> CGRect {
+    let size = CGSize::new(
+        (frame.size.width + dw).clamp(MIN_FLOATING_SIZE, screen.size.width.max(MIN_FLOATING_SIZE)),
+        (frame.size.height + dh)
+            .clamp(MIN_FLOATING_SIZE, screen.size.height.max(MIN_FLOATING_SIZE)),
+    );
+    clamp_to_screen(screen, CGRect::new(frame.origin, size))
+}
+
+/// `frame` with its size kept, moved to the middle of `screen`.
+pub(crate) fn center_frame(screen: CGRect, frame: CGRect) -> CGRect {
+    clamp_to_screen(screen, centered(screen, frame.size))
+}
+
+pub(crate) fn snap_frame(screen: CGRect, region: SnapRegion) -> CGRect {
+    let (x, y) = (screen.origin.x, screen.origin.y);
+    let (w, h) = (screen.size.width, screen.size.height);
+    let (half_w, half_h, third_w) = (w / 2.0, h / 2.0, w / 3.0);
+    let (x, y, w, h) = match region {
+        SnapRegion::LeftHalf => (x, y, half_w, h),
+        SnapRegion::RightHalf => (x + half_w, y, half_w, h),
+        SnapRegion::TopHalf => (x, y, w, half_h),
+        SnapRegion::BottomHalf => (x, y + half_h, w, half_h),
+        SnapRegion::TopLeft => (x, y, half_w, half_h),
+        SnapRegion::TopRight => (x + half_w, y, half_w, half_h),
+        SnapRegion::BottomLeft => (x, y + half_h, half_w, half_h),
+        SnapRegion::BottomRight => (x + half_w, y + half_h, half_w, half_h),
+        SnapRegion::LeftThird => (x, y, third_w, h),
+        SnapRegion::CenterThird => (x + third_w, y, third_w, h),
+        SnapRegion::RightThird => (x + 2.0 * third_w, y, third_w, h),
+    };
+    CGRect::new(CGPoint::new(x, y), CGSize::new(w, h))
+}
+
+/// Last floating frame of each app, keyed by bundle id.
+#[serde_as]
+#[derive(Serialize, Deserialize, Default)]
+pub(crate) struct PlacementMemory {
+    #[serde_as(as = "HashMap<_, CGRectDef>")]
+    frames: HashMap<String, CGRect>,
+}
+
+impl PlacementMemory {
+    pub(crate) fn remember(&mut self, bundle_id: &str, frame: CGRect) {
+        self.frames.insert(bundle_id.to_owned(), frame);
+    }
+
+    pub(crate) fn recall(&self, bundle_id: &str) -> Option<CGRect> {
+        self.frames.get(bundle_id).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn screen() -> CGRect { CGRect::new(CGPoint::new(0.0, 0.0), CGSize::new(1000.0, 800.0)) }
+
+    fn size() -> CGSize { CGSize::new(400.0, 300.0) }
+
+    fn rect(x: f64, y: f64, w: f64, h: f64) -> CGRect {
+        CGRect::new(CGPoint::new(x, y), CGSize::new(w, h))
+    }
+
+    fn overlap(candidate: CGRect, occupied: &[CGRect]) -> f64 {
+        occupied.iter().map(|r| candidate.intersection(r).area()).sum()
+    }
+
+    #[test]
+    fn center_and_fallbacks_center_the_window() {
+        let expected = rect(300.0, 250.0, 400.0, 300.0);
+        for policy in [
+            FloatingPlacement::Center,
+            FloatingPlacement::UnderCursor,
+            FloatingPlacement::Remember,
+            FloatingPlacement::Smart,
+        ] {
+            assert_eq!(place(policy, screen(), size(), Default::default()), expected);
+        }
+    }
+
+    #[test]
+    fn under_cursor_stays_on_screen() {
+        let ctx = PlacementContext {
+            cursor: Some(CGPoint::new(950.0, 400.0)),
+            ..Default::default()
+        };
+        let placed = place(FloatingPlacement::UnderCursor, screen(), size(), ctx);
+        assert_eq!(placed, rect(600.0, 250.0, 400.0, 300.0));
+    }
+
+    #[test]
+    fn remember_reuses_the_last_frame() {
+        let ctx = PlacementContext {
+            remembered: Some(rect(50.0, 60.0, 200.0, 100.0)),
+            ..Default::default()
+        };
+        let placed = place(FloatingPlacement::Remember, screen(), size(), ctx);
+        assert_eq!(placed, rect(50.0, 60.0, 200.0, 100.0));
+    }
+
+    #[test]
+    fn cascade_steps_past_taken_spots_and_wraps() {
+        let first = place(FloatingPlacement::Cascade, screen(), size(), Default::default());
+        assert_eq!(first.origin, CGPoint::new(30.0, 30.0));
+
+        let occupied = [first, rect(60.0, 60.0, 400.0, 300.0)];
+        let ctx = PlacementContext {
+            occupied: &occupied,
+            ..Default::default()
+        };
+        let third = place(FloatingPlacement::Cascade, screen(), size(), ctx);
+        assert_eq!(third.origin, CGPoint::new(90.0, 90.0));
+
+        let occupied: Vec<_> = (1..=20)
+            .map(|i| rect(30.0 * f64::from(i), 30.0 * f64::from(i), 400.0, 300.0))
+            .collect();
+        let ctx = PlacementContext {
+            occupied: &occupied,
+            ..Default::default()
+        };
+        let wrapped = place(FloatingPlacement::Cascade, screen(), size(), ctx);
+        assert_eq!(wrapped.origin, CGPoint::new(30.0, 30.0));
+    }
+
+    #[test]
+    fn smart_avoids_overlap_when_there_is_room() {
+        let occupied = [rect(400.0, 300.0, 200.0, 200.0)];
+        let ctx = PlacementContext {
+            occupied: &occupied,
+            ..Default::default()
+        };
+        let placed = place(FloatingPlacement::Smart, screen(), size(), ctx);
+        assert_eq!(overlap(placed, &occupied), 0.0);
+        assert!(screen().contains_rect(placed));
+
+        let occupied = [rect(0.0, 0.0, 500.0, 800.0), rect(500.0, 0.0, 500.0, 400.0)];
+        let ctx = PlacementContext {
+            occupied: &occupied,
+            ..Default::default()
+        };
+        let placed = place(FloatingPlacement::Smart, screen(), size(), ctx);
+        assert_eq!(overlap(placed, &occupied), 0.0);
+        assert_eq!(placed.origin.x, 500.0);
+    }
+
+    #[test]
+    fn keyboard_moves_and_resizes_stay_on_screen() {
+        let frame = rect(100.0, 100.0, 400.0, 300.0);
+        assert_eq!(
+            offset_frame(screen(), frame, 50.0, -20.0),
+            rect(150.0, 80.0, 400.0, 300.0)
+        );
+        assert_eq!(
+            offset_frame(screen(), frame, 900.0, -500.0),
+            rect(600.0, 0.0, 400.0, 300.0)
+        );
+
+        assert_eq!(
+            resize_frame(screen(), frame, 100.0, 50.0),
+            rect(100.0, 100.0, 500.0, 350.0)
+        );
+        assert_eq!(
+            resize_frame(screen(), frame, -1000.0, 0.0).size,
+            CGSize::new(100.0, 300.0)
+        );
+        assert_eq!(
+            resize_frame(screen(), frame, 2000.0, 0.0),
+            rect(0.0, 100.0, 1000.0, 300.0)
+        );
+
+        assert_eq!(center_frame(screen(), frame), rect(300.0, 250.0, 400.0, 300.0));
+    }
+
+    #[test]
+    fn snap_regions_tile_the_screen() {
+        let screen = rect(0.0, 25.0, 1200.0, 800.0);
+        assert_eq!(
+            snap_frame(screen, SnapRegion::RightHalf),
+            rect(600.0, 25.0, 600.0, 800.0)
+        );
+        assert_eq!(
+            snap_frame(screen, SnapRegion::BottomHalf),
+            rect(0.0, 425.0, 1200.0, 400.0)
+        );
+        assert_eq!(
+            snap_frame(screen, SnapRegion::TopRight),
+            rect(600.0, 25.0, 600.0, 400.0)
+        );
+        assert_eq!(
+            snap_frame(screen, SnapRegion::CenterThird),
+            rect(400.0, 25.0, 400.0, 800.0)
+        );
+
+        let quarters = [
+            SnapRegion::TopLeft,
+            SnapRegion::TopRight,
+            SnapRegion::BottomLeft,
+            SnapRegion::BottomRight,
+        ];
+        let thirds = [
+            SnapRegion::LeftThird,
+            SnapRegion::CenterThird,
+            SnapRegion::RightThird,
+        ];
+        for regions in [&quarters[..], &thirds[..]] {
+            let rects: Vec<_> = regions.iter().map(|region| snap_frame(screen, *region)).collect();
+            let area: f64 = rects.iter().map(|rect| rect.area()).sum();
+            assert_eq!(area, screen.area());
+            for (i, a) in rects.iter().enumerate() {
+                assert!(screen.contains_rect(*a));
+                for b in &rects[i + 1..] {
+                    assert_eq!(a.intersection(b).area(), 0.0);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn smart_minimizes_overlap_on_a_full_screen() {
+        let occupied = [rect(0.0, 0.0, 1000.0, 800.0), rect(0.0, 0.0, 600.0, 800.0)];
+        let ctx = PlacementContext {
+            occupied: &occupied,
+            ..Default::default()
+        };
+        let placed = place(FloatingPlacement::Smart, screen(), size(), ctx);
+        assert_eq!(overlap(placed, &occupied), 400.0 * 300.0);
+        assert!(placed.origin.x >= 600.0);
+    }
+}
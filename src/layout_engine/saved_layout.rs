@@ -0,0 +1,356 @@
+//! Layouts saved under a name so they can be restored later, including after
+//! the windows in them have been closed and reopened.
+//!
+//! A saved layout is a [`LayoutTree`] whose windows are described by their
+//! application and title instead of by [`WindowId`], which does not outlive
+//! the application. Each layout is stored as a JSON file named after it in
+//! `~/.rift/layouts/`.
+
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{Context, bail};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::actor::app::WindowId;
+use crate::common::config::{LayoutMode, layouts_dir};
+use crate::layout_engine::{
+    ContainerKind, LAYOUT_TREE_VERSION, LayoutTree, LayoutTreeNode, Orientation,
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SavedLayout {
+    pub version: u32,
+    #[serde(default)]
+    pub mode: Option<LayoutMode>,
+    pub root: SavedLayoutNode,
+}
+
+/// Same as [`LayoutTreeNode`], with windows described by a [`SavedWindow`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SavedLayoutNode {
+    Container {
+        kind: ContainerKind,
+        orientation: Orientation,
+        #[serde(default)]
+        proportions: Vec<f64>,
+        children: Vec<SavedLayoutNode>,
+    },
+    Window(SavedWindow),
+}
+
+/// Which window goes in a slot of a saved layout. Fields that are not set
+/// match any window.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct SavedWindow {
+    /// Bundle identifier of the window's application
+    #[serde(default)]
+    pub app_id: Option<String>,
+    /// Regular expression the window title must match. Saving a layout
+    /// records the exact title, which can be loosened by editing the file.
+    #[serde(default)]
+    pub title_regex: Option<String>,
+}
+
+impl SavedWindow {
+    pub fn new(app_id: Option<String>, title: &str) -> Self {
+        SavedWindow {
+            app_id,
+            title_regex: Some(format!("^{}$", regex::escape(title))),
+        }
+    }
+
+    fn matches_app(&self, app_id: Option<&str>) -> bool {
+        self.app_id.as_deref().is_none_or(|id| Some(id) == app_id)
+    }
+
+    fn matches_title(&self, title: &str) -> bool {
+        let Some(pattern) = &self.title_regex else { return true };
+        match regex::Regex::new(pattern) {
+            Ok(re) => re.is_match(title),
+            Err(e) => {
+                warn!("Invalid title_regex '{}' in saved layout: {}", pattern, e);
+                false
+            }
+        }
+    }
+}
+
+/// A window that can be placed in a saved layout.
+#[derive(Debug, Clone)]
+pub struct WindowCandidate {
+    pub id: WindowId,
+    pub app_id: Option<String>,
+    pub title: String,
+}
+
+impl SavedLayout {
+    /// Describes `tree` with `describe`, dropping windows it returns `None` for.
+    pub fn from_tree(
+        tree: &LayoutTree,
+        describe: impl Fn(WindowId) -> Option<SavedWindow>,
+    ) -> Self {
+        fn convert(
+            node: &LayoutTreeNode,
+            describe: &impl Fn(WindowId) -> Option<SavedWindow>,
+        ) -> Option<SavedLayoutNode> {
+            match node {
+                LayoutTreeNode::Window { window_id } => {
+                    describe(*window_id).map(SavedLayoutNode::Window)
+                }
+                LayoutTreeNode::Container {
+                    kind,
+                    orientation,
+                    proportions,
+                    children,
+                } => {
+                    let (proportions, children): (Vec<_>, Vec<_>) = children
+                        .iter()
+                        .enumerate()
+                        .filter_map(|(i, child)| {
+                            let child = convert(child, describe)?;
+                            Some((proportions.get(i).copied().unwrap_or(0.0), child))
+                        })
+                        .unzip();
+                    Some(SavedLayoutNode::Container {
+                        kind: *kind,
+                        orientation: *orientation,
+                        proportions,
+                        children,
+                    })
+                }
+            }
+        }
+
+        SavedLayout {
+            version: LAYOUT_TREE_VERSION,
+            mode: tree.mode,
+            root: convert(&tree.root, &describe)
+                .unwrap_or_else(|| SavedLayoutNode::empty(Orientation::Horizontal)),
+        }
+    }
+
+    /// Fills the saved layout with `candidates`. Each slot takes the first
+    /// unused window whose application and title match; slots left empty
+    /// after that take any unused window of the same application, and slots
+    /// still empty are dropped.
+    pub fn to_tree(&self, candidates: &[WindowCandidate]) -> LayoutTree {
+        let mut slots = Vec::new();
+        self.root.collect_windows(&mut slots);
+
+        let mut assigned: Vec<Option<WindowId>> = vec![None; slots.len()];
+        let mut used = vec![false; candidates.len()];
+        for strict in [true, false] {
+            for (slot, assignment) in slots.iter().zip(assigned.iter_mut()) {
+                if assignment.is_some() {
+                    continue;
+                }
+                let found = (0..candidates.len()).find(|&i| {
+                    !used[i]
+                        && slot.matches_app(candidates[i].app_id.as_deref())
+                        && (!strict || slot.matches_title(&candidates[i].title))
+                });
+                if let Some(i) = found {
+                    used[i] = true;
+                    *assignment = Some(candidates[i].id);
+                }
+            }
+        }
+
+        let mut assigned = assigned.into_iter();
+        LayoutTree {
+            version: LAYOUT_TREE_VERSION,
+            mode: self.mode,
+            root: self
+                .root
+                .to_tree_node(&mut assigned)
+                .unwrap_or_else(|| LayoutTreeNode::split(Orientation::Horizontal, Vec::new())),
+        }
+    }
+}
+
+impl SavedLayoutNode {
+    fn empty(orientation: Orientation) -> Self {
+        SavedLayoutNode::Container {
+            kind: ContainerKind::Split,
+            orientation,
+            proportions: Vec::new(),
+            children: Vec::new(),
+        }
+    }
+
+    fn collect_windows<'a>(&'a self, windows: &mut Vec<&'a SavedWindow>) {
+        match self {
+            SavedLayoutNode::Window(window) => windows.push(window),
+            SavedLayoutNode::Container { children, .. } => {
+                for child in children {
+                    child.collect_windows(windows);
+                }
+            }
+        }
+    }
+
+    /// Replaces the windows in this node, in order, with the ids from
+    /// `assigned`, dropping windows without one and containers left empty.
+    fn to_tree_node(
+        &self,
+        assigned: &mut impl Iterator<Item = Option<WindowId>>,
+    ) -> Option<LayoutTreeNode> {
+        match self {
+            SavedLayoutNode::Window(_) => {
+                assigned.next().flatten().map(|window_id| LayoutTreeNode::Window { window_id })
+            }
+            SavedLayoutNode::Container {
+                kind,
+                orientation,
+                proportions,
+                children,
+            } => {
+                let (proportions, children): (Vec<_>, Vec<_>) = children
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, child)| {
+                        let child = child.to_tree_node(assigned)?;
+                        Some((proportions.get(i).copied().unwrap_or(0.0), child))
+                    })
+                    .unzip();
+                (!children.is_empty()).then(|| LayoutTreeNode::Container {
+                    kind: *kind,
+                    orientation: *orientation,
+                    proportions,
+                    children,
+                })
+            }
+        }
+    }
+}
+
+/// Directory of saved layouts, one `<name>.json` file per layout.
+pub struct SavedLayoutStore {
+    dir: PathBuf,
+}
+
+impl Default for SavedLayoutStore {
+    fn default() -> Self { SavedLayoutStore::new(layouts_dir()) }
+}
+
+impl SavedLayoutStore {
+    pub fn new(dir: PathBuf) -> Self { SavedLayoutStore { dir } }
+
+    pub fn save(&self, name: &str, layout: &SavedLayout) -> anyhow::Result<()> {
+        let path = self.path(name)?;
+        fs::create_dir_all(&self.dir)?;
+        fs::write(&path, serde_json::to_string_pretty(layout)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    pub fn load(&self, name: &str) -> anyhow::Result<SavedLayout> {
+        let path = self.path(name)?;
+        let json =
+            fs::read_to_string(&path).with_context(|| format!("No saved layout named '{name}'"))?;
+        let layout: SavedLayout = serde_json::from_str(&json)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        if layout.version != LAYOUT_TREE_VERSION {
+            bail!("Unsupported saved layout version {}", layout.version);
+        }
+        Ok(layout)
+    }
+
+    /// Names of the saved layouts, sorted.
+    pub fn list(&self) -> anyhow::Result<Vec<String>> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut names = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "json")
+                && let Some(name) = path.file_stem().and_then(|stem| stem.to_str())
+            {
+                names.push(name.to_string());
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+
+    pub fn delete(&self, name: &str) -> anyhow::Result<()> {
+        let path = self.path(name)?;
+        fs::remove_file(&path).with_context(|| format!("No saved layout named '{name}'"))
+    }
+
+    fn path(&self, name: &str) -> anyhow::Result<PathBuf> {
+        if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+            bail!("Invalid layout name '{name}'");
+        }
+        Ok(self.dir.join(format!("{name}.json")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn w(idx: u32) -> WindowId { WindowId::new(1, idx) }
+
+    fn candidate(idx: u32, app_id: &str, title: &str) -> WindowCandidate {
+        WindowCandidate {
+            id: w(idx),
+            app_id: Some(app_id.to_string()),
+            title: title.to_string(),
+        }
+    }
+
+    fn saved() -> SavedLayout {
+        let tree = LayoutTree::new(LayoutMode::Traditional, LayoutTreeNode::Container {
+            kind: ContainerKind::Split,
+            orientation: Orientation::Horizontal,
+            proportions: vec![0.7, 0.3],
+            children: vec![
+                LayoutTreeNode::Window { window_id: w(1) },
+                LayoutTreeNode::Window { window_id: w(2) },
+            ],
+        });
+        SavedLayout::from_tree(&tree, |wid| {
+            Some(match wid.idx.get() {
+                1 => SavedWindow::new(Some("com.editor".into()), "main.rs (rift)"),
+                _ => SavedWindow::new(Some("com.terminal".into()), "zsh"),
+            })
+        })
+    }
+
+    #[test]
+    fn restores_windows_by_app_and_title() {
+        let tree = saved().to_tree(&[
+            candidate(10, "com.terminal", "vim"),
+            candidate(11, "com.terminal", "zsh"),
+            candidate(12, "com.editor", "main.rs (rift)"),
+        ]);
+        assert_eq!(tree.root.windows(), vec![w(12), w(11)]);
+        assert_eq!(tree.root.shares(), vec![0.7, 0.3]);
+    }
+
+    #[test]
+    fn falls_back_to_any_window_of_the_app_and_drops_unmatched_slots() {
+        let tree = saved().to_tree(&[candidate(10, "com.terminal", "vim")]);
+        assert_eq!(tree.root.windows(), vec![w(10)]);
+        assert_eq!(tree.mode, Some(LayoutMode::Traditional));
+    }
+
+    #[test]
+    fn store_saves_lists_and_deletes() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = SavedLayoutStore::new(dir.path().join("layouts"));
+        assert_eq!(store.list().unwrap(), Vec::<String>::new());
+
+        store.save("coding", &saved()).unwrap();
+        assert_eq!(store.list().unwrap(), vec!["coding".to_string()]);
+        assert_eq!(store.load("coding").unwrap(), saved());
+
+        store.delete("coding").unwrap();
+        assert!(store.load("coding").is_err());
+        assert!(store.save("../escape", &saved()).is_err());
+    }
+}
@@ -3,10 +3,35 @@ use objc2_core_foundation::CGRect;
 use serde::{Deserialize, Serialize};
 
 use crate::actor::app::{WindowId, pid_t};
-use crate::layout_engine::{Direction, LayoutKind};
+use crate::common::config::{LayoutMode, LayoutSettings};
+use crate::layout_engine::{Direction, LayoutKind, LayoutTreeNode, Orientation};
 
 slotmap::new_key_type! { pub struct LayoutId; }
 
+/// Size to give the selected node with [`LayoutSystem::set_selection_size`].
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SizeTarget {
+    /// Share of the container the selection is in, between 0 and 1
+    Ratio(f64),
+    /// Width in points, within the nearest side-by-side container
+    Width(f64),
+    /// Height in points, within the nearest top-to-bottom container
+    Height(f64),
+}
+
+impl SizeTarget {
+    /// Axis the target applies to; a ratio applies to whichever container the
+    /// selection is in.
+    pub fn orientation(self) -> Option<Orientation> {
+        match self {
+            SizeTarget::Ratio(_) => None,
+            SizeTarget::Width(_) => Some(Orientation::Horizontal),
+            SizeTarget::Height(_) => Some(Orientation::Vertical),
+        }
+    }
+}
+
 #[enum_dispatch]
 pub trait LayoutSystem: Serialize + for<'de> Deserialize<'de> {
     fn create_layout(&mut self) -> LayoutId;
@@ -29,6 +54,8 @@ pub trait LayoutSystem: Serialize + for<'de> Deserialize<'de> {
     fn selected_window(&self, layout: LayoutId) -> Option<WindowId>;
     fn visible_windows_in_layout(&self, layout: LayoutId) -> Vec<WindowId>;
     fn visible_windows_under_selection(&self, layout: LayoutId) -> Vec<WindowId>;
+    /// All windows in the layout, including hidden stack members, in layout order.
+    fn windows_in_layout(&self, layout: LayoutId) -> Vec<WindowId>;
     fn ascend_selection(&mut self, layout: LayoutId) -> bool;
     fn descend_selection(&mut self, layout: LayoutId) -> bool;
     fn move_focus(
@@ -55,6 +82,9 @@ pub trait LayoutSystem: Serialize + for<'de> Deserialize<'de> {
     );
 
     fn swap_windows(&mut self, layout: LayoutId, a: WindowId, b: WindowId) -> bool;
+    /// Puts `new` in the place `old` occupies in the layout, keeping its size and
+    /// position. Fails if `old` is not in the layout or `new` already is.
+    fn replace_window(&mut self, layout: LayoutId, old: WindowId, new: WindowId) -> bool;
 
     fn move_selection(&mut self, layout: LayoutId, direction: Direction) -> bool;
     fn move_selection_to_layout_after_selection(
@@ -63,6 +93,12 @@ pub trait LayoutSystem: Serialize + for<'de> Deserialize<'de> {
         to_layout: LayoutId,
     );
     fn split_selection(&mut self, layout: LayoutId, kind: LayoutKind);
+    /// Makes the next window added to the layout split the selected window towards
+    /// `direction`, or clears the preselection with `None`.
+    fn preselect(&mut self, layout: LayoutId, direction: Option<Direction>) -> bool;
+    /// The selected window, the side of it the next window will go and the share
+    /// of its frame that window will take, if preselected.
+    fn preselection(&self, layout: LayoutId) -> Option<(WindowId, Direction, f64)>;
 
     fn toggle_fullscreen_of_selection(&mut self, layout: LayoutId) -> Vec<WindowId>;
     fn toggle_fullscreen_within_gaps_of_selection(&mut self, layout: LayoutId) -> Vec<WindowId>;
@@ -81,14 +117,55 @@ pub trait LayoutSystem: Serialize + for<'de> Deserialize<'de> {
     fn parent_of_selection_is_stacked(&self, layout: LayoutId) -> bool;
     fn unjoin_selection(&mut self, _layout: LayoutId);
     fn resize_selection_by(&mut self, layout: LayoutId, amount: f64);
+    /// Moves the edge of the selection facing `direction` by `amount` (a fraction
+    /// of the screen), outwards when positive. Fails if there is nothing on that
+    /// side to take space from.
+    fn resize_selection_edge(
+        &mut self,
+        layout: LayoutId,
+        direction: Direction,
+        amount: f64,
+    ) -> bool;
+    /// Resizes the selection to an absolute size. `screen` and `gaps` must be the
+    /// ones the layout is calculated with so that lengths map onto shares.
+    fn set_selection_size(
+        &mut self,
+        layout: LayoutId,
+        target: SizeTarget,
+        screen: CGRect,
+        gaps: &crate::common::config::GapSettings,
+    ) -> bool;
+    /// Evens out the sizes in the layout. The traditional system only fills in
+    /// sizes that were never set; the others discard sizes the user has set.
     fn rebalance(&mut self, layout: LayoutId);
+    /// Rotates the subtree containing the selection clockwise by `degrees`, which
+    /// must be a multiple of 90.
+    fn rotate_selection(&mut self, layout: LayoutId, degrees: i32);
+    /// Mirrors the subtree containing the selection along `axis`: horizontally
+    /// swaps left and right, vertically swaps top and bottom.
+    fn flip_selection(&mut self, layout: LayoutId, axis: Orientation);
     fn toggle_tile_orientation(&mut self, layout: LayoutId);
+    /// Describes the layout as a tree whose root is always a container.
+    fn layout_tree(&self, layout: LayoutId) -> LayoutTreeNode;
+    /// Rearranges the layout's windows to match `tree` as closely as the
+    /// system allows. Windows in `tree` that are not in the layout are
+    /// ignored, and windows missing from it are appended at the end.
+    fn apply_layout_tree(&mut self, layout: LayoutId, tree: &LayoutTreeNode);
 }
 
 mod traditional;
 pub use traditional::TraditionalLayoutSystem;
 mod bsp;
 pub use bsp::BspLayoutSystem;
+mod monocle;
+mod window_list;
+pub use monocle::MonocleLayoutSystem;
+mod grid;
+pub use grid::GridLayoutSystem;
+mod master_stack;
+pub use master_stack::MasterStackLayoutSystem;
+mod multi;
+pub use multi::MultiLayoutSystem;
 
 #[derive(Serialize, Deserialize)]
 #[serde(tag = "kind", rename_all = "snake_case")]
@@ -96,4 +173,21 @@ pub use bsp::BspLayoutSystem;
 pub enum LayoutSystemKind {
     Traditional(TraditionalLayoutSystem),
     Bsp(BspLayoutSystem),
+    Monocle(MonocleLayoutSystem),
+    Grid(GridLayoutSystem),
+    MasterStack(MasterStackLayoutSystem),
+}
+
+impl LayoutSystemKind {
+    pub fn new(mode: LayoutMode, settings: &LayoutSettings) -> Self {
+        match mode {
+            LayoutMode::Traditional => Self::Traditional(TraditionalLayoutSystem::default()),
+            LayoutMode::Bsp => Self::Bsp(BspLayoutSystem::new(&settings.bsp)),
+            LayoutMode::Monocle => Self::Monocle(MonocleLayoutSystem::default()),
+            LayoutMode::Grid => Self::Grid(GridLayoutSystem::default()),
+            LayoutMode::MasterStack => {
+                Self::MasterStack(MasterStackLayoutSystem::new(&settings.master_stack))
+            }
+        }
+    }
 }
@@ -1,11 +1,16 @@
+use std::cell::RefCell;
+
 use objc2_core_foundation::{CGPoint, CGRect, CGSize};
 use serde::{Deserialize, Serialize};
 
 use crate::actor::app::{WindowId, pid_t};
 use crate::common::collections::{HashMap, HashSet};
-use crate::layout_engine::systems::LayoutSystem;
+use crate::common::config::{BspInsertion, BspSettings};
+use crate::layout_engine::systems::{LayoutSystem, SizeTarget};
 use crate::layout_engine::utils::compute_tiling_area;
-use crate::layout_engine::{Direction, LayoutId, LayoutKind, Orientation};
+use crate::layout_engine::{
+    ContainerKind, Direction, LayoutId, LayoutKind, LayoutTreeNode, Orientation,
+};
 use crate::model::selection::*;
 use crate::model::tree::{NodeId, NodeMap, Tree};
 
@@ -34,6 +39,12 @@ pub struct BspLayoutSystem {
     tree: Tree<Components>,
     kind: slotmap::SecondaryMap<NodeId, NodeKind>,
     window_to_node: HashMap<WindowId, NodeId>,
+    #[serde(skip)]
+    settings: BspSettings,
+    /// Screen size each layout was last calculated for, used to find a leaf's
+    /// longest side.
+    #[serde(skip)]
+    screen_sizes: RefCell<slotmap::SecondaryMap<LayoutId, CGSize>>,
 }
 
 impl BspLayoutSystem {
@@ -160,12 +171,12 @@ impl BspLayoutSystem {
             }
             self.window_to_node.insert(new_window, new_node);
 
-            self.kind.insert(leaf, NodeKind::Split { orientation, ratio: 0.5 });
-
-            let (first_child, second_child) = match direction {
-                Direction::Left | Direction::Up => (new_node, existing_node),
-                Direction::Right | Direction::Down => (existing_node, new_node),
+            let split_ratio = self.settings.split_ratio as f32;
+            let (first_child, second_child, ratio) = match direction {
+                Direction::Left | Direction::Up => (new_node, existing_node, 1.0 - split_ratio),
+                Direction::Right | Direction::Down => (existing_node, new_node, split_ratio),
             };
+            self.kind.insert(leaf, NodeKind::Split { orientation, ratio });
 
             first_child.detach(&mut self.tree).push_back(leaf);
             second_child.detach(&mut self.tree).push_back(leaf);
@@ -182,10 +193,23 @@ impl Default for BspLayoutSystem {
             tree: Tree::with_observer(Components::default()),
             kind: Default::default(),
             window_to_node: Default::default(),
+            settings: Default::default(),
+            screen_sizes: Default::default(),
         }
     }
 }
 
+impl BspLayoutSystem {
+    pub fn new(settings: &BspSettings) -> Self {
+        Self {
+            settings: settings.clone(),
+            ..Default::default()
+        }
+    }
+
+    pub fn set_settings(&mut self, settings: &BspSettings) { self.settings = settings.clone(); }
+}
+
 impl BspLayoutSystem {
     fn make_leaf(&mut self, window: Option<WindowId>) -> NodeId {
         let id = self.tree.mk_node().into_id();
@@ -201,6 +225,124 @@ impl BspLayoutSystem {
         id
     }
 
+    /// Sets each split's ratio from the number of windows on either side of it.
+    /// Returns the number of leaves under `node`.
+    fn balance_node(&mut self, node: NodeId) -> usize {
+        let children: Vec<_> = node.children(&self.tree.map).collect();
+        let counts: Vec<_> = children.into_iter().map(|child| self.balance_node(child)).collect();
+        match (self.kind.get_mut(node), counts.as_slice()) {
+            (Some(NodeKind::Split { ratio, .. }), &[first, second]) => {
+                *ratio = first as f32 / (first + second) as f32;
+                first + second
+            }
+            (Some(NodeKind::Split { .. }), _) => counts.iter().sum(),
+            _ => 1,
+        }
+    }
+
+    /// Applies `f` to every split under the nearest split containing the selection.
+    /// `f` returns the split's new orientation and whether its children swap places.
+    fn transform_selected_subtree(
+        &mut self,
+        layout: LayoutId,
+        f: impl Fn(Orientation) -> (Orientation, bool),
+    ) {
+        let Some(selection) = self.selection_of_layout(layout) else {
+            return;
+        };
+        let Some(top) = selection
+            .ancestors(&self.tree.map)
+            .find(|&node| matches!(self.kind.get(node), Some(NodeKind::Split { .. })))
+        else {
+            return;
+        };
+        let mut stack = vec![top];
+        while let Some(node) = stack.pop() {
+            let swap = match self.kind.get_mut(node) {
+                Some(NodeKind::Split { orientation, ratio }) => {
+                    let (new_orientation, swap) = f(*orientation);
+                    *orientation = new_orientation;
+                    if swap {
+                        *ratio = 1.0 - *ratio;
+                    }
+                    swap
+                }
+                _ => continue,
+            };
+            if swap && let Some(first) = node.first_child(&self.tree.map) {
+                first.detach(&mut self.tree).push_back(node);
+            }
+            stack.extend(node.children(&self.tree.map));
+        }
+        // Moving children around shifts the selection to their siblings.
+        self.tree.data.selection.select(&self.tree.map, selection);
+    }
+
+    fn layout_tree_node(&self, node: NodeId) -> LayoutTreeNode {
+        match self.kind.get(node) {
+            Some(NodeKind::Split { orientation, ratio }) => LayoutTreeNode::Container {
+                kind: ContainerKind::Split,
+                orientation: *orientation,
+                proportions: vec![f64::from(*ratio), f64::from(1.0 - *ratio)],
+                children: node
+                    .children(&self.tree.map)
+                    .map(|child| self.layout_tree_node(child))
+                    .collect(),
+            },
+            Some(NodeKind::Leaf { window: Some(window_id), .. }) => {
+                LayoutTreeNode::Window { window_id: *window_id }
+            }
+            _ => LayoutTreeNode::split(Orientation::Horizontal, Vec::new()),
+        }
+    }
+
+    /// Builds a detached subtree for `tree` out of the windows in `pending`,
+    /// removing them as they are placed. Containers with more than two
+    /// children become a chain of splits, and stacks become splits.
+    fn build_layout_tree(
+        &mut self,
+        tree: &LayoutTreeNode,
+        pending: &mut Vec<WindowId>,
+    ) -> Option<NodeId> {
+        match tree {
+            LayoutTreeNode::Window { window_id } => {
+                let index = pending.iter().position(|wid| wid == window_id)?;
+                pending.remove(index);
+                Some(self.make_leaf(Some(*window_id)))
+            }
+            LayoutTreeNode::Container { orientation, children, .. } => {
+                let parts: Vec<_> = children
+                    .iter()
+                    .zip(tree.shares())
+                    .filter_map(|(child, share)| {
+                        Some((self.build_layout_tree(child, pending)?, share))
+                    })
+                    .collect();
+                self.chain_splits(*orientation, &parts)
+            }
+        }
+    }
+
+    fn chain_splits(
+        &mut self,
+        orientation: Orientation,
+        parts: &[(NodeId, f64)],
+    ) -> Option<NodeId> {
+        let (&(first, first_share), rest) = parts.split_first()?;
+        let Some(second) = self.chain_splits(orientation, rest) else {
+            return Some(first);
+        };
+        let rest_share: f64 = rest.iter().map(|(_, share)| share).sum();
+        let node = self.tree.mk_node().into_id();
+        self.kind.insert(node, NodeKind::Split {
+            orientation,
+            ratio: (first_share / (first_share + rest_share)) as f32,
+        });
+        first.detach(&mut self.tree).push_back(node);
+        second.detach(&mut self.tree).push_back(node);
+        Some(node)
+    }
+
     fn descend_to_leaf(&self, mut node: NodeId) -> NodeId {
         loop {
             match self.kind.get(node) {
@@ -319,43 +461,95 @@ impl BspLayoutSystem {
         let Some(state) = self.layouts.get(layout).copied() else {
             return;
         };
-        let sel = self.tree.data.selection.current_selection(state.root);
-        match self.kind.get_mut(sel) {
+        let leaf = match self.settings.insertion {
+            BspInsertion::Rightmost => self.last_leaf(state.root),
+            _ => self.descend_to_leaf(self.tree.data.selection.current_selection(state.root)),
+        };
+        match self.kind.get_mut(leaf) {
             Some(NodeKind::Leaf {
                 window,
                 fullscreen,
                 fullscreen_within_gaps,
                 ..
-            }) => {
-                if window.is_none() {
-                    *window = Some(wid);
-                    *fullscreen = false;
-                    *fullscreen_within_gaps = false;
-                    self.window_to_node.insert(wid, sel);
+            }) if window.is_none() => {
+                *window = Some(wid);
+                *fullscreen = false;
+                *fullscreen_within_gaps = false;
+                self.window_to_node.insert(wid, leaf);
+                self.tree.data.selection.select(&self.tree.map, leaf);
+            }
+            Some(NodeKind::Leaf { .. }) => {
+                let direction = self.insertion_direction(layout, leaf);
+                self.split_leaf_in_direction(leaf, direction, wid);
+            }
+            _ => {}
+        }
+    }
+
+    /// The side of `leaf` a new window goes on under the configured strategy.
+    fn insertion_direction(&self, layout: LayoutId, leaf: NodeId) -> Direction {
+        let parent_orientation =
+            leaf.parent(&self.tree.map).and_then(|parent| match self.kind.get(parent) {
+                Some(NodeKind::Split { orientation, .. }) => Some(*orientation),
+                _ => None,
+            });
+        match self.settings.insertion {
+            BspInsertion::SideBySide => Direction::Right,
+            BspInsertion::LongestSide => {
+                let size = self.leaf_size(layout, leaf);
+                if size.height > size.width {
+                    Direction::Down
                 } else {
-                    let existing = *window;
-                    let left = self.make_leaf(existing);
-                    let right = self.make_leaf(Some(wid));
-                    self.window_to_node.insert(wid, right);
-                    if let Some(w) = existing {
-                        self.window_to_node.insert(w, left);
-                    }
-                    self.kind.insert(sel, NodeKind::Split {
-                        orientation: Orientation::Horizontal,
-                        ratio: 0.5,
-                    });
-                    left.detach(&mut self.tree).push_back(sel);
-                    right.detach(&mut self.tree).push_back(sel);
-                    self.tree.data.selection.select(&self.tree.map, right);
+                    Direction::Right
                 }
             }
-            Some(NodeKind::Split { .. }) => {
-                let leaf = self.descend_to_leaf(sel);
-                self.tree.data.selection.select(&self.tree.map, leaf);
-                self.insert_window_at_selection(layout, wid);
+            BspInsertion::Spiral => {
+                const TURNS: [Direction; 4] = [
+                    Direction::Right,
+                    Direction::Down,
+                    Direction::Left,
+                    Direction::Up,
+                ];
+                TURNS[(leaf.ancestors(&self.tree.map).count() - 1) % TURNS.len()]
+            }
+            BspInsertion::Rightmost | BspInsertion::Alternate => match parent_orientation {
+                Some(Orientation::Horizontal) => Direction::Down,
+                _ => Direction::Right,
+            },
+        }
+    }
+
+    /// Size of `leaf` on the screen the layout was last calculated for, ignoring gaps.
+    fn leaf_size(&self, layout: LayoutId, leaf: NodeId) -> CGSize {
+        // Until the layout has been calculated, a square makes the first split
+        // side by side.
+        let screen =
+            self.screen_sizes.borrow().get(layout).copied().unwrap_or(CGSize::new(1.0, 1.0));
+        let mut size = screen;
+        for node in leaf.ancestors(&self.tree.map) {
+            let Some(parent) = node.parent(&self.tree.map) else {
+                break;
+            };
+            if let Some(NodeKind::Split { orientation, ratio }) = self.kind.get(parent) {
+                let share = if parent.first_child(&self.tree.map) == Some(node) {
+                    f64::from(*ratio)
+                } else {
+                    1.0 - f64::from(*ratio)
+                };
+                match orientation {
+                    Orientation::Horizontal => size.width *= share,
+                    Orientation::Vertical => size.height *= share,
+                }
             }
-            None => {}
         }
+        size
+    }
+
+    fn last_leaf(&self, mut node: NodeId) -> NodeId {
+        while let Some(child) = node.last_child(&self.tree.map) {
+            node = child;
+        }
+        node
     }
 
     fn remove_window_internal(&mut self, layout: crate::layout_engine::LayoutId, wid: WindowId) {
@@ -455,6 +649,38 @@ impl BspLayoutSystem {
         }
     }
 
+    /// Length along `orientation` that the two children of `split` share,
+    /// mirroring how `calculate_layout_recursive` divides the tiling area.
+    fn available_length(
+        &self,
+        split: NodeId,
+        orientation: Orientation,
+        tiling_area: CGRect,
+        gaps: &crate::common::config::GapSettings,
+    ) -> f64 {
+        let (mut length, gap) = match orientation {
+            Orientation::Horizontal => (tiling_area.size.width, gaps.inner.horizontal),
+            Orientation::Vertical => (tiling_area.size.height, gaps.inner.vertical),
+        };
+        let path: Vec<_> = split.ancestors(&self.tree.map).collect();
+        for &node in path.iter().rev() {
+            let Some(parent) = node.parent(&self.tree.map) else {
+                continue;
+            };
+            if let Some(NodeKind::Split { orientation: o, ratio }) = self.kind.get(parent)
+                && *o == orientation
+            {
+                let share = if parent.first_child(&self.tree.map) == Some(node) {
+                    f64::from(*ratio)
+                } else {
+                    1.0 - f64::from(*ratio)
+                };
+                length = (length - gap).max(0.0) * share;
+            }
+        }
+        length - gap
+    }
+
     fn apply_outer_gaps(screen: CGRect, gaps: &crate::common::config::GapSettings) -> CGRect {
         compute_tiling_area(screen, gaps)
     }
@@ -527,6 +753,287 @@ mod tests {
         assert_eq!(system.window_in_direction(layout, Direction::Down), Some(w(1)));
         assert_eq!(system.window_in_direction(layout, Direction::Up), Some(w(2)));
     }
+
+    fn screen() -> CGRect { CGRect::new(CGPoint::new(0.0, 0.0), CGSize::new(1000.0, 800.0)) }
+
+    fn frame_of(system: &BspLayoutSystem, layout: LayoutId, wid: WindowId) -> CGRect {
+        system
+            .calculate_layout(
+                layout,
+                screen(),
+                0.0,
+                &Default::default(),
+                0.0,
+                Default::default(),
+                Default::default(),
+            )
+            .into_iter()
+            .find(|(w, _)| *w == wid)
+            .unwrap()
+            .1
+    }
+
+    #[test]
+    fn set_selection_size_sets_the_split_ratio() {
+        let mut system = BspLayoutSystem::default();
+        let layout = system.create_layout();
+        system.add_window_after_selection(layout, w(1));
+        system.add_window_after_selection(layout, w(2));
+        system.select_window(layout, w(2));
+        let gaps = crate::common::config::GapSettings::default();
+
+        assert!(system.set_selection_size(layout, SizeTarget::Ratio(0.7), screen(), &gaps));
+        assert_eq!(frame_of(&system, layout, w(2)).size.width.round(), 700.0);
+
+        assert!(system.set_selection_size(layout, SizeTarget::Width(250.0), screen(), &gaps));
+        assert_eq!(frame_of(&system, layout, w(2)).size.width.round(), 250.0);
+        assert_eq!(frame_of(&system, layout, w(1)).size.width.round(), 750.0);
+
+        // There is no top-to-bottom split to take a height from.
+        assert!(!system.set_selection_size(layout, SizeTarget::Height(300.0), screen(), &gaps));
+    }
+
+    #[test]
+    fn resize_selection_edge_moves_the_divider() {
+        let mut system = BspLayoutSystem::default();
+        let layout = system.create_layout();
+        system.add_window_after_selection(layout, w(1));
+        system.add_window_after_selection(layout, w(2));
+        system.select_window(layout, w(1));
+
+        assert!(system.resize_selection_edge(layout, Direction::Right, 0.1));
+        assert_eq!(frame_of(&system, layout, w(1)).size.width.round(), 600.0);
+        assert_eq!(frame_of(&system, layout, w(2)).origin.x.round(), 600.0);
+        assert_eq!(frame_of(&system, layout, w(2)).size.width.round(), 400.0);
+
+        assert!(!system.resize_selection_edge(layout, Direction::Left, 0.1));
+        assert!(!system.resize_selection_edge(layout, Direction::Down, 0.1));
+
+        system.select_window(layout, w(2));
+        assert!(system.resize_selection_edge(layout, Direction::Left, 0.2));
+        assert_eq!(frame_of(&system, layout, w(1)).size.width.round(), 400.0);
+        assert_eq!(frame_of(&system, layout, w(2)).size.width.round(), 600.0);
+    }
+
+    #[test]
+    fn preselection_decides_where_the_next_window_goes() {
+        let mut system = BspLayoutSystem::default();
+        let layout = system.create_layout();
+        system.add_window_after_selection(layout, w(1));
+        system.add_window_after_selection(layout, w(2));
+        system.select_window(layout, w(1));
+
+        assert!(system.preselect(layout, Some(Direction::Down)));
+        assert_eq!(system.preselection(layout), Some((w(1), Direction::Down, 0.5)));
+        system.add_window_after_selection(layout, w(3));
+        assert_eq!(system.preselection(layout), None);
+
+        let w1 = frame_of(&system, layout, w(1));
+        let w3 = frame_of(&system, layout, w(3));
+        assert_eq!((w1.origin.y, w1.size.height, w1.size.width), (0.0, 400.0, 500.0));
+        assert_eq!((w3.origin.x, w3.origin.y, w3.size.width), (0.0, 400.0, 500.0));
+        assert_eq!(frame_of(&system, layout, w(2)).size.height, 800.0);
+
+        system.select_window(layout, w(2));
+        assert!(system.preselect(layout, Some(Direction::Left)));
+        assert!(system.preselect(layout, None));
+        assert_eq!(system.preselection(layout), None);
+    }
+
+    fn rect_of(system: &BspLayoutSystem, layout: LayoutId, wid: WindowId) -> (f64, f64, f64, f64) {
+        let frame = frame_of(system, layout, wid);
+        (
+            frame.origin.x.round(),
+            frame.origin.y.round(),
+            frame.size.width.round(),
+            frame.size.height.round(),
+        )
+    }
+
+    #[test]
+    fn rebalance_flip_and_rotate() {
+        let mut system = BspLayoutSystem::default();
+        let layout = system.create_layout();
+        for i in 1..=3 {
+            system.add_window_after_selection(layout, w(i));
+        }
+        let rect = |system: &BspLayoutSystem, wid: WindowId| rect_of(system, layout, wid);
+        assert_eq!(rect(&system, w(1)).2, 500.0);
+
+        system.rebalance(layout);
+        assert_eq!(rect(&system, w(1)), (0.0, 0.0, 333.0, 800.0));
+        assert_eq!(rect(&system, w(2)), (333.0, 0.0, 333.0, 800.0));
+        assert_eq!(rect(&system, w(3)), (667.0, 0.0, 333.0, 800.0));
+
+        // Only the split holding the selection (w3) is affected.
+        system.flip_selection(layout, Orientation::Vertical);
+        assert_eq!(rect(&system, w(3)).0, 667.0);
+        system.flip_selection(layout, Orientation::Horizontal);
+        assert_eq!(rect(&system, w(3)).0, 333.0);
+        assert_eq!(rect(&system, w(2)).0, 667.0);
+        assert_eq!(rect(&system, w(1)).0, 0.0);
+
+        system.rotate_selection(layout, 90);
+        assert_eq!(rect(&system, w(3)), (333.0, 0.0, 667.0, 400.0));
+        assert_eq!(rect(&system, w(2)), (333.0, 400.0, 667.0, 400.0));
+        assert_eq!(system.selected_window(layout), Some(w(3)));
+
+        system.rotate_selection(layout, 180);
+        assert_eq!(rect(&system, w(2)).1, 0.0);
+        assert_eq!(rect(&system, w(3)).1, 400.0);
+
+        system.rotate_selection(layout, 45);
+        assert_eq!(rect(&system, w(3)).1, 400.0);
+        assert_eq!(system.selected_window(layout), Some(w(3)));
+    }
+
+    fn system_with(insertion: BspInsertion) -> (BspLayoutSystem, LayoutId) {
+        let mut system = BspLayoutSystem::new(&BspSettings {
+            insertion,
+            ..Default::default()
+        });
+        let layout = system.create_layout();
+        (system, layout)
+    }
+
+    /// Adds a window and lays the tree out, as the engine does.
+    fn insert(system: &mut BspLayoutSystem, layout: LayoutId, wid: WindowId) {
+        system.add_window_after_selection(layout, wid);
+        frame_of(system, layout, wid);
+    }
+
+    #[test]
+    fn side_by_side_insertion_splits_to_the_right() {
+        let (mut system, layout) = system_with(BspInsertion::SideBySide);
+        for i in 1..=3 {
+            insert(&mut system, layout, w(i));
+        }
+        assert_eq!(rect_of(&system, layout, w(1)), (0.0, 0.0, 500.0, 800.0));
+        assert_eq!(rect_of(&system, layout, w(2)), (500.0, 0.0, 250.0, 800.0));
+        assert_eq!(rect_of(&system, layout, w(3)), (750.0, 0.0, 250.0, 800.0));
+    }
+
+    #[test]
+    fn longest_side_insertion_splits_along_the_longer_side() {
+        let (mut system, layout) = system_with(BspInsertion::LongestSide);
+        for i in 1..=4 {
+            insert(&mut system, layout, w(i));
+        }
+        assert_eq!(rect_of(&system, layout, w(1)), (0.0, 0.0, 500.0, 800.0));
+        assert_eq!(rect_of(&system, layout, w(2)), (500.0, 0.0, 500.0, 400.0));
+        assert_eq!(rect_of(&system, layout, w(3)), (500.0, 400.0, 250.0, 400.0));
+        assert_eq!(rect_of(&system, layout, w(4)), (750.0, 400.0, 250.0, 400.0));
+    }
+
+    #[test]
+    fn spiral_insertion_turns_clockwise() {
+        let (mut system, layout) = system_with(BspInsertion::Spiral);
+        for i in 1..=4 {
+            insert(&mut system, layout, w(i));
+        }
+        assert_eq!(rect_of(&system, layout, w(1)), (0.0, 0.0, 500.0, 800.0));
+        assert_eq!(rect_of(&system, layout, w(2)), (500.0, 0.0, 500.0, 400.0));
+        assert_eq!(rect_of(&system, layout, w(4)), (500.0, 400.0, 250.0, 400.0));
+        assert_eq!(rect_of(&system, layout, w(3)), (750.0, 400.0, 250.0, 400.0));
+    }
+
+    #[test]
+    fn rightmost_insertion_ignores_the_selection() {
+        let (mut system, layout) = system_with(BspInsertion::Rightmost);
+        insert(&mut system, layout, w(1));
+        insert(&mut system, layout, w(2));
+        system.select_window(layout, w(1));
+        insert(&mut system, layout, w(3));
+        system.select_window(layout, w(1));
+        insert(&mut system, layout, w(4));
+
+        assert_eq!(rect_of(&system, layout, w(1)), (0.0, 0.0, 500.0, 800.0));
+        assert_eq!(rect_of(&system, layout, w(2)), (500.0, 0.0, 500.0, 400.0));
+        assert_eq!(rect_of(&system, layout, w(3)), (500.0, 400.0, 250.0, 400.0));
+        assert_eq!(rect_of(&system, layout, w(4)), (750.0, 400.0, 250.0, 400.0));
+    }
+
+    #[test]
+    fn alternate_insertion_splits_across_the_parent() {
+        let (mut system, layout) = system_with(BspInsertion::Alternate);
+        insert(&mut system, layout, w(1));
+        insert(&mut system, layout, w(2));
+        system.select_window(layout, w(1));
+        insert(&mut system, layout, w(3));
+        insert(&mut system, layout, w(4));
+
+        assert_eq!(rect_of(&system, layout, w(1)), (0.0, 0.0, 500.0, 400.0));
+        assert_eq!(rect_of(&system, layout, w(3)), (0.0, 400.0, 250.0, 400.0));
+        assert_eq!(rect_of(&system, layout, w(4)), (250.0, 400.0, 250.0, 400.0));
+        assert_eq!(rect_of(&system, layout, w(2)), (500.0, 0.0, 500.0, 800.0));
+    }
+
+    #[test]
+    fn split_ratio_is_kept_by_the_split_window() {
+        let mut system = BspLayoutSystem::new(&BspSettings {
+            split_ratio: 0.7,
+            ..Default::default()
+        });
+        let layout = system.create_layout();
+        insert(&mut system, layout, w(1));
+        insert(&mut system, layout, w(2));
+        assert_eq!(rect_of(&system, layout, w(1)).2, 700.0);
+
+        assert!(system.preselect(layout, Some(Direction::Left)));
+        insert(&mut system, layout, w(3));
+        assert_eq!(rect_of(&system, layout, w(3)), (700.0, 0.0, 90.0, 800.0));
+        assert_eq!(rect_of(&system, layout, w(2)), (790.0, 0.0, 210.0, 800.0));
+    }
+
+    #[test]
+    fn apply_layout_tree_rebuilds_the_splits() {
+        let (mut system, layout) = system_with(BspInsertion::LongestSide);
+        for i in 1..=4 {
+            insert(&mut system, layout, w(i));
+        }
+        system.select_window(layout, w(2));
+
+        let window = |i| LayoutTreeNode::Window { window_id: w(i) };
+        system.apply_layout_tree(
+            layout,
+            &LayoutTreeNode::split(Orientation::Vertical, vec![
+                window(3),
+                LayoutTreeNode::Container {
+                    kind: ContainerKind::Stack,
+                    orientation: Orientation::Horizontal,
+                    proportions: vec![2.0, 1.0, 1.0],
+                    children: vec![window(1), window(9), window(2)],
+                },
+            ]),
+        );
+
+        // w4 was left out of the tree, so it is inserted next to the first window.
+        assert_eq!(rect_of(&system, layout, w(3)), (0.0, 0.0, 500.0, 400.0));
+        assert_eq!(rect_of(&system, layout, w(4)), (500.0, 0.0, 500.0, 400.0));
+        assert_eq!(rect_of(&system, layout, w(1)), (0.0, 400.0, 667.0, 400.0));
+        assert_eq!(rect_of(&system, layout, w(2)), (667.0, 400.0, 333.0, 400.0));
+        assert_eq!(system.selected_window(layout), Some(w(2)));
+
+        let exported = system.layout_tree(layout);
+        assert_eq!(exported.windows(), vec![w(3), w(4), w(1), w(2)]);
+        system.apply_layout_tree(layout, &exported);
+        assert_eq!(rect_of(&system, layout, w(1)), (0.0, 400.0, 667.0, 400.0));
+    }
+
+    #[test]
+    fn replace_window_keeps_the_leaf() {
+        let mut system = BspLayoutSystem::default();
+        let layout = system.create_layout();
+        system.add_window_after_selection(layout, w(1));
+        system.add_window_after_selection(layout, w(2));
+        assert_eq!(system.window_in_direction(layout, Direction::Right), Some(w(1)));
+
+        assert!(system.replace_window(layout, w(1), w(3)));
+        assert_eq!(system.windows_in_layout(layout), vec![w(3), w(2)]);
+        assert!(!system.contains_window(layout, w(1)));
+        assert_eq!(system.window_in_direction(layout, Direction::Right), Some(w(3)));
+        assert!(!system.replace_window(layout, w(2), w(3)));
+    }
 }
 
 impl LayoutSystem for BspLayoutSystem {
@@ -550,6 +1057,7 @@ impl LayoutSystem for BspLayoutSystem {
     }
 
     fn remove_layout(&mut self, layout: LayoutId) {
+        self.screen_sizes.get_mut().remove(layout);
         if let Some(state) = self.layouts.remove(layout) {
             let mut windows = Vec::new();
             self.collect_windows_under(state.root, &mut windows);
@@ -607,6 +1115,7 @@ impl LayoutSystem for BspLayoutSystem {
     ) -> Vec<(WindowId, CGRect)> {
         let mut out = Vec::new();
         if let Some(state) = self.layouts.get(layout).copied() {
+            self.screen_sizes.borrow_mut().insert(layout, screen.size);
             let rect = Self::apply_outer_gaps(screen, gaps);
             self.calculate_layout_recursive(state.root, rect, screen, gaps, &mut out);
         }
@@ -637,6 +1146,10 @@ impl LayoutSystem for BspLayoutSystem {
         out
     }
 
+    fn windows_in_layout(&self, layout: LayoutId) -> Vec<WindowId> {
+        self.visible_windows_in_layout(layout)
+    }
+
     fn ascend_selection(&mut self, layout: LayoutId) -> bool {
         if let Some(sel) = self.selection_of_layout(layout) {
             if self.kind.get(sel).is_none() {
@@ -921,6 +1434,28 @@ impl LayoutSystem for BspLayoutSystem {
         true
     }
 
+    fn replace_window(&mut self, layout: LayoutId, old: WindowId, new: WindowId) -> bool {
+        if self.window_to_node.contains_key(&new) {
+            return false;
+        }
+        let Some(&node) = self.window_to_node.get(&old) else {
+            return false;
+        };
+        let Some(state) = self.layouts.get(layout).copied() else {
+            return false;
+        };
+        if !self.belongs_to_layout(state, node) {
+            return false;
+        }
+        let Some(NodeKind::Leaf { window, .. }) = self.kind.get_mut(node) else {
+            return false;
+        };
+        *window = Some(new);
+        self.window_to_node.remove(&old);
+        self.window_to_node.insert(new, node);
+        true
+    }
+
     fn move_selection_to_layout_after_selection(
         &mut self,
         from_layout: LayoutId,
@@ -963,6 +1498,34 @@ impl LayoutSystem for BspLayoutSystem {
         }
     }
 
+    fn preselect(&mut self, layout: LayoutId, direction: Option<Direction>) -> bool {
+        let Some(sel) = self.selection_of_layout(layout) else {
+            return false;
+        };
+        let leaf = self.descend_to_leaf(sel);
+        match self.kind.get_mut(leaf) {
+            Some(NodeKind::Leaf {
+                window: Some(_), preselected, ..
+            }) => {
+                *preselected = direction;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn preselection(&self, layout: LayoutId) -> Option<(WindowId, Direction, f64)> {
+        let leaf = self.descend_to_leaf(self.selection_of_layout(layout)?);
+        match self.kind.get(leaf) {
+            Some(NodeKind::Leaf {
+                window: Some(window),
+                preselected: Some(direction),
+                ..
+            }) => Some((*window, *direction, 1.0 - self.settings.split_ratio)),
+            _ => None,
+        }
+    }
+
     fn toggle_fullscreen_of_selection(&mut self, layout: LayoutId) -> Vec<WindowId> {
         if let Some(sel) = self.selection_of_layout(layout) {
             let sel_leaf = self.descend_to_leaf(sel);
@@ -1123,7 +1686,129 @@ impl LayoutSystem for BspLayoutSystem {
         }
     }
 
-    fn rebalance(&mut self, _layout: LayoutId) {}
+    fn resize_selection_edge(
+        &mut self,
+        layout: LayoutId,
+        direction: Direction,
+        amount: f64,
+    ) -> bool {
+        let Some(mut node) = self.selection_of_layout(layout) else {
+            return false;
+        };
+        // Find the split whose divider is the edge being moved: one along the
+        // same axis with the selection on the near side of it.
+        let parent = loop {
+            let Some(parent) = node.parent(&self.tree.map) else {
+                return false;
+            };
+            let is_first = parent.first_child(&self.tree.map) == Some(node);
+            if let Some(NodeKind::Split { orientation, .. }) = self.kind.get(parent)
+                && *orientation == direction.orientation()
+                && is_first == matches!(direction, Direction::Right | Direction::Down)
+            {
+                break parent;
+            }
+            node = parent;
+        };
+
+        // `amount` is relative to the screen; scale it to the split's own extent.
+        let extent = parent.ancestors(&self.tree.map).fold(1.0, |extent, node| {
+            let Some(grandparent) = node.parent(&self.tree.map) else {
+                return extent;
+            };
+            match self.kind.get(grandparent) {
+                Some(NodeKind::Split { orientation, ratio })
+                    if *orientation == direction.orientation() =>
+                {
+                    if grandparent.first_child(&self.tree.map) == Some(node) {
+                        extent * f64::from(*ratio)
+                    } else {
+                        extent * (1.0 - f64::from(*ratio))
+                    }
+                }
+                _ => extent,
+            }
+        });
+        let delta = (amount / extent.max(0.01)) as f32;
+        let Some(NodeKind::Split { ratio, .. }) = self.kind.get_mut(parent) else {
+            return false;
+        };
+        *ratio = match direction {
+            Direction::Right | Direction::Down => *ratio + delta,
+            Direction::Left | Direction::Up => *ratio - delta,
+        }
+        .clamp(0.05, 0.95);
+        true
+    }
+
+    fn set_selection_size(
+        &mut self,
+        layout: LayoutId,
+        target: SizeTarget,
+        screen: CGRect,
+        gaps: &crate::common::config::GapSettings,
+    ) -> bool {
+        let Some(mut node) = self.selection_of_layout(layout) else {
+            return false;
+        };
+        let wanted = target.orientation();
+        let (parent, orientation) = loop {
+            let Some(parent) = node.parent(&self.tree.map) else {
+                return false;
+            };
+            if let Some(NodeKind::Split { orientation, .. }) = self.kind.get(parent)
+                && wanted.is_none_or(|wanted| wanted == *orientation)
+            {
+                break (parent, *orientation);
+            }
+            node = parent;
+        };
+
+        let share = match target {
+            SizeTarget::Ratio(ratio) => ratio,
+            SizeTarget::Width(length) | SizeTarget::Height(length) => {
+                let tiling_area = Self::apply_outer_gaps(screen, gaps);
+                let available = self.available_length(parent, orientation, tiling_area, gaps);
+                if available <= 0.0 {
+                    return false;
+                }
+                length / available
+            }
+        };
+        let is_first = parent.first_child(&self.tree.map) == Some(node);
+        let Some(NodeKind::Split { ratio, .. }) = self.kind.get_mut(parent) else {
+            return false;
+        };
+        let share = (share as f32).clamp(0.05, 0.95);
+        *ratio = if is_first { share } else { 1.0 - share };
+        true
+    }
+
+    fn rebalance(&mut self, layout: LayoutId) {
+        if let Some(state) = self.layouts.get(layout).copied() {
+            self.balance_node(state.root);
+        }
+    }
+
+    fn rotate_selection(&mut self, layout: LayoutId, degrees: i32) {
+        let degrees = degrees.rem_euclid(360);
+        if degrees == 0 || degrees % 90 != 0 {
+            return;
+        }
+        self.transform_selected_subtree(layout, |orientation| match (degrees, orientation) {
+            (180, _) => (orientation, true),
+            // A quarter turn clockwise takes the left side to the top and the top
+            // to the right, so only top-to-bottom splits change their order.
+            (90, Orientation::Horizontal) => (Orientation::Vertical, false),
+            (90, Orientation::Vertical) => (Orientation::Horizontal, true),
+            (_, Orientation::Horizontal) => (Orientation::Vertical, true),
+            (_, Orientation::Vertical) => (Orientation::Horizontal, false),
+        });
+    }
+
+    fn flip_selection(&mut self, layout: LayoutId, axis: Orientation) {
+        self.transform_selected_subtree(layout, |orientation| (orientation, orientation == axis));
+    }
 
     fn toggle_tile_orientation(&mut self, layout: LayoutId) {
         let sel_snapshot = self.selection_of_layout(layout);
@@ -1159,4 +1844,43 @@ impl LayoutSystem for BspLayoutSystem {
             }
         }
     }
+
+    fn layout_tree(&self, layout: LayoutId) -> LayoutTreeNode {
+        let Some(state) = self.layouts.get(layout) else {
+            return LayoutTreeNode::split(Orientation::Horizontal, Vec::new());
+        };
+        match self.layout_tree_node(state.root) {
+            container @ LayoutTreeNode::Container { .. } => container,
+            window => LayoutTreeNode::split(Orientation::Horizontal, vec![window]),
+        }
+    }
+
+    fn apply_layout_tree(&mut self, layout: LayoutId, tree: &LayoutTreeNode) {
+        let Some(state) = self.layouts.get(layout).copied() else {
+            return;
+        };
+        let selected = self.selection_window(&state);
+        let mut pending = Vec::new();
+        self.collect_windows_under(state.root, &mut pending);
+
+        let ids: Vec<_> = state.root.traverse_preorder(&self.tree.map).collect();
+        for id in ids {
+            self.kind.remove(id);
+        }
+        state.root.remove_root(&mut self.tree);
+
+        let root = match self.build_layout_tree(tree, &mut pending) {
+            Some(root) => root,
+            None => self.make_leaf(None),
+        };
+        self.layouts[layout].root = root;
+        let first = self.descend_to_leaf(root);
+        self.tree.data.selection.select(&self.tree.map, first);
+        for wid in pending {
+            self.add_window_after_selection(layout, wid);
+        }
+        if let Some(wid) = selected {
+            self.select_window(layout, wid);
+        }
+    }
 }
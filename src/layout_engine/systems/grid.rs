@@ -0,0 +1,194 @@
+use objc2_core_foundation::{CGPoint, CGRect, CGSize};
+use serde::{Deserialize, Serialize};
+
+use crate::common::config::GapSettings;
+use crate::layout_engine::Direction;
+use crate::layout_engine::systems::window_list::{Arrangement, ListLayoutSystem, WindowList};
+
+/// Returns the (rows, columns) used to arrange `count` windows.
+///
+/// Columns are chosen first so the grid is as close to square as possible;
+/// the last row holds any remainder and is stretched to the full width.
+fn grid_dimensions(count: usize) -> (usize, usize) {
+    if count == 0 {
+        return (0, 0);
+    }
+    let cols = (count as f64).sqrt().ceil() as usize;
+    (count.div_ceil(cols), cols)
+}
+
+fn columns_in_row(count: usize, row: usize) -> usize {
+    let (rows, cols) = grid_dimensions(count);
+    if row + 1 == rows {
+        count - cols * (rows - 1)
+    } else {
+        cols
+    }
+}
+
+fn neighbor_index(count: usize, idx: usize, direction: Direction) -> Option<usize> {
+    let (rows, cols) = grid_dimensions(count);
+    if idx >= count {
+        return None;
+    }
+    let (row, col) = (idx / cols, idx % cols);
+    match direction {
+        Direction::Left => (col > 0).then(|| idx - 1),
+        Direction::Right => (col + 1 < columns_in_row(count, row)).then(|| idx + 1),
+        Direction::Up | Direction::Down => {
+            let target_row = match direction {
+                Direction::Up => row.checked_sub(1)?,
+                _ => (row + 1 < rows).then_some(row + 1)?,
+            };
+            // Rows can have different widths, so pick the cell in the target
+            // row whose span contains the centre of the current cell.
+            let center = (col as f64 + 0.5) / columns_in_row(count, row) as f64;
+            let target_cols = columns_in_row(count, target_row);
+            let target_col = ((center * target_cols as f64) as usize).min(target_cols - 1);
+            Some(target_row * cols + target_col)
+        }
+    }
+}
+
+fn cell_frames(area: CGRect, count: usize, gaps: &GapSettings) -> Vec<CGRect> {
+    let (rows, _) = grid_dimensions(count);
+    if rows == 0 {
+        return vec![];
+    }
+    let h_gap = gaps.inner.horizontal;
+    let v_gap = gaps.inner.vertical;
+    let row_height = ((area.size.height - v_gap * (rows - 1) as f64) / rows as f64).max(0.0);
+
+    let mut frames = Vec::with_capacity(count);
+    for row in 0..rows {
+        let n = columns_in_row(count, row);
+        let col_width = ((area.size.width - h_gap * (n - 1) as f64) / n as f64).max(0.0);
+        let y = area.origin.y + row as f64 * (row_height + v_gap);
+        for col in 0..n {
+            let x = area.origin.x + col as f64 * (col_width + h_gap);
+            frames.push(CGRect::new(
+                CGPoint::new(x, y),
+                CGSize::new(col_width, row_height),
+            ));
+        }
+    }
+    frames
+}
+
+/// Arranges the windows of a layout into a near-square grid of rows and
+/// columns, filled in reading order.
+pub type GridLayoutSystem = ListLayoutSystem<Grid>;
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct Grid;
+
+impl Arrangement for Grid {
+    type Params = ();
+
+    const NAME: &'static str = "grid";
+
+    fn new_params(&self) -> Self::Params {}
+
+    fn describe(&self, list: &WindowList<()>) -> String {
+        let (rows, cols) = grid_dimensions(list.windows.len());
+        format!("Grid {rows}x{cols}")
+    }
+
+    fn describe_window(&self, list: &WindowList<()>, idx: usize) -> String {
+        let (_, cols) = grid_dimensions(list.windows.len());
+        format!("[{}, {}] ", idx / cols, idx % cols)
+    }
+
+    fn frames(&self, list: &WindowList<()>, tiling: CGRect, gaps: &GapSettings) -> Vec<CGRect> {
+        cell_frames(tiling, list.windows.len(), gaps)
+    }
+
+    fn neighbor_index(
+        &self,
+        list: &WindowList<()>,
+        idx: usize,
+        direction: Direction,
+    ) -> Option<usize> {
+        neighbor_index(list.windows.len(), idx, direction)
+    }
+
+    fn entry_index(&self, list: &WindowList<()>, direction: Direction) -> Option<usize> {
+        let count = list.windows.len();
+        if count == 0 {
+            return None;
+        }
+        let (rows, cols) = grid_dimensions(count);
+        // Entering the grid from the opposite side picks the nearest edge cell.
+        Some(match direction {
+            Direction::Right | Direction::Down => 0,
+            Direction::Left => columns_in_row(count, 0) - 1,
+            Direction::Up => (rows - 1) * cols,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::config::{InnerGaps, OuterGaps};
+    use crate::layout_engine::systems::LayoutSystem;
+    use crate::layout_engine::systems::window_list::tests::{frames, rect, system_with, w};
+
+    #[test]
+    fn dimensions_stay_near_square() {
+        assert_eq!(grid_dimensions(0), (0, 0));
+        assert_eq!(grid_dimensions(1), (1, 1));
+        assert_eq!(grid_dimensions(2), (1, 2));
+        assert_eq!(grid_dimensions(3), (2, 2));
+        assert_eq!(grid_dimensions(5), (2, 3));
+        assert_eq!(grid_dimensions(9), (3, 3));
+        assert_eq!(grid_dimensions(10), (3, 4));
+    }
+
+    #[test]
+    fn layout_honours_inner_and_outer_gaps() {
+        let (system, layout): (GridLayoutSystem, _) = system_with(3);
+        let gaps = GapSettings {
+            outer: OuterGaps {
+                top: 10.0,
+                left: 10.0,
+                bottom: 10.0,
+                right: 10.0,
+            },
+            inner: InnerGaps {
+                horizontal: 20.0,
+                vertical: 20.0,
+            },
+            ..Default::default()
+        };
+
+        assert_eq!(frames(&system, layout, rect(0., 0., 1020., 820.), &gaps), vec![
+            (w(1), rect(10., 10., 490., 390.)),
+            (w(2), rect(520., 10., 490., 390.)),
+            (w(3), rect(10., 420., 1000., 390.)),
+        ]);
+    }
+
+    #[test]
+    fn directional_focus_moves_between_cells() {
+        let (mut system, layout): (GridLayoutSystem, _) = system_with(5);
+        system.select_window(layout, w(1));
+
+        assert_eq!(system.move_focus(layout, Direction::Right).0, Some(w(2)));
+        assert_eq!(system.move_focus(layout, Direction::Right).0, Some(w(3)));
+        assert_eq!(system.move_focus(layout, Direction::Right).0, None);
+        assert_eq!(system.move_focus(layout, Direction::Down).0, Some(w(5)));
+        assert_eq!(system.move_focus(layout, Direction::Left).0, Some(w(4)));
+        assert_eq!(system.move_focus(layout, Direction::Up).0, Some(w(1)));
+        assert_eq!(system.move_focus(layout, Direction::Up).0, None);
+    }
+
+    #[test]
+    fn window_in_direction_enters_from_nearest_edge() {
+        let (system, layout): (GridLayoutSystem, _) = system_with(5);
+
+        assert_eq!(system.window_in_direction(layout, Direction::Right), Some(w(1)));
+        assert_eq!(system.window_in_direction(layout, Direction::Left), Some(w(3)));
+        assert_eq!(system.window_in_direction(layout, Direction::Up), Some(w(4)));
+    }
+}
@@ -0,0 +1,417 @@
+use objc2_core_foundation::{CGPoint, CGRect, CGSize};
+use serde::{Deserialize, Serialize};
+
+use crate::actor::app::WindowId;
+use crate::common::config::{GapSettings, MasterStackSettings};
+use crate::layout_engine::systems::SizeTarget;
+use crate::layout_engine::systems::window_list::{Arrangement, ListLayoutSystem, WindowList};
+use crate::layout_engine::utils::compute_tiling_area;
+use crate::layout_engine::{ContainerKind, Direction, LayoutId, LayoutTreeNode, Orientation};
+
+const MIN_MASTER_RATIO: f64 = 0.05;
+const MAX_MASTER_RATIO: f64 = 0.95;
+
+/// Splits `area` into `count` rows of equal height separated by `gap`.
+fn column_frames(area: CGRect, count: usize, gap: f64) -> Vec<CGRect> {
+    if count == 0 {
+        return vec![];
+    }
+    let height = ((area.size.height - gap * (count - 1) as f64) / count as f64).max(0.0);
+    (0..count)
+        .map(|i| {
+            CGRect::new(
+                CGPoint::new(area.origin.x, area.origin.y + i as f64 * (height + gap)),
+                CGSize::new(area.size.width, height),
+            )
+        })
+        .collect()
+}
+
+/// Size of the master area of one layout.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct MasterArea {
+    master_ratio: f64,
+    /// The first `master_count` windows are in the master area.
+    master_count: usize,
+}
+
+impl WindowList<MasterArea> {
+    /// Number of windows currently shown in the master area.
+    fn masters(&self) -> usize { self.params.master_count.min(self.windows.len()) }
+
+    fn is_master(&self, idx: usize) -> bool { idx < self.masters() }
+
+    fn set_master_ratio(&mut self, ratio: f64) {
+        self.params.master_ratio = ratio.clamp(MIN_MASTER_RATIO, MAX_MASTER_RATIO);
+    }
+}
+
+/// dwm-style layout: the first `master_count` windows share the master area on
+/// the left, the remaining windows are tiled vertically in the stack area.
+pub type MasterStackLayoutSystem = ListLayoutSystem<MasterStack>;
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct MasterStack {
+    settings: MasterStackSettings,
+}
+
+impl MasterStackLayoutSystem {
+    pub fn new(settings: &MasterStackSettings) -> Self {
+        Self {
+            layouts: Default::default(),
+            arrangement: MasterStack { settings: settings.clone() },
+        }
+    }
+
+    /// Takes effect for new layouts and when a layout is rebalanced.
+    pub fn set_settings(&mut self, settings: &MasterStackSettings) {
+        self.arrangement.settings = settings.clone();
+    }
+
+    /// Changes how many windows are shown in the master area. At least one
+    /// window stays in the master area, and no more than the layout holds.
+    pub fn adjust_master_count(&mut self, layout: LayoutId, delta: isize) {
+        if let Some(list) = self.layouts.get_mut(layout) {
+            let count = list.params.master_count.min(list.windows.len());
+            list.params.master_count =
+                count.saturating_add_signed(delta).clamp(1, list.windows.len().max(1));
+        }
+    }
+
+    /// Moves the selected window to the top of the master area. If it is
+    /// already there, it is swapped with the next window instead.
+    pub fn promote_selection_to_master(&mut self, layout: LayoutId) -> Option<WindowId> {
+        let list = self.layouts.get_mut(layout)?;
+        let wid = list.selected_window()?;
+        if list.selected == 0 {
+            if list.windows.len() < 2 {
+                return None;
+            }
+            list.windows.swap(0, 1);
+            list.selected = 1;
+        } else {
+            list.windows.remove(list.selected);
+            list.windows.insert(0, wid);
+            list.selected = 0;
+        }
+        Some(wid)
+    }
+}
+
+impl Arrangement for MasterStack {
+    type Params = MasterArea;
+
+    const NAME: &'static str = "master_stack";
+
+    fn new_params(&self) -> MasterArea {
+        MasterArea {
+            master_ratio: self.settings.master_ratio,
+            master_count: self.settings.master_count,
+        }
+    }
+
+    fn describe(&self, list: &WindowList<MasterArea>) -> String {
+        format!(
+            "MasterStack ratio={:.2} count={}",
+            list.params.master_ratio, list.params.master_count
+        )
+    }
+
+    fn describe_window(&self, list: &WindowList<MasterArea>, idx: usize) -> String {
+        if list.is_master(idx) {
+            "master ".to_string()
+        } else {
+            "stack ".to_string()
+        }
+    }
+
+    fn frames(
+        &self,
+        list: &WindowList<MasterArea>,
+        tiling: CGRect,
+        gaps: &GapSettings,
+    ) -> Vec<CGRect> {
+        let masters = list.masters();
+        let stacked = list.windows.len() - masters;
+        if masters == 0 || stacked == 0 {
+            return column_frames(tiling, list.windows.len(), gaps.inner.vertical);
+        }
+        let gap = gaps.inner.horizontal;
+        let available = (tiling.size.width - gap).max(0.0);
+        let master_w = available * list.params.master_ratio;
+        let master_area = CGRect::new(tiling.origin, CGSize::new(master_w, tiling.size.height));
+        let stack_area = CGRect::new(
+            CGPoint::new(tiling.origin.x + master_w + gap, tiling.origin.y),
+            CGSize::new(available - master_w, tiling.size.height),
+        );
+        let mut frames = column_frames(master_area, masters, gaps.inner.vertical);
+        frames.extend(column_frames(stack_area, stacked, gaps.inner.vertical));
+        frames
+    }
+
+    fn neighbor_index(
+        &self,
+        list: &WindowList<MasterArea>,
+        idx: usize,
+        direction: Direction,
+    ) -> Option<usize> {
+        let len = list.windows.len();
+        if idx >= len {
+            return None;
+        }
+        let masters = list.masters();
+        let (start, end) = if list.is_master(idx) {
+            (0, masters)
+        } else {
+            (masters, len)
+        };
+        match direction {
+            Direction::Up => (idx > start).then(|| idx - 1),
+            Direction::Down => (idx + 1 < end).then(|| idx + 1),
+            Direction::Left | Direction::Right => {
+                let (target_start, target_end) = match (direction, list.is_master(idx)) {
+                    (Direction::Right, true) => (masters, len),
+                    (Direction::Left, false) => (0, masters),
+                    _ => return None,
+                };
+                if target_start == target_end {
+                    return None;
+                }
+                // Land on the window at the same relative height in the other column.
+                let offset = (idx - start) * (target_end - target_start) / (end - start);
+                Some((target_start + offset).min(target_end - 1))
+            }
+        }
+    }
+
+    fn entry_index(&self, list: &WindowList<MasterArea>, direction: Direction) -> Option<usize> {
+        // Entering from the right lands in the stack, everything else in the master area.
+        Some(match direction {
+            Direction::Left if list.windows.len() > list.masters() => list.masters(),
+            _ => 0,
+        })
+    }
+
+    fn resize_selection_by(&self, list: &mut WindowList<MasterArea>, amount: f64) {
+        // Growing a stack window means shrinking the master area.
+        let delta = if list.is_master(list.selected) {
+            amount
+        } else {
+            -amount
+        };
+        list.set_master_ratio(list.params.master_ratio + delta);
+    }
+
+    fn resize_selection_edge(
+        &self,
+        list: &mut WindowList<MasterArea>,
+        direction: Direction,
+        amount: f64,
+    ) -> bool {
+        if list.masters() == 0 || list.masters() == list.windows.len() {
+            return false;
+        }
+        // The only movable edge is the one between the master and stack areas.
+        let delta = match (list.is_master(list.selected), direction) {
+            (true, Direction::Right) => amount,
+            (false, Direction::Left) => -amount,
+            _ => return false,
+        };
+        list.set_master_ratio(list.params.master_ratio + delta);
+        true
+    }
+
+    fn set_selection_size(
+        &self,
+        list: &mut WindowList<MasterArea>,
+        target: SizeTarget,
+        screen: CGRect,
+        gaps: &GapSettings,
+    ) -> bool {
+        let masters = list.masters();
+        if masters == 0 || masters == list.windows.len() {
+            return false;
+        }
+        // Only the split between the master and stack areas can be sized.
+        let share = match target {
+            SizeTarget::Ratio(ratio) => ratio,
+            SizeTarget::Width(width) => {
+                let tiling = compute_tiling_area(screen, gaps);
+                let available = tiling.size.width - gaps.inner.horizontal;
+                if available <= 0.0 {
+                    return false;
+                }
+                width / available
+            }
+            SizeTarget::Height(_) => return false,
+        };
+        if list.is_master(list.selected) {
+            list.set_master_ratio(share);
+        } else {
+            list.set_master_ratio(1.0 - share);
+        }
+        true
+    }
+
+    fn rebalance(&self, list: &mut WindowList<MasterArea>) {
+        list.params.master_ratio = self.settings.master_ratio;
+    }
+
+    fn layout_tree(&self, list: &WindowList<MasterArea>) -> LayoutTreeNode {
+        let column = |windows: &[WindowId]| {
+            LayoutTreeNode::split(
+                Orientation::Vertical,
+                windows.iter().map(|&window_id| LayoutTreeNode::Window { window_id }).collect(),
+            )
+        };
+        let (masters, stacked) = list.windows.split_at(list.masters());
+        if masters.is_empty() || stacked.is_empty() {
+            return column(&list.windows);
+        }
+        LayoutTreeNode::Container {
+            kind: ContainerKind::Split,
+            orientation: Orientation::Horizontal,
+            proportions: vec![list.params.master_ratio, 1.0 - list.params.master_ratio],
+            children: vec![column(masters), column(stacked)],
+        }
+    }
+
+    /// A side-by-side container with two children is read as the master and
+    /// stack areas; any other tree only changes the order of the windows.
+    fn apply_layout_tree(&self, list: &mut WindowList<MasterArea>, tree: &LayoutTreeNode) {
+        if let LayoutTreeNode::Container {
+            orientation: Orientation::Horizontal,
+            children,
+            ..
+        } = tree
+            && let [master, _] = children.as_slice()
+        {
+            let master_windows = master.windows();
+            let masters = list.windows.iter().filter(|wid| master_windows.contains(wid)).count();
+            list.params.master_count = masters.max(1);
+            list.set_master_ratio(tree.shares()[0]);
+        }
+        list.windows = tree.arrange(&list.windows);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout_engine::systems::LayoutSystem;
+    use crate::layout_engine::systems::window_list::tests::{self, rect, w};
+
+    fn system_with(count: u32) -> (MasterStackLayoutSystem, LayoutId) { tests::system_with(count) }
+
+    fn frames(system: &MasterStackLayoutSystem, layout: LayoutId) -> Vec<(WindowId, CGRect)> {
+        tests::frames(
+            system,
+            layout,
+            rect(0., 0., 1000., 900.),
+            &GapSettings::default(),
+        )
+    }
+
+    #[test]
+    fn master_takes_ratio_and_stack_splits_remaining_space() {
+        let (system, layout) = system_with(4);
+
+        assert_eq!(frames(&system, layout), vec![
+            (w(1), rect(0., 0., 500., 900.)),
+            (w(2), rect(500., 0., 500., 300.)),
+            (w(3), rect(500., 300., 500., 300.)),
+            (w(4), rect(500., 600., 500., 300.)),
+        ]);
+    }
+
+    #[test]
+    fn resize_adjusts_master_ratio() {
+        let (mut system, layout) = system_with(2);
+        system.select_window(layout, w(1));
+        system.resize_selection_by(layout, 0.25);
+        assert_eq!(frames(&system, layout)[0].1, rect(0., 0., 750., 900.));
+
+        system.select_window(layout, w(2));
+        system.resize_selection_by(layout, 0.5);
+        assert_eq!(frames(&system, layout)[0].1, rect(0., 0., 250., 900.));
+
+        system.rebalance(layout);
+        assert_eq!(frames(&system, layout)[0].1, rect(0., 0., 500., 900.));
+    }
+
+    #[test]
+    fn master_count_moves_windows_between_areas() {
+        let (mut system, layout) = system_with(3);
+        system.adjust_master_count(layout, 1);
+        assert_eq!(frames(&system, layout), vec![
+            (w(1), rect(0., 0., 500., 450.)),
+            (w(2), rect(0., 450., 500., 450.)),
+            (w(3), rect(500., 0., 500., 900.)),
+        ]);
+
+        system.adjust_master_count(layout, -5);
+        assert_eq!(frames(&system, layout)[0].1, rect(0., 0., 500., 900.));
+    }
+
+    #[test]
+    fn master_count_stays_within_the_window_count() {
+        let (mut system, layout) = system_with(3);
+        system.adjust_master_count(layout, 10);
+        assert_eq!(frames(&system, layout)[0].1, rect(0., 0., 1000., 300.));
+
+        // Only as many steps as there are windows are needed to get back.
+        system.adjust_master_count(layout, -1);
+        assert_eq!(frames(&system, layout)[0].1, rect(0., 0., 500., 450.));
+
+        system.adjust_master_count(layout, -10);
+        system.adjust_master_count(layout, -1);
+        assert_eq!(frames(&system, layout)[0].1, rect(0., 0., 500., 900.));
+    }
+
+    #[test]
+    fn layout_tree_sets_the_master_area() {
+        let (mut system, layout) = system_with(3);
+        let window = |i| LayoutTreeNode::Window { window_id: w(i) };
+        system.apply_layout_tree(layout, &LayoutTreeNode::Container {
+            kind: ContainerKind::Split,
+            orientation: Orientation::Horizontal,
+            proportions: vec![0.7, 0.3],
+            children: vec![
+                LayoutTreeNode::split(Orientation::Vertical, vec![window(3), window(1)]),
+                window(2),
+            ],
+        });
+        assert_eq!(frames(&system, layout), vec![
+            (w(3), rect(0., 0., 700., 450.)),
+            (w(1), rect(0., 450., 700., 450.)),
+            (w(2), rect(700., 0., 300., 900.)),
+        ]);
+
+        let exported = system.layout_tree(layout);
+        assert_eq!(exported.windows(), vec![w(3), w(1), w(2)]);
+        assert_eq!(exported.shares()[0], 0.7);
+    }
+
+    #[test]
+    fn promote_moves_selection_to_master() {
+        let (mut system, layout) = system_with(3);
+        assert_eq!(system.promote_selection_to_master(layout), Some(w(3)));
+        assert_eq!(system.visible_windows_in_layout(layout), vec![w(3), w(1), w(2)]);
+        assert_eq!(system.selected_window(layout), Some(w(3)));
+
+        assert_eq!(system.promote_selection_to_master(layout), Some(w(3)));
+        assert_eq!(system.visible_windows_in_layout(layout), vec![w(1), w(3), w(2)]);
+    }
+
+    #[test]
+    fn focus_moves_between_master_and_stack() {
+        let (mut system, layout) = system_with(3);
+        system.select_window(layout, w(1));
+
+        assert_eq!(system.move_focus(layout, Direction::Right).0, Some(w(2)));
+        assert_eq!(system.move_focus(layout, Direction::Down).0, Some(w(3)));
+        assert_eq!(system.move_focus(layout, Direction::Down).0, None);
+        assert_eq!(system.move_focus(layout, Direction::Left).0, Some(w(1)));
+        assert_eq!(system.move_focus(layout, Direction::Left).0, None);
+    }
+}
@@ -0,0 +1,85 @@
+use objc2_core_foundation::CGRect;
+use serde::{Deserialize, Serialize};
+
+use crate::common::config::GapSettings;
+use crate::layout_engine::Direction;
+use crate::layout_engine::systems::window_list::{Arrangement, ListLayoutSystem, WindowList};
+
+/// Every window in a layout fills the tiling area; only the selected one is
+/// raised, and focus cycles through the rest in insertion order.
+pub type MonocleLayoutSystem = ListLayoutSystem<Monocle>;
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct Monocle;
+
+impl Arrangement for Monocle {
+    type Params = ();
+
+    const NAME: &'static str = "monocle";
+    const OVERLAPPING: bool = true;
+
+    fn new_params(&self) -> Self::Params {}
+
+    fn describe(&self, _list: &WindowList<()>) -> String { "Monocle".to_string() }
+
+    fn frames(&self, list: &WindowList<()>, tiling: CGRect, _gaps: &GapSettings) -> Vec<CGRect> {
+        vec![tiling; list.windows.len()]
+    }
+
+    fn neighbor_index(
+        &self,
+        list: &WindowList<()>,
+        idx: usize,
+        direction: Direction,
+    ) -> Option<usize> {
+        match direction {
+            Direction::Left | Direction::Up => idx.checked_sub(1),
+            Direction::Right | Direction::Down => (idx + 1 < list.windows.len()).then_some(idx + 1),
+        }
+    }
+
+    fn entry_index(&self, list: &WindowList<()>, _direction: Direction) -> Option<usize> {
+        Some(list.selected)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout_engine::systems::LayoutSystem;
+    use crate::layout_engine::systems::window_list::tests::{frames, rect, system_with, w};
+
+    #[test]
+    fn every_window_fills_the_tiling_area() {
+        let (system, layout): (MonocleLayoutSystem, _) = system_with(2);
+        let screen = rect(0., 0., 1000., 800.);
+
+        assert_eq!(frames(&system, layout, screen, &GapSettings::default()), vec![
+            (w(1), screen),
+            (w(2), screen)
+        ]);
+        assert_eq!(system.selected_window(layout), Some(w(2)));
+    }
+
+    #[test]
+    fn focus_cycles_through_windows() {
+        let (mut system, layout): (MonocleLayoutSystem, _) = system_with(3);
+
+        assert_eq!(
+            system.move_focus(layout, Direction::Right),
+            (Some(w(1)), vec![w(1)])
+        );
+        assert_eq!(system.move_focus(layout, Direction::Left).0, Some(w(3)));
+        assert_eq!(system.move_focus(layout, Direction::Left).0, Some(w(2)));
+    }
+
+    #[test]
+    fn removing_selected_window_selects_previous() {
+        let (mut system, layout): (MonocleLayoutSystem, _) = system_with(3);
+        system.select_window(layout, w(2));
+        system.remove_window(w(2));
+
+        assert_eq!(system.selected_window(layout), Some(w(1)));
+        assert_eq!(system.visible_windows_in_layout(layout), vec![w(1), w(3)]);
+    }
+}
@@ -0,0 +1,491 @@
+use objc2_core_foundation::CGRect;
+use serde::{Deserialize, Serialize};
+
+use crate::actor::app::{WindowId, pid_t};
+use crate::common::collections::HashMap;
+use crate::common::config::{LayoutMode, LayoutSettings};
+use crate::layout_engine::systems::{LayoutSystem, LayoutSystemKind, SizeTarget};
+use crate::layout_engine::{Direction, LayoutId, LayoutKind, LayoutTreeNode, Orientation};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+struct Route {
+    mode: LayoutMode,
+    inner: LayoutId,
+}
+
+/// Holds one layout system per [`LayoutMode`] in use and routes every layout
+/// to the system it was created in, so that different workspaces can use
+/// different layout modes.
+///
+/// Layout ids handed out by this type are stable across mode changes; only
+/// the id inside the backing system changes.
+#[derive(Serialize, Deserialize)]
+pub struct MultiLayoutSystem {
+    default_mode: LayoutMode,
+    systems: HashMap<LayoutMode, LayoutSystemKind>,
+    layouts: slotmap::SlotMap<LayoutId, Route>,
+    #[serde(skip)]
+    settings: LayoutSettings,
+}
+
+impl Default for MultiLayoutSystem {
+    fn default() -> Self { Self::new(&LayoutSettings::default()) }
+}
+
+impl MultiLayoutSystem {
+    pub fn new(settings: &LayoutSettings) -> Self {
+        let mut this = Self {
+            default_mode: settings.mode,
+            systems: HashMap::default(),
+            layouts: Default::default(),
+            settings: settings.clone(),
+        };
+        this.system_mut(settings.mode);
+        this
+    }
+
+    pub fn set_settings(&mut self, settings: &LayoutSettings) {
+        self.settings = settings.clone();
+        if let Some(LayoutSystemKind::Bsp(bsp)) = self.systems.get_mut(&LayoutMode::Bsp) {
+            bsp.set_settings(&settings.bsp);
+        }
+        if let Some(LayoutSystemKind::MasterStack(master_stack)) =
+            self.systems.get_mut(&LayoutMode::MasterStack)
+        {
+            master_stack.set_settings(&settings.master_stack);
+        }
+    }
+
+    pub fn default_mode(&self) -> LayoutMode { self.default_mode }
+
+    /// Sets the mode used for newly created layouts. Existing layouts are not
+    /// converted; see [`Self::convert_layout`].
+    pub fn set_default_mode(&mut self, mode: LayoutMode) {
+        self.default_mode = mode;
+        self.system_mut(mode);
+    }
+
+    pub fn mode_of(&self, layout: LayoutId) -> Option<LayoutMode> {
+        self.layouts.get(layout).map(|r| r.mode)
+    }
+
+    fn system_mut(&mut self, mode: LayoutMode) -> &mut LayoutSystemKind {
+        let settings = &self.settings;
+        self.systems
+            .entry(mode)
+            .or_insert_with(|| LayoutSystemKind::new(mode, settings))
+    }
+
+    /// Returns the system backing `layout` along with the layout's id inside it.
+    pub fn system_for_layout(&self, layout: LayoutId) -> Option<(&LayoutSystemKind, LayoutId)> {
+        let route = self.layouts.get(layout)?;
+        Some((self.systems.get(&route.mode)?, route.inner))
+    }
+
+    pub fn system_for_layout_mut(
+        &mut self,
+        layout: LayoutId,
+    ) -> Option<(&mut LayoutSystemKind, LayoutId)> {
+        let route = *self.layouts.get(layout)?;
+        Some((self.systems.get_mut(&route.mode)?, route.inner))
+    }
+
+    pub fn create_layout_with_mode(&mut self, mode: LayoutMode) -> LayoutId {
+        let inner = self.system_mut(mode).create_layout();
+        self.layouts.insert(Route { mode, inner })
+    }
+
+    /// Rebuilds `layout` in the system for `mode`, keeping its windows in order
+    /// and its selection. Returns false if the layout already uses `mode`.
+    pub fn convert_layout(&mut self, layout: LayoutId, mode: LayoutMode) -> bool {
+        let Some(route) = self.layouts.get(layout).copied() else {
+            return false;
+        };
+        if route.mode == mode {
+            return false;
+        }
+        let Some(old) = self.systems.get_mut(&route.mode) else {
+            return false;
+        };
+        let windows = old.windows_in_layout(route.inner);
+        let selected = old.selected_window(route.inner);
+        old.remove_layout(route.inner);
+
+        let new = self.system_mut(mode);
+        let inner = new.create_layout();
+        for wid in windows {
+            new.add_window_after_selection(inner, wid);
+        }
+        if let Some(wid) = selected {
+            new.select_window(inner, wid);
+        }
+        self.layouts[layout] = Route { mode, inner };
+        true
+    }
+}
+
+impl LayoutSystem for MultiLayoutSystem {
+    fn create_layout(&mut self) -> LayoutId { self.create_layout_with_mode(self.default_mode) }
+
+    fn clone_layout(&mut self, layout: LayoutId) -> LayoutId {
+        let Some((system, inner)) = self.system_for_layout_mut(layout) else {
+            return self.create_layout();
+        };
+        let inner = system.clone_layout(inner);
+        let mode = self.layouts[layout].mode;
+        self.layouts.insert(Route { mode, inner })
+    }
+
+    fn remove_layout(&mut self, layout: LayoutId) {
+        if let Some((system, inner)) = self.system_for_layout_mut(layout) {
+            system.remove_layout(inner);
+        }
+        self.layouts.remove(layout);
+    }
+
+    fn draw_tree(&self, layout: LayoutId) -> String {
+        match self.system_for_layout(layout) {
+            Some((system, inner)) => system.draw_tree(inner),
+            None => "<unknown layout>".to_string(),
+        }
+    }
+
+    fn calculate_layout(
+        &self,
+        layout: LayoutId,
+        screen: CGRect,
+        stack_offset: f64,
+        gaps: &crate::common::config::GapSettings,
+        stack_line_thickness: f64,
+        stack_line_horiz: crate::common::config::HorizontalPlacement,
+        stack_line_vert: crate::common::config::VerticalPlacement,
+    ) -> Vec<(WindowId, CGRect)> {
+        let Some((system, inner)) = self.system_for_layout(layout) else {
+            return vec![];
+        };
+        system.calculate_layout(
+            inner,
+            screen,
+            stack_offset,
+            gaps,
+            stack_line_thickness,
+            stack_line_horiz,
+            stack_line_vert,
+        )
+    }
+
+    fn selected_window(&self, layout: LayoutId) -> Option<WindowId> {
+        let (system, inner) = self.system_for_layout(layout)?;
+        system.selected_window(inner)
+    }
+
+    fn visible_windows_in_layout(&self, layout: LayoutId) -> Vec<WindowId> {
+        self.system_for_layout(layout)
+            .map(|(system, inner)| system.visible_windows_in_layout(inner))
+            .unwrap_or_default()
+    }
+
+    fn visible_windows_under_selection(&self, layout: LayoutId) -> Vec<WindowId> {
+        self.system_for_layout(layout)
+            .map(|(system, inner)| system.visible_windows_under_selection(inner))
+            .unwrap_or_default()
+    }
+
+    fn windows_in_layout(&self, layout: LayoutId) -> Vec<WindowId> {
+        self.system_for_layout(layout)
+            .map(|(system, inner)| system.windows_in_layout(inner))
+            .unwrap_or_default()
+    }
+
+    fn ascend_selection(&mut self, layout: LayoutId) -> bool {
+        self.system_for_layout_mut(layout)
+            .is_some_and(|(system, inner)| system.ascend_selection(inner))
+    }
+
+    fn descend_selection(&mut self, layout: LayoutId) -> bool {
+        self.system_for_layout_mut(layout)
+            .is_some_and(|(system, inner)| system.descend_selection(inner))
+    }
+
+    fn move_focus(
+        &mut self,
+        layout: LayoutId,
+        direction: Direction,
+    ) -> (Option<WindowId>, Vec<WindowId>) {
+        match self.system_for_layout_mut(layout) {
+            Some((system, inner)) => system.move_focus(inner, direction),
+            None => (None, vec![]),
+        }
+    }
+
+    fn window_in_direction(&self, layout: LayoutId, direction: Direction) -> Option<WindowId> {
+        let (system, inner) = self.system_for_layout(layout)?;
+        system.window_in_direction(inner, direction)
+    }
+
+    fn add_window_after_selection(&mut self, layout: LayoutId, wid: WindowId) {
+        if let Some((system, inner)) = self.system_for_layout_mut(layout) {
+            system.add_window_after_selection(inner, wid);
+        }
+    }
+
+    fn remove_window(&mut self, wid: WindowId) {
+        for system in self.systems.values_mut() {
+            system.remove_window(wid);
+        }
+    }
+
+    fn remove_windows_for_app(&mut self, pid: pid_t) {
+        for system in self.systems.values_mut() {
+            system.remove_windows_for_app(pid);
+        }
+    }
+
+    fn set_windows_for_app(&mut self, layout: LayoutId, pid: pid_t, desired: Vec<WindowId>) {
+        if let Some((system, inner)) = self.system_for_layout_mut(layout) {
+            system.set_windows_for_app(inner, pid, desired);
+        }
+    }
+
+    fn has_windows_for_app(&self, layout: LayoutId, pid: pid_t) -> bool {
+        self.system_for_layout(layout)
+            .is_some_and(|(system, inner)| system.has_windows_for_app(inner, pid))
+    }
+
+    fn contains_window(&self, layout: LayoutId, wid: WindowId) -> bool {
+        self.system_for_layout(layout)
+            .is_some_and(|(system, inner)| system.contains_window(inner, wid))
+    }
+
+    fn select_window(&mut self, layout: LayoutId, wid: WindowId) -> bool {
+        self.system_for_layout_mut(layout)
+            .is_some_and(|(system, inner)| system.select_window(inner, wid))
+    }
+
+    fn on_window_resized(
+        &mut self,
+        layout: LayoutId,
+        wid: WindowId,
+        old_frame: CGRect,
+        new_frame: CGRect,
+        screen: CGRect,
+        gaps: &crate::common::config::GapSettings,
+    ) {
+        if let Some((system, inner)) = self.system_for_layout_mut(layout) {
+            system.on_window_resized(inner, wid, old_frame, new_frame, screen, gaps);
+        }
+    }
+
+    fn swap_windows(&mut self, layout: LayoutId, a: WindowId, b: WindowId) -> bool {
+        self.system_for_layout_mut(layout)
+            .is_some_and(|(system, inner)| system.swap_windows(inner, a, b))
+    }
+
+    fn replace_window(&mut self, layout: LayoutId, old: WindowId, new: WindowId) -> bool {
+        self.system_for_layout_mut(layout)
+            .is_some_and(|(system, inner)| system.replace_window(inner, old, new))
+    }
+
+    fn move_selection(&mut self, layout: LayoutId, direction: Direction) -> bool {
+        self.system_for_layout_mut(layout)
+            .is_some_and(|(system, inner)| system.move_selection(inner, direction))
+    }
+
+    fn move_selection_to_layout_after_selection(
+        &mut self,
+        from_layout: LayoutId,
+        to_layout: LayoutId,
+    ) {
+        let (Some(from), Some(to)) = (
+            self.layouts.get(from_layout).copied(),
+            self.layouts.get(to_layout).copied(),
+        ) else {
+            return;
+        };
+        if from.mode == to.mode {
+            if let Some(system) = self.systems.get_mut(&from.mode) {
+                system.move_selection_to_layout_after_selection(from.inner, to.inner);
+            }
+            return;
+        }
+        let Some(wid) = self.selected_window(from_layout) else {
+            return;
+        };
+        if let Some(system) = self.systems.get_mut(&from.mode) {
+            system.remove_window(wid);
+        }
+        if let Some(system) = self.systems.get_mut(&to.mode) {
+            system.add_window_after_selection(to.inner, wid);
+        }
+    }
+
+    fn split_selection(&mut self, layout: LayoutId, kind: LayoutKind) {
+        if let Some((system, inner)) = self.system_for_layout_mut(layout) {
+            system.split_selection(inner, kind);
+        }
+    }
+
+    fn preselect(&mut self, layout: LayoutId, direction: Option<Direction>) -> bool {
+        self.system_for_layout_mut(layout)
+            .is_some_and(|(system, inner)| system.preselect(inner, direction))
+    }
+
+    fn preselection(&self, layout: LayoutId) -> Option<(WindowId, Direction, f64)> {
+        self.system_for_layout(layout)
+            .and_then(|(system, inner)| system.preselection(inner))
+    }
+
+    fn toggle_fullscreen_of_selection(&mut self, layout: LayoutId) -> Vec<WindowId> {
+        self.system_for_layout_mut(layout)
+            .map(|(system, inner)| system.toggle_fullscreen_of_selection(inner))
+            .unwrap_or_default()
+    }
+
+    fn toggle_fullscreen_within_gaps_of_selection(&mut self, layout: LayoutId) -> Vec<WindowId> {
+        self.system_for_layout_mut(layout)
+            .map(|(system, inner)| system.toggle_fullscreen_within_gaps_of_selection(inner))
+            .unwrap_or_default()
+    }
+
+    fn join_selection_with_direction(&mut self, layout: LayoutId, direction: Direction) {
+        if let Some((system, inner)) = self.system_for_layout_mut(layout) {
+            system.join_selection_with_direction(inner, direction);
+        }
+    }
+
+    fn apply_stacking_to_parent_of_selection(
+        &mut self,
+        layout: LayoutId,
+        default_orientation: crate::common::config::StackDefaultOrientation,
+    ) -> Vec<WindowId> {
+        self.system_for_layout_mut(layout)
+            .map(|(system, inner)| {
+                system.apply_stacking_to_parent_of_selection(inner, default_orientation)
+            })
+            .unwrap_or_default()
+    }
+
+    fn unstack_parent_of_selection(
+        &mut self,
+        layout: LayoutId,
+        default_orientation: crate::common::config::StackDefaultOrientation,
+    ) -> Vec<WindowId> {
+        self.system_for_layout_mut(layout)
+            .map(|(system, inner)| system.unstack_parent_of_selection(inner, default_orientation))
+            .unwrap_or_default()
+    }
+
+    fn parent_of_selection_is_stacked(&self, layout: LayoutId) -> bool {
+        self.system_for_layout(layout)
+            .is_some_and(|(system, inner)| system.parent_of_selection_is_stacked(inner))
+    }
+
+    fn unjoin_selection(&mut self, layout: LayoutId) {
+        if let Some((system, inner)) = self.system_for_layout_mut(layout) {
+            system.unjoin_selection(inner);
+        }
+    }
+
+    fn resize_selection_by(&mut self, layout: LayoutId, amount: f64) {
+        if let Some((system, inner)) = self.system_for_layout_mut(layout) {
+            system.resize_selection_by(inner, amount);
+        }
+    }
+
+    fn resize_selection_edge(
+        &mut self,
+        layout: LayoutId,
+        direction: Direction,
+        amount: f64,
+    ) -> bool {
+        self.system_for_layout_mut(layout)
+            .is_some_and(|(system, inner)| system.resize_selection_edge(inner, direction, amount))
+    }
+
+    fn set_selection_size(
+        &mut self,
+        layout: LayoutId,
+        target: SizeTarget,
+        screen: CGRect,
+        gaps: &crate::common::config::GapSettings,
+    ) -> bool {
+        self.system_for_layout_mut(layout)
+            .is_some_and(|(system, inner)| system.set_selection_size(inner, target, screen, gaps))
+    }
+
+    fn rebalance(&mut self, layout: LayoutId) {
+        if let Some((system, inner)) = self.system_for_layout_mut(layout) {
+            system.rebalance(inner);
+        }
+    }
+
+    fn rotate_selection(&mut self, layout: LayoutId, degrees: i32) {
+        if let Some((system, inner)) = self.system_for_layout_mut(layout) {
+            system.rotate_selection(inner, degrees);
+        }
+    }
+
+    fn flip_selection(&mut self, layout: LayoutId, axis: Orientation) {
+        if let Some((system, inner)) = self.system_for_layout_mut(layout) {
+            system.flip_selection(inner, axis);
+        }
+    }
+
+    fn toggle_tile_orientation(&mut self, layout: LayoutId) {
+        if let Some((system, inner)) = self.system_for_layout_mut(layout) {
+            system.toggle_tile_orientation(inner);
+        }
+    }
+
+    fn layout_tree(&self, layout: LayoutId) -> LayoutTreeNode {
+        self.system_for_layout(layout)
+            .map(|(system, inner)| system.layout_tree(inner))
+            .unwrap_or_else(|| LayoutTreeNode::split(Orientation::Horizontal, Vec::new()))
+    }
+
+    fn apply_layout_tree(&mut self, layout: LayoutId, tree: &LayoutTreeNode) {
+        if let Some((system, inner)) = self.system_for_layout_mut(layout) {
+            system.apply_layout_tree(inner, tree);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn w(idx: u32) -> WindowId { WindowId::new(1, idx) }
+
+    #[test]
+    fn layouts_are_routed_to_their_mode() {
+        let mut system = MultiLayoutSystem::default();
+        let traditional = system.create_layout();
+        let bsp = system.create_layout_with_mode(LayoutMode::Bsp);
+        system.add_window_after_selection(traditional, w(1));
+        system.add_window_after_selection(bsp, w(2));
+
+        assert_eq!(system.mode_of(traditional), Some(LayoutMode::Traditional));
+        assert_eq!(system.mode_of(bsp), Some(LayoutMode::Bsp));
+        assert_eq!(system.windows_in_layout(traditional), vec![w(1)]);
+        assert_eq!(system.windows_in_layout(bsp), vec![w(2)]);
+
+        system.remove_window(w(2));
+        assert!(system.windows_in_layout(bsp).is_empty());
+    }
+
+    #[test]
+    fn convert_layout_preserves_order_and_selection() {
+        let mut system = MultiLayoutSystem::default();
+        let layout = system.create_layout();
+        for i in 1..=3 {
+            system.add_window_after_selection(layout, w(i));
+        }
+        system.select_window(layout, w(2));
+
+        assert!(system.convert_layout(layout, LayoutMode::Bsp));
+        assert_eq!(system.mode_of(layout), Some(LayoutMode::Bsp));
+        assert_eq!(system.windows_in_layout(layout), vec![w(1), w(2), w(3)]);
+        assert_eq!(system.selected_window(layout), Some(w(2)));
+        assert!(!system.convert_layout(layout, LayoutMode::Bsp));
+    }
+}
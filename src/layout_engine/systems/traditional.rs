@@ -3,9 +3,11 @@ use serde::{Deserialize, Serialize};
 use tracing::warn;
 
 use crate::actor::app::{WindowId, pid_t};
-use crate::layout_engine::systems::LayoutSystem;
+use crate::layout_engine::systems::{LayoutSystem, SizeTarget};
 use crate::layout_engine::utils::compute_tiling_area;
-use crate::layout_engine::{Direction, LayoutId, LayoutKind, Orientation};
+use crate::layout_engine::{
+    ContainerKind, Direction, LayoutId, LayoutKind, LayoutTreeNode, Orientation,
+};
 use crate::model::selection::*;
 use crate::model::tree::{self, NodeId, NodeMap, OwnedNode, Tree};
 use crate::sys::geometry::Round;
@@ -401,6 +403,13 @@ impl LayoutSystem for TraditionalLayoutSystem {
         self.visible_windows_under_internal(selection)
     }
 
+    fn windows_in_layout(&self, layout: LayoutId) -> Vec<WindowId> {
+        self.root(layout)
+            .traverse_preorder(self.map())
+            .filter_map(|node| self.window_at(node))
+            .collect()
+    }
+
     fn ascend_selection(&mut self, layout: LayoutId) -> bool {
         if let Some(parent) = self.selection(layout).parent(self.map()) {
             self.select(parent);
@@ -608,6 +617,10 @@ impl LayoutSystem for TraditionalLayoutSystem {
         self.nest_in_container_internal(layout, selection, kind);
     }
 
+    fn preselect(&mut self, _layout: LayoutId, _direction: Option<Direction>) -> bool { false }
+
+    fn preselection(&self, _layout: LayoutId) -> Option<(WindowId, Direction, f64)> { None }
+
     fn toggle_fullscreen_of_selection(&mut self, layout: LayoutId) -> Vec<WindowId> {
         let node = self.selection(layout);
         if self.tree.data.layout.toggle_fullscreen(node) {
@@ -851,11 +864,58 @@ impl LayoutSystem for TraditionalLayoutSystem {
         }
     }
 
+    fn resize_selection_edge(
+        &mut self,
+        layout: LayoutId,
+        direction: Direction,
+        amount: f64,
+    ) -> bool {
+        let selection = self.selection(layout);
+        self.resize_internal(selection, amount, direction)
+    }
+
+    fn set_selection_size(
+        &mut self,
+        layout: LayoutId,
+        target: SizeTarget,
+        screen: CGRect,
+        gaps: &crate::common::config::GapSettings,
+    ) -> bool {
+        let orientation = target.orientation();
+        let Some(node) = self.selection(layout).ancestors(self.map()).find(|&node| {
+            node.parent(self.map()).is_some_and(|parent| {
+                let kind = self.layout(parent);
+                !kind.is_group() && orientation.is_none_or(|o| kind.orientation() == o)
+            })
+        }) else {
+            return false;
+        };
+        let parent = node.parent(self.map()).unwrap();
+
+        let share = match target {
+            SizeTarget::Ratio(ratio) => ratio,
+            SizeTarget::Width(length) | SizeTarget::Height(length) => {
+                let orientation = self.layout(parent).orientation();
+                let tiling_area = compute_tiling_area(screen, gaps);
+                let usable = self.usable_axis_length(parent, orientation, tiling_area, gaps);
+                if usable <= 0.0 {
+                    return false;
+                }
+                length / usable
+            }
+        };
+        self.tree.data.layout.set_share(&self.tree.map, node, share)
+    }
+
     fn rebalance(&mut self, layout: LayoutId) {
         let root = self.root(layout);
         self.rebalance_node(root)
     }
 
+    fn rotate_selection(&mut self, _layout: LayoutId, _degrees: i32) {}
+
+    fn flip_selection(&mut self, _layout: LayoutId, _axis: Orientation) {}
+
     fn swap_windows(&mut self, layout: LayoutId, a: WindowId, b: WindowId) -> bool {
         let node_a = match self.tree.data.window.node_for(layout, a) {
             Some(n) => n,
@@ -907,6 +967,31 @@ impl LayoutSystem for TraditionalLayoutSystem {
         true
     }
 
+    fn replace_window(&mut self, layout: LayoutId, old: WindowId, new: WindowId) -> bool {
+        let window = &mut self.tree.data.window;
+        if window.node_for(layout, new).is_some() {
+            return false;
+        }
+        let Some(node) = window.node_for(layout, old) else {
+            return false;
+        };
+
+        window.windows.insert(node, new);
+        if let Some(infos) = window.window_nodes.get_mut(&old) {
+            infos.0.retain(|info| info.layout != layout);
+            if infos.0.is_empty() {
+                window.window_nodes.remove(&old);
+            }
+        }
+        window
+            .window_nodes
+            .entry(new)
+            .or_default()
+            .0
+            .push(WindowNodeInfo { layout, node });
+        true
+    }
+
     fn toggle_tile_orientation(&mut self, layout: LayoutId) {
         use crate::layout_engine::LayoutKind;
 
@@ -934,6 +1019,46 @@ impl LayoutSystem for TraditionalLayoutSystem {
 
         self.rebalance(layout);
     }
+
+    fn layout_tree(&self, layout: LayoutId) -> LayoutTreeNode {
+        self.layout_tree_node(self.root(layout))
+    }
+
+    fn apply_layout_tree(&mut self, layout: LayoutId, tree: &LayoutTreeNode) {
+        let root = self.root(layout);
+        let selected = self.selected_window(layout);
+        let mut pending = self.windows_in_layout(layout);
+        let children: Vec<_> = root.children(self.map()).collect();
+        for child in children {
+            child.detach(&mut self.tree).remove();
+        }
+
+        match tree {
+            LayoutTreeNode::Container { kind, orientation, .. } => {
+                self.set_layout(root, kind.layout_kind(*orientation));
+                self.build_layout_tree(layout, root, tree, &mut pending);
+            }
+            LayoutTreeNode::Window { .. } => {
+                let wrapped =
+                    LayoutTreeNode::split(self.layout(root).orientation(), vec![tree.clone()]);
+                self.build_layout_tree(layout, root, &wrapped, &mut pending);
+            }
+        }
+        for wid in pending {
+            self.add_window_under(layout, root, wid);
+        }
+
+        match selected {
+            Some(wid) => {
+                self.select_window(layout, wid);
+            }
+            None => {
+                if let Some(first) = root.first_child(self.map()) {
+                    self.select(first);
+                }
+            }
+        }
+    }
 }
 
 impl TraditionalLayoutSystem {
@@ -1198,6 +1323,81 @@ impl TraditionalLayoutSystem {
         }
     }
 
+    fn layout_tree_node(&self, node: NodeId) -> LayoutTreeNode {
+        if let Some(window_id) = self.window_at(node) {
+            return LayoutTreeNode::Window { window_id };
+        }
+        let kind = self.layout(node);
+        let children: Vec<_> = node.children(self.map()).collect();
+        LayoutTreeNode::Container {
+            kind: ContainerKind::of(kind),
+            orientation: kind.orientation(),
+            proportions: children
+                .iter()
+                .map(|&child| self.tree.data.layout.proportion(self.map(), child).unwrap_or(0.0))
+                .collect(),
+            children: children.iter().map(|&child| self.layout_tree_node(child)).collect(),
+        }
+    }
+
+    /// Builds the children of the container `tree` under `parent`, taking
+    /// windows out of `pending` as they are placed. Containers holding only
+    /// one of the pending windows are skipped, so that every container built
+    /// has at least two children.
+    fn build_layout_tree(
+        &mut self,
+        layout: LayoutId,
+        parent: NodeId,
+        tree: &LayoutTreeNode,
+        pending: &mut Vec<WindowId>,
+    ) {
+        let LayoutTreeNode::Container { children, .. } = tree else {
+            return;
+        };
+        let mut built = Vec::new();
+        for (child, share) in children.iter().zip(tree.shares()) {
+            let mut child = child;
+            let windows = loop {
+                let windows: Vec<_> =
+                    child.windows().into_iter().filter(|wid| pending.contains(wid)).collect();
+                match child {
+                    LayoutTreeNode::Container { children, .. } if windows.len() == 1 => {
+                        child = children
+                            .iter()
+                            .find(|c| c.windows().iter().any(|wid| pending.contains(wid)))
+                            .unwrap();
+                    }
+                    _ => break windows,
+                }
+            };
+            if windows.is_empty() {
+                continue;
+            }
+            let node = match child {
+                LayoutTreeNode::Window { window_id } => {
+                    pending.retain(|wid| wid != window_id);
+                    self.add_window_under(layout, parent, *window_id)
+                }
+                LayoutTreeNode::Container { kind, orientation, .. } => {
+                    let node = self.tree.mk_node().push_back(parent);
+                    self.set_layout(node, kind.layout_kind(*orientation));
+                    self.build_layout_tree(layout, node, child, pending);
+                    node
+                }
+            };
+            built.push((node, share));
+        }
+
+        // Sizes are relative to the parent's total, which layout_axis expects to
+        // match the number of children.
+        let count = built.len() as f64;
+        let sum: f64 = built.iter().map(|(_, share)| share).sum();
+        for &(node, share) in &built {
+            self.tree.data.layout.info[node].size = (share / sum * count) as f32;
+        }
+        self.tree.data.layout.info[parent].total = count as f32;
+    }
+
     fn select(&mut self, selection: NodeId) {
         self.tree.data.selection.select(&self.tree.map, selection)
     }
@@ -1430,6 +1630,43 @@ impl TraditionalLayoutSystem {
         true
     }
 
+    /// Length along `orientation` that the children of `container` share,
+    /// mirroring how `layout_axis` divides the tiling area.
+    fn usable_axis_length(
+        &self,
+        container: NodeId,
+        orientation: Orientation,
+        tiling_area: CGRect,
+        gaps: &crate::common::config::GapSettings,
+    ) -> f64 {
+        let gap = match orientation {
+            Orientation::Horizontal => gaps.inner.horizontal,
+            Orientation::Vertical => gaps.inner.vertical,
+        };
+        let usable = |node: NodeId, length: f64| {
+            let children = node.children(self.map()).count();
+            (length - children.saturating_sub(1) as f64 * gap).max(0.0)
+        };
+
+        let mut length = match orientation {
+            Orientation::Horizontal => tiling_area.size.width,
+            Orientation::Vertical => tiling_area.size.height,
+        };
+        let path: Vec<_> = container.ancestors(self.map()).collect();
+        for &node in path.iter().rev() {
+            let Some(parent) = node.parent(self.map()) else {
+                continue;
+            };
+            let kind = self.layout(parent);
+            if kind.is_group() || kind.orientation() != orientation {
+                continue;
+            }
+            let proportion = self.tree.data.layout.proportion(self.map(), node).unwrap_or(1.0);
+            length = usable(parent, length) * proportion;
+        }
+        usable(container, length)
+    }
+
     fn resize_internal(&mut self, node: NodeId, screen_ratio: f64, direction: Direction) -> bool {
         let can_resize = |&node: &NodeId| -> bool {
             let Some(parent) = node.parent(&self.tree.map) else {
@@ -1851,6 +2088,10 @@ impl StackLayoutResult {
     }
 }
 
+/// Smallest size a child of a split may have before the split is reset to
+/// equal sizes.
+const MIN_CHILD_SIZE: f32 = 0.05;
+
 #[derive(Default, Serialize, Deserialize)]
 struct Layout {
     info: slotmap::SecondaryMap<NodeId, LayoutInfo>,
@@ -1914,6 +2155,37 @@ impl Layout {
         Some(f64::from(self.info[node].size) / f64::from(self.info[parent].total))
     }
 
+    /// Gives `node` `share` of its parent and scales its siblings to fill the
+    /// rest, keeping every child above the size `layout_axis` accepts.
+    fn set_share(&mut self, map: &NodeMap, node: NodeId, share: f64) -> bool {
+        let Some(parent) = node.parent(map) else { return false };
+        let siblings: Vec<_> = parent.children(map).filter(|&child| child != node).collect();
+        if siblings.is_empty() {
+            return false;
+        }
+
+        let total = (siblings.len() + 1) as f32;
+        let others: f32 = siblings.iter().map(|&child| self.info[child].size).sum();
+        let smallest = siblings.iter().map(|&child| self.info[child].size).fold(f32::MAX, f32::min);
+        if others <= 0.0 || smallest <= 0.0 {
+            return false;
+        }
+        let min_share = MIN_CHILD_SIZE / total;
+        let max_share = 1.0 - MIN_CHILD_SIZE * others / (smallest * total);
+        let size = (share as f32).clamp(min_share, max_share.max(min_share)) * total;
+
+        let scale = (total - size) / others;
+        let mut remaining = total;
+        for &child in &siblings {
+            let resized = (self.info[child].size * scale).max(MIN_CHILD_SIZE);
+            self.info[child].size = resized;
+            remaining -= resized;
+        }
+        self.info[node].size = remaining;
+        self.info[parent].total = total;
+        true
+    }
+
     fn take_share(&mut self, map: &NodeMap, node: NodeId, from: NodeId, share: f32) {
         assert_eq!(node.parent(map), from.parent(map));
         let share = share.min(self.info[from].size);
@@ -2108,7 +2380,7 @@ impl Layout {
         if children.is_empty() {
             return;
         }
-        let min_size = 0.05;
+        let min_size = MIN_CHILD_SIZE;
         let expected_total = children.len() as f32;
         let mut needs_normalization = false;
         let mut actual_total = 0.0;
@@ -2268,6 +2540,152 @@ mod tests {
         assert_eq!(system.window_in_direction(layout, Direction::Up), Some(w(2)));
     }
 
+    #[test]
+    fn replace_window_takes_over_the_node() {
+        let mut system = TraditionalLayoutSystem::default();
+        let layout = system.create_layout();
+        for i in 1..=3 {
+            system.add_window_after_selection(layout, w(i));
+        }
+
+        assert!(system.replace_window(layout, w(2), w(9)));
+        assert_eq!(system.windows_in_layout(layout), vec![w(1), w(9), w(3)]);
+        assert!(!system.contains_window(layout, w(2)));
+        assert!(!system.replace_window(layout, w(1), w(3)));
+        assert!(!system.replace_window(layout, w(2), w(4)));
+
+        assert!(system.replace_window(layout, w(9), w(2)));
+        assert_eq!(system.windows_in_layout(layout), vec![w(1), w(2), w(3)]);
+        system.remove_window(w(2));
+        assert_eq!(system.windows_in_layout(layout), vec![w(1), w(3)]);
+    }
+
+    #[test]
+    fn set_selection_size_takes_from_the_siblings() {
+        let mut system = TraditionalLayoutSystem::default();
+        let layout = system.create_layout();
+        let root = system.root(layout);
+        system.tree.data.layout.set_kind(root, LayoutKind::Horizontal);
+        for i in 1..=3 {
+            system.add_window_after_selection(layout, w(i));
+        }
+        system.select_window(layout, w(2));
+
+        let screen = CGRect::new(CGPoint::new(0.0, 0.0), CGSize::new(1000.0, 800.0));
+        let mut gaps = crate::common::config::GapSettings::default();
+        gaps.inner.horizontal = 10.0;
+        let widths = |system: &TraditionalLayoutSystem| -> Vec<f64> {
+            system
+                .calculate_layout(
+                    layout,
+                    screen,
+                    0.0,
+                    &gaps,
+                    0.0,
+                    Default::default(),
+                    Default::default(),
+                )
+                .into_iter()
+                .map(|(_, rect)| rect.size.width.round())
+                .collect()
+        };
+
+        assert!(system.set_selection_size(layout, SizeTarget::Ratio(0.5), screen, &gaps));
+        assert_eq!(widths(&system), vec![245.0, 490.0, 245.0]);
+
+        assert!(system.set_selection_size(layout, SizeTarget::Width(200.0), screen, &gaps));
+        assert_eq!(widths(&system), vec![390.0, 200.0, 390.0]);
+
+        assert!(!system.set_selection_size(layout, SizeTarget::Height(300.0), screen, &gaps));
+    }
+
+    #[test]
+    fn resize_selection_edge_moves_only_that_edge() {
+        let mut system = TraditionalLayoutSystem::default();
+        let layout = system.create_layout();
+        let root = system.root(layout);
+        system.tree.data.layout.set_kind(root, LayoutKind::Horizontal);
+        for i in 1..=3 {
+            system.add_window_after_selection(layout, w(i));
+        }
+        system.select_window(layout, w(2));
+
+        let screen = CGRect::new(CGPoint::new(0.0, 0.0), CGSize::new(900.0, 600.0));
+        let frames = |system: &TraditionalLayoutSystem| -> Vec<(f64, f64)> {
+            system
+                .calculate_layout(
+                    layout,
+                    screen,
+                    0.0,
+                    &Default::default(),
+                    0.0,
+                    Default::default(),
+                    Default::default(),
+                )
+                .into_iter()
+                .map(|(_, rect)| (rect.origin.x.round(), rect.size.width.round()))
+                .collect()
+        };
+
+        assert!(system.resize_selection_edge(layout, Direction::Right, 0.1));
+        assert_eq!(frames(&system), vec![
+            (0.0, 300.0),
+            (300.0, 390.0),
+            (690.0, 210.0)
+        ]);
+
+        assert!(system.resize_selection_edge(layout, Direction::Left, -0.1));
+        assert_eq!(frames(&system), vec![
+            (0.0, 390.0),
+            (390.0, 300.0),
+            (690.0, 210.0)
+        ]);
+
+        assert!(!system.resize_selection_edge(layout, Direction::Up, 0.1));
+    }
+
+    #[test]
+    fn apply_layout_tree_rebuilds_the_layout() {
+        let mut system = TraditionalLayoutSystem::default();
+        let layout = system.create_layout();
+        for i in 1..=4 {
+            system.add_window_after_selection(layout, w(i));
+        }
+        system.select_window(layout, w(2));
+
+        let window = |i| LayoutTreeNode::Window { window_id: w(i) };
+        let tree = LayoutTreeNode::Container {
+            kind: ContainerKind::Split,
+            orientation: Orientation::Horizontal,
+            proportions: vec![3.0, 1.0],
+            children: vec![window(3), LayoutTreeNode::Container {
+                kind: ContainerKind::Stack,
+                orientation: Orientation::Vertical,
+                proportions: vec![],
+                children: vec![window(1), window(9), window(2)],
+            }],
+        };
+        system.apply_layout_tree(layout, &tree);
+
+        // w9 is not in the layout and w4 is missing from the tree.
+        assert_eq!(system.layout_tree(layout), LayoutTreeNode::Container {
+            kind: ContainerKind::Split,
+            orientation: Orientation::Horizontal,
+            proportions: vec![0.5, 1.0 / 6.0, 1.0 / 3.0],
+            children: vec![
+                window(3),
+                LayoutTreeNode::Container {
+                    kind: ContainerKind::Stack,
+                    orientation: Orientation::Vertical,
+                    proportions: vec![0.5, 0.5],
+                    children: vec![window(1), window(2)],
+                },
+                window(4),
+            ],
+        });
+        assert_eq!(system.selected_window(layout), Some(w(2)));
+    }
+
     struct TestTraditionalLayoutSystem {
         system: TraditionalLayoutSystem,
         _root: OwnedNode,
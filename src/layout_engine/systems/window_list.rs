@@ -0,0 +1,670 @@
+//! Layout systems that arrange a flat, ordered list of windows.
+//!
+//! Monocle, grid and master/stack all keep each layout as a list of windows
+//! with one of them selected, and only differ in where the windows go on the
+//! screen and how focus moves between them. [`ListLayoutSystem`] implements
+//! [`LayoutSystem`] on top of the list; each of them only provides an
+//! [`Arrangement`].
+
+use objc2_core_foundation::CGRect;
+use serde::{Deserialize, Serialize};
+
+use crate::actor::app::{WindowId, pid_t};
+use crate::common::collections::HashSet;
+use crate::common::config::GapSettings;
+use crate::layout_engine::systems::{LayoutSystem, SizeTarget};
+use crate::layout_engine::utils::compute_tiling_area;
+use crate::layout_engine::{Direction, LayoutId, LayoutKind, LayoutTreeNode, Orientation};
+
+/// The windows of one layout in order, with the selection and the windows
+/// that were made fullscreen.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct WindowList<P> {
+    pub(super) windows: Vec<WindowId>,
+    pub(super) selected: usize,
+    fullscreen: HashSet<WindowId>,
+    fullscreen_within_gaps: HashSet<WindowId>,
+    /// Parameters of the arrangement for this layout
+    pub(super) params: P,
+}
+
+impl<P> WindowList<P> {
+    fn new(params: P) -> Self {
+        WindowList {
+            windows: Vec::new(),
+            selected: 0,
+            fullscreen: HashSet::default(),
+            fullscreen_within_gaps: HashSet::default(),
+            params,
+        }
+    }
+
+    pub(super) fn selected_window(&self) -> Option<WindowId> {
+        self.windows.get(self.selected).copied()
+    }
+
+    pub(super) fn position(&self, wid: WindowId) -> Option<usize> {
+        self.windows.iter().position(|&w| w == wid)
+    }
+
+    fn is_fullscreen(&self, wid: WindowId) -> bool {
+        self.fullscreen.contains(&wid) || self.fullscreen_within_gaps.contains(&wid)
+    }
+
+    /// Inserts `wid` after the selection and selects it, or only selects it if
+    /// it is already in the list.
+    fn insert_after_selection(&mut self, wid: WindowId) {
+        if let Some(idx) = self.position(wid) {
+            self.selected = idx;
+            return;
+        }
+        let idx = if self.windows.is_empty() {
+            0
+        } else {
+            self.selected + 1
+        };
+        self.windows.insert(idx, wid);
+        self.selected = idx;
+    }
+
+    /// Removes `wid`. If it was selected, the window that takes its place is
+    /// selected, or the one before it with `select_previous`.
+    fn remove(&mut self, wid: WindowId, select_previous: bool) {
+        let Some(idx) = self.position(wid) else {
+            return;
+        };
+        self.windows.remove(idx);
+        self.fullscreen.remove(&wid);
+        self.fullscreen_within_gaps.remove(&wid);
+        let moves = if select_previous {
+            idx <= self.selected
+        } else {
+            idx < self.selected || self.selected >= self.windows.len()
+        };
+        if moves {
+            self.selected = self.selected.saturating_sub(1);
+        }
+    }
+
+    fn select(&mut self, wid: WindowId) -> bool {
+        match self.position(wid) {
+            Some(idx) => {
+                self.selected = idx;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Where the windows of a [`WindowList`] go on the screen and how to move
+/// between them.
+pub trait Arrangement: Default + Serialize + for<'de> Deserialize<'de> {
+    /// Parameters kept for each layout, such as the size of the master area.
+    type Params: Clone + Serialize + for<'de> Deserialize<'de>;
+
+    /// Name used by `draw_tree` for a layout that does not exist.
+    const NAME: &'static str;
+
+    /// Whether the windows are drawn on top of each other rather than side by
+    /// side. Focus then wraps around the list and only raises the focused
+    /// window, and closing the selected window selects the one before it.
+    const OVERLAPPING: bool = false;
+
+    fn new_params(&self) -> Self::Params;
+
+    /// First line of `draw_tree`.
+    fn describe(&self, list: &WindowList<Self::Params>) -> String;
+
+    /// Where the window at `idx` is, shown before it in `draw_tree`.
+    fn describe_window(&self, _list: &WindowList<Self::Params>, _idx: usize) -> String {
+        String::new()
+    }
+
+    /// Frame of each window in the list, in order, within the tiling area.
+    fn frames(
+        &self,
+        list: &WindowList<Self::Params>,
+        tiling: CGRect,
+        gaps: &GapSettings,
+    ) -> Vec<CGRect>;
+
+    /// Index of the window next to the one at `idx` towards `direction`.
+    fn neighbor_index(
+        &self,
+        list: &WindowList<Self::Params>,
+        idx: usize,
+        direction: Direction,
+    ) -> Option<usize>;
+
+    /// Index of the window focus lands on when it enters the layout moving
+    /// towards `direction`.
+    fn entry_index(&self, list: &WindowList<Self::Params>, direction: Direction) -> Option<usize>;
+
+    fn resize_selection_by(&self, _list: &mut WindowList<Self::Params>, _amount: f64) {}
+
+    fn resize_selection_edge(
+        &self,
+        _list: &mut WindowList<Self::Params>,
+        _direction: Direction,
+        _amount: f64,
+    ) -> bool {
+        false
+    }
+
+    fn set_selection_size(
+        &self,
+        _list: &mut WindowList<Self::Params>,
+        _target: SizeTarget,
+        _screen: CGRect,
+        _gaps: &GapSettings,
+    ) -> bool {
+        false
+    }
+
+    fn rebalance(&self, _list: &mut WindowList<Self::Params>) {}
+
+    /// Describes the layout as a tree. By default the windows are a single row.
+    fn layout_tree(&self, list: &WindowList<Self::Params>) -> LayoutTreeNode {
+        LayoutTreeNode::split(
+            Orientation::Horizontal,
+            list.windows
+                .iter()
+                .map(|&window_id| LayoutTreeNode::Window { window_id })
+                .collect(),
+        )
+    }
+
+    /// Orders the windows like `tree`, along with anything else the
+    /// arrangement can take from it. The selection is kept by the caller.
+    fn apply_layout_tree(&self, list: &mut WindowList<Self::Params>, tree: &LayoutTreeNode) {
+        list.windows = tree.arrange(&list.windows);
+    }
+}
+
+/// [`LayoutSystem`] for an [`Arrangement`] of a flat list of windows.
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct ListLayoutSystem<A: Arrangement> {
+    pub(super) layouts: slotmap::SlotMap<LayoutId, WindowList<A::Params>>,
+    pub(super) arrangement: A,
+}
+
+impl<A: Arrangement> Default for ListLayoutSystem<A> {
+    fn default() -> Self {
+        ListLayoutSystem {
+            layouts: Default::default(),
+            arrangement: A::default(),
+        }
+    }
+}
+
+impl<A: Arrangement> LayoutSystem for ListLayoutSystem<A> {
+    fn create_layout(&mut self) -> LayoutId {
+        self.layouts.insert(WindowList::new(self.arrangement.new_params()))
+    }
+
+    fn clone_layout(&mut self, layout: LayoutId) -> LayoutId {
+        let list = match self.layouts.get(layout) {
+            Some(list) => list.clone(),
+            None => WindowList::new(self.arrangement.new_params()),
+        };
+        self.layouts.insert(list)
+    }
+
+    fn remove_layout(&mut self, layout: LayoutId) { self.layouts.remove(layout); }
+
+    fn draw_tree(&self, layout: LayoutId) -> String {
+        let Some(list) = self.layouts.get(layout) else {
+            return format!("<empty {}>", A::NAME);
+        };
+        let mut s = self.arrangement.describe(list);
+        s.push('\n');
+        for (idx, w) in list.windows.iter().enumerate() {
+            let marker = if idx == list.selected { "*" } else { " " };
+            let position = self.arrangement.describe_window(list, idx);
+            s.push_str(&format!("  {marker} {position}Window {w:?}\n"));
+        }
+        s
+    }
+
+    fn calculate_layout(
+        &self,
+        layout: LayoutId,
+        screen: CGRect,
+        _stack_offset: f64,
+        gaps: &crate::common::config::GapSettings,
+        _stack_line_thickness: f64,
+        _stack_line_horiz: crate::common::config::HorizontalPlacement,
+        _stack_line_vert: crate::common::config::VerticalPlacement,
+    ) -> Vec<(WindowId, CGRect)> {
+        let Some(list) = self.layouts.get(layout) else {
+            return vec![];
+        };
+        let tiling = compute_tiling_area(screen, gaps);
+        let frames = self.arrangement.frames(list, tiling, gaps);
+        list.windows
+            .iter()
+            .zip(frames)
+            .map(|(&w, cell)| {
+                let frame = if list.fullscreen.contains(&w) {
+                    screen
+                } else if list.fullscreen_within_gaps.contains(&w) {
+                    tiling
+                } else {
+                    cell
+                };
+                (w, frame)
+            })
+            .collect()
+    }
+
+    fn selected_window(&self, layout: LayoutId) -> Option<WindowId> {
+        self.layouts.get(layout).and_then(|list| list.selected_window())
+    }
+
+    fn visible_windows_in_layout(&self, layout: LayoutId) -> Vec<WindowId> {
+        self.layouts.get(layout).map(|list| list.windows.clone()).unwrap_or_default()
+    }
+
+    fn visible_windows_under_selection(&self, layout: LayoutId) -> Vec<WindowId> {
+        self.selected_window(layout).into_iter().collect()
+    }
+
+    fn windows_in_layout(&self, layout: LayoutId) -> Vec<WindowId> {
+        self.visible_windows_in_layout(layout)
+    }
+
+    fn ascend_selection(&mut self, _layout: LayoutId) -> bool { false }
+
+    fn descend_selection(&mut self, _layout: LayoutId) -> bool { false }
+
+    fn move_focus(
+        &mut self,
+        layout: LayoutId,
+        direction: Direction,
+    ) -> (Option<WindowId>, Vec<WindowId>) {
+        let Some(list) = self.layouts.get_mut(layout) else {
+            return (None, vec![]);
+        };
+        let len = list.windows.len();
+        let next = if A::OVERLAPPING {
+            (len > 1).then(|| match direction {
+                Direction::Left | Direction::Up => (list.selected + len - 1) % len,
+                Direction::Right | Direction::Down => (list.selected + 1) % len,
+            })
+        } else {
+            self.arrangement.neighbor_index(list, list.selected, direction)
+        };
+        let Some(next) = next else {
+            return (None, vec![]);
+        };
+        list.selected = next;
+        let focus = list.selected_window();
+        if A::OVERLAPPING {
+            (focus, focus.into_iter().collect())
+        } else {
+            (focus, list.windows.clone())
+        }
+    }
+
+    fn window_in_direction(&self, layout: LayoutId, direction: Direction) -> Option<WindowId> {
+        let list = self.layouts.get(layout)?;
+        let idx = self.arrangement.entry_index(list, direction)?;
+        list.windows.get(idx).copied()
+    }
+
+    fn add_window_after_selection(&mut self, layout: LayoutId, wid: WindowId) {
+        if let Some(list) = self.layouts.get_mut(layout) {
+            list.insert_after_selection(wid);
+        }
+    }
+
+    fn remove_window(&mut self, wid: WindowId) {
+        // A layout is cloned for every screen size, so the window can be in
+        // several of them.
+        for (_, list) in self.layouts.iter_mut() {
+            list.remove(wid, A::OVERLAPPING);
+        }
+    }
+
+    fn remove_windows_for_app(&mut self, pid: pid_t) {
+        for (_, list) in self.layouts.iter_mut() {
+            let windows: Vec<_> = list.windows.iter().copied().filter(|w| w.pid == pid).collect();
+            for w in windows {
+                list.remove(w, A::OVERLAPPING);
+            }
+        }
+    }
+
+    fn set_windows_for_app(&mut self, layout: LayoutId, pid: pid_t, desired: Vec<WindowId>) {
+        let desired_set: HashSet<WindowId> = desired.iter().copied().collect();
+        let Some(list) = self.layouts.get_mut(layout) else {
+            return;
+        };
+        let current: Vec<_> = list.windows.iter().copied().filter(|w| w.pid == pid).collect();
+        for &w in &current {
+            // Keep fullscreen windows in the layout, as the other systems do.
+            if !desired_set.contains(&w) && !list.is_fullscreen(w) {
+                list.remove(w, A::OVERLAPPING);
+            }
+        }
+        for w in desired {
+            if !current.contains(&w) {
+                list.insert_after_selection(w);
+            }
+        }
+    }
+
+    fn has_windows_for_app(&self, layout: LayoutId, pid: pid_t) -> bool {
+        self.layouts
+            .get(layout)
+            .is_some_and(|list| list.windows.iter().any(|w| w.pid == pid))
+    }
+
+    fn contains_window(&self, layout: LayoutId, wid: WindowId) -> bool {
+        self.layouts.get(layout).is_some_and(|list| list.windows.contains(&wid))
+    }
+
+    fn select_window(&mut self, layout: LayoutId, wid: WindowId) -> bool {
+        self.layouts.get_mut(layout).is_some_and(|list| list.select(wid))
+    }
+
+    fn on_window_resized(
+        &mut self,
+        layout: LayoutId,
+        wid: WindowId,
+        old_frame: CGRect,
+        new_frame: CGRect,
+        screen: CGRect,
+        gaps: &crate::common::config::GapSettings,
+    ) {
+        let Some(list) = self.layouts.get_mut(layout) else {
+            return;
+        };
+        if !list.windows.contains(&wid) {
+            return;
+        }
+        let tiling = compute_tiling_area(screen, gaps);
+        if new_frame == screen {
+            list.fullscreen.insert(wid);
+            list.fullscreen_within_gaps.remove(&wid);
+        } else if old_frame == screen {
+            list.fullscreen.remove(&wid);
+        } else if new_frame == tiling {
+            list.fullscreen_within_gaps.insert(wid);
+            list.fullscreen.remove(&wid);
+        } else if old_frame == tiling {
+            list.fullscreen_within_gaps.remove(&wid);
+        }
+    }
+
+    fn swap_windows(&mut self, layout: LayoutId, a: WindowId, b: WindowId) -> bool {
+        if a == b {
+            return false;
+        }
+        let Some(list) = self.layouts.get_mut(layout) else {
+            return false;
+        };
+        let (Some(ia), Some(ib)) = (list.position(a), list.position(b)) else {
+            return false;
+        };
+        list.windows.swap(ia, ib);
+        true
+    }
+
+    fn replace_window(&mut self, layout: LayoutId, old: WindowId, new: WindowId) -> bool {
+        let Some(list) = self.layouts.get_mut(layout) else {
+            return false;
+        };
+        if list.position(new).is_some() {
+            return false;
+        }
+        let Some(idx) = list.position(old) else {
+            return false;
+        };
+        list.windows[idx] = new;
+        list.fullscreen.remove(&old);
+        list.fullscreen_within_gaps.remove(&old);
+        true
+    }
+
+    fn move_selection(&mut self, layout: LayoutId, direction: Direction) -> bool {
+        let Some(list) = self.layouts.get_mut(layout) else {
+            return false;
+        };
+        let Some(target) = self.arrangement.neighbor_index(list, list.selected, direction) else {
+            return false;
+        };
+        list.windows.swap(list.selected, target);
+        list.selected = target;
+        true
+    }
+
+    fn move_selection_to_layout_after_selection(
+        &mut self,
+        from_layout: LayoutId,
+        to_layout: LayoutId,
+    ) {
+        let Some(w) = self.selected_window(from_layout) else {
+            return;
+        };
+        if let Some(list) = self.layouts.get_mut(from_layout) {
+            list.remove(w, A::OVERLAPPING);
+        }
+        self.add_window_after_selection(to_layout, w);
+    }
+
+    fn split_selection(&mut self, _layout: LayoutId, _kind: LayoutKind) {}
+
+    fn preselect(&mut self, _layout: LayoutId, _direction: Option<Direction>) -> bool { false }
+
+    fn preselection(&self, _layout: LayoutId) -> Option<(WindowId, Direction, f64)> { None }
+
+    fn toggle_fullscreen_of_selection(&mut self, layout: LayoutId) -> Vec<WindowId> {
+        let Some(list) = self.layouts.get_mut(layout) else {
+            return vec![];
+        };
+        let Some(w) = list.selected_window() else {
+            return vec![];
+        };
+        if !list.fullscreen.remove(&w) {
+            list.fullscreen.insert(w);
+            list.fullscreen_within_gaps.remove(&w);
+        }
+        vec![w]
+    }
+
+    fn toggle_fullscreen_within_gaps_of_selection(&mut self, layout: LayoutId) -> Vec<WindowId> {
+        let Some(list) = self.layouts.get_mut(layout) else {
+            return vec![];
+        };
+        let Some(w) = list.selected_window() else {
+            return vec![];
+        };
+        if !list.fullscreen_within_gaps.remove(&w) {
+            list.fullscreen_within_gaps.insert(w);
+            list.fullscreen.remove(&w);
+        }
+        vec![w]
+    }
+
+    fn join_selection_with_direction(&mut self, _layout: LayoutId, _direction: Direction) {}
+
+    fn apply_stacking_to_parent_of_selection(
+        &mut self,
+        _: LayoutId,
+        _: crate::common::config::StackDefaultOrientation,
+    ) -> Vec<WindowId> {
+        vec![]
+    }
+
+    fn unstack_parent_of_selection(
+        &mut self,
+        _: LayoutId,
+        _: crate::common::config::StackDefaultOrientation,
+    ) -> Vec<WindowId> {
+        vec![]
+    }
+
+    fn parent_of_selection_is_stacked(&self, _layout: LayoutId) -> bool { false }
+
+    fn unjoin_selection(&mut self, _layout: LayoutId) {}
+
+    fn resize_selection_by(&mut self, layout: LayoutId, amount: f64) {
+        if let Some(list) = self.layouts.get_mut(layout) {
+            self.arrangement.resize_selection_by(list, amount);
+        }
+    }
+
+    fn resize_selection_edge(
+        &mut self,
+        layout: LayoutId,
+        direction: Direction,
+        amount: f64,
+    ) -> bool {
+        self.layouts
+            .get_mut(layout)
+            .is_some_and(|list| self.arrangement.resize_selection_edge(list, direction, amount))
+    }
+
+    fn set_selection_size(
+        &mut self,
+        layout: LayoutId,
+        target: SizeTarget,
+        screen: CGRect,
+        gaps: &crate::common::config::GapSettings,
+    ) -> bool {
+        self.layouts
+            .get_mut(layout)
+            .is_some_and(|list| self.arrangement.set_selection_size(list, target, screen, gaps))
+    }
+
+    fn rebalance(&mut self, layout: LayoutId) {
+        if let Some(list) = self.layouts.get_mut(layout) {
+            self.arrangement.rebalance(list);
+        }
+    }
+
+    fn rotate_selection(&mut self, _layout: LayoutId, _degrees: i32) {}
+
+    fn flip_selection(&mut self, _layout: LayoutId, _axis: Orientation) {}
+
+    fn toggle_tile_orientation(&mut self, _layout: LayoutId) {}
+
+    fn layout_tree(&self, layout: LayoutId) -> LayoutTreeNode {
+        match self.layouts.get(layout) {
+            Some(list) => self.arrangement.layout_tree(list),
+            None => LayoutTreeNode::split(Orientation::Horizontal, Vec::new()),
+        }
+    }
+
+    fn apply_layout_tree(&mut self, layout: LayoutId, tree: &LayoutTreeNode) {
+        let Some(list) = self.layouts.get_mut(layout) else {
+            return;
+        };
+        let selected = list.selected_window();
+        self.arrangement.apply_layout_tree(list, tree);
+        if let Some(wid) = selected {
+            list.select(wid);
+        }
+    }
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use objc2_core_foundation::{CGPoint, CGSize};
+
+    use super::*;
+    use crate::common::config::{HorizontalPlacement, VerticalPlacement};
+    use crate::layout_engine::systems::GridLayoutSystem;
+
+    pub fn w(idx: u32) -> WindowId { WindowId::new(1, idx) }
+
+    pub fn rect(x: f64, y: f64, w: f64, h: f64) -> CGRect {
+        CGRect::new(CGPoint::new(x, y), CGSize::new(w, h))
+    }
+
+    /// A layout holding windows 1 to `count`, with the last one selected.
+    pub fn system_with<A: Arrangement>(count: u32) -> (ListLayoutSystem<A>, LayoutId) {
+        let mut system = ListLayoutSystem::<A>::default();
+        let layout = system.create_layout();
+        for i in 1..=count {
+            system.add_window_after_selection(layout, w(i));
+        }
+        (system, layout)
+    }
+
+    pub fn frames<A: Arrangement>(
+        system: &ListLayoutSystem<A>,
+        layout: LayoutId,
+        screen: CGRect,
+        gaps: &GapSettings,
+    ) -> Vec<(WindowId, CGRect)> {
+        system.calculate_layout(
+            layout,
+            screen,
+            0.0,
+            gaps,
+            0.0,
+            HorizontalPlacement::default(),
+            VerticalPlacement::default(),
+        )
+    }
+
+    #[test]
+    fn windows_are_added_after_the_selection() {
+        let (mut system, layout): (GridLayoutSystem, _) = system_with(3);
+        system.select_window(layout, w(1));
+        system.add_window_after_selection(layout, w(4));
+
+        assert_eq!(system.visible_windows_in_layout(layout), vec![
+            w(1),
+            w(4),
+            w(2),
+            w(3)
+        ]);
+        assert_eq!(system.selected_window(layout), Some(w(4)));
+    }
+
+    #[test]
+    fn removed_windows_leave_every_copy_of_a_layout() {
+        let (mut system, layout): (GridLayoutSystem, _) = system_with(3);
+        let copy = system.clone_layout(layout);
+        system.remove_window(w(2));
+
+        assert_eq!(system.visible_windows_in_layout(layout), vec![w(1), w(3)]);
+        assert_eq!(system.visible_windows_in_layout(copy), vec![w(1), w(3)]);
+    }
+
+    #[test]
+    fn swap_windows_exchanges_places() {
+        let (mut system, layout): (GridLayoutSystem, _) = system_with(4);
+
+        assert!(system.swap_windows(layout, w(1), w(4)));
+        assert_eq!(system.visible_windows_in_layout(layout), vec![
+            w(4),
+            w(2),
+            w(3),
+            w(1)
+        ]);
+        assert!(!system.swap_windows(layout, w(1), w(9)));
+    }
+
+    #[test]
+    fn fullscreen_windows_cover_the_screen_and_survive_app_updates() {
+        let (mut system, layout): (GridLayoutSystem, _) = system_with(2);
+        let screen = rect(0., 0., 1000., 800.);
+        assert_eq!(system.toggle_fullscreen_of_selection(layout), vec![w(2)]);
+        assert_eq!(
+            frames(&system, layout, screen, &GapSettings::default())[1],
+            (w(2), screen)
+        );
+
+        system.set_windows_for_app(layout, 1, vec![w(1)]);
+        assert_eq!(system.visible_windows_in_layout(layout), vec![w(1), w(2)]);
+        system.toggle_fullscreen_of_selection(layout);
+        system.set_windows_for_app(layout, 1, vec![w(1)]);
+        assert_eq!(system.visible_windows_in_layout(layout), vec![w(1)]);
+    }
+}
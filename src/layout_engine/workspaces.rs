@@ -1,7 +1,8 @@
 use objc2_core_foundation::CGSize;
 use serde::{Deserialize, Serialize};
 
-use super::{LayoutId, LayoutSystem};
+use super::{LayoutId, LayoutSystem, MultiLayoutSystem};
+use crate::common::config::LayoutMode;
 use crate::sys::screen::SpaceId;
 
 #[derive(Serialize, Deserialize, Debug, Default)]
@@ -43,11 +44,11 @@ impl WorkspaceLayouts {
         &mut self,
         space: SpaceId,
         size: CGSize,
-        workspaces: impl IntoIterator<Item = crate::model::VirtualWorkspaceId>,
-        tree: &mut impl LayoutSystem,
+        workspaces: impl IntoIterator<Item = (crate::model::VirtualWorkspaceId, Option<LayoutMode>)>,
+        tree: &mut MultiLayoutSystem,
     ) {
         let size = Size::from(size);
-        for workspace_id in workspaces {
+        for (workspace_id, mode) in workspaces {
             let workspace_key = (space, workspace_id);
             let (workspace_layout, mut unchanged) = match self.map.entry(workspace_key) {
                 crate::common::collections::hash_map::Entry::Vacant(entry) => (
@@ -80,6 +81,8 @@ impl WorkspaceLayouts {
                         source
                     } else if let Some(source) = workspace_layout.last_saved {
                         tree.clone_layout(source)
+                    } else if let Some(mode) = mode {
+                        tree.create_layout_with_mode(mode)
                     } else {
                         tree.create_layout()
                     })
@@ -145,6 +148,25 @@ impl WorkspaceLayouts {
         }
     }
 
+    /// Every layout kept for the workspace, one per screen size it has been seen at.
+    pub(crate) fn layouts_for_workspace(
+        &self,
+        space: SpaceId,
+        workspace_id: crate::model::VirtualWorkspaceId,
+    ) -> Vec<LayoutId> {
+        self.map
+            .get(&(space, workspace_id))
+            .map(|info| info.configurations.values().copied().collect())
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn all_layouts(&self) -> Vec<(crate::model::VirtualWorkspaceId, LayoutId)> {
+        self.map
+            .iter()
+            .flat_map(|(&(_, ws), info)| info.configurations.values().map(move |&l| (ws, l)))
+            .collect()
+    }
+
     pub(crate) fn active_layouts_for_space(
         &self,
         space: SpaceId,
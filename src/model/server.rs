@@ -3,8 +3,27 @@ use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
 
 use crate::actor::app::{WindowId, pid_t};
+use crate::layout_engine::Direction;
 use crate::sys::geometry::CGRectDef;
 
+/// Focus and workspace after a command ran, as returned by the `execute`
+/// request.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CommandEffect {
+    pub focused_window: Option<WindowId>,
+    pub focus_changed: bool,
+    /// Active workspace on the space commands apply to
+    pub workspace: Option<ActiveWorkspaceData>,
+    pub workspace_changed: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ActiveWorkspaceData {
+    pub id: String,
+    pub index: Option<u64>,
+    pub name: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorkspaceData {
     pub id: String,
@@ -23,6 +42,8 @@ pub struct WindowData {
     #[serde_as(as = "CGRectDef")]
     pub frame: CGRect,
     pub is_floating: bool,
+    pub is_sticky: bool,
+    pub marks: Vec<String>,
     pub is_focused: bool,
     pub bundle_id: Option<String>,
     pub window_server_id: Option<u32>,
@@ -44,6 +65,18 @@ pub struct LayoutStateData {
     pub floating_windows: Vec<WindowId>,
     pub tiled_windows: Vec<WindowId>,
     pub focused_window: Option<WindowId>,
+    /// Where the next tiled window will be inserted, if a preselection is active.
+    pub preselection: Option<PreselectionData>,
+}
+
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreselectionData {
+    pub window: WindowId,
+    pub direction: Direction,
+    /// The part of the window's frame the new window will take.
+    #[serde_as(as = "CGRectDef")]
+    pub area: CGRect,
 }
 
 #[serde_as]
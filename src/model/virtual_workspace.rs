@@ -6,7 +6,10 @@ use tracing::{error, warn};
 
 use crate::actor::app::WindowId;
 use crate::common::collections::{HashMap, HashSet};
-use crate::common::config::{AppWorkspaceRule, VirtualWorkspaceSettings, WorkspaceSelector};
+use crate::common::config::{
+    AppWorkspaceRule, FloatingPlacement, LayoutMode, VirtualWorkspaceSettings, WorkspaceLayoutRule,
+    WorkspaceSelector,
+};
 use crate::common::log::trace_misc;
 use crate::layout_engine::Direction;
 use crate::sys::app::pid_t;
@@ -44,6 +47,9 @@ pub struct VirtualWorkspace {
     pub space: SpaceId,
     windows: HashSet<WindowId>,
     last_focused: Option<WindowId>,
+    /// Layout mode chosen at runtime, overriding the configured one.
+    #[serde(default)]
+    layout_mode: Option<LayoutMode>,
 }
 
 impl VirtualWorkspace {
@@ -53,6 +59,7 @@ impl VirtualWorkspace {
             space,
             windows: HashSet::default(),
             last_focused: None,
+            layout_mode: None,
         }
     }
 
@@ -78,6 +85,24 @@ impl VirtualWorkspace {
     pub fn window_count(&self) -> usize { self.windows.len() }
 }
 
+/// Per-space scratchpad. Its windows live in a workspace that is not listed in
+/// `workspaces_by_space`, so they are hidden like any inactive workspace and
+/// never reached by workspace navigation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Scratchpad {
+    workspace: VirtualWorkspaceId,
+    /// Windows in the order they were sent, most recent last.
+    entries: Vec<ScratchpadEntry>,
+    /// Window currently shown over the active workspace, if any.
+    shown: Option<WindowId>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ScratchpadEntry {
+    window: WindowId,
+    name: Option<String>,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum HideCorner {
     BottomLeft,
@@ -99,7 +124,13 @@ pub struct VirtualWorkspaceManager {
     window_rule_floating: HashMap<(SpaceId, WindowId), bool>,
     #[serde(skip)]
     last_rule_decision: HashMap<(SpaceId, WindowId), bool>,
+    #[serde(skip)]
+    window_rule_placement: HashMap<WindowId, FloatingPlacement>,
     floating_positions: HashMap<(SpaceId, VirtualWorkspaceId), FloatingWindowPositions>,
+    #[serde(default)]
+    scratchpads: HashMap<SpaceId, Scratchpad>,
+    #[serde(default)]
+    sticky_windows: HashSet<WindowId>,
     workspace_counter: usize,
     #[serde(skip)]
     app_rules: Vec<AppWorkspaceRule>,
@@ -113,6 +144,8 @@ pub struct VirtualWorkspaceManager {
     default_workspace: usize,
     #[serde(skip)]
     workspace_auto_back_and_forth: bool,
+    #[serde(skip)]
+    workspace_layout_modes: Vec<WorkspaceLayoutRule>,
 }
 
 impl Default for VirtualWorkspaceManager {
@@ -140,7 +173,10 @@ impl VirtualWorkspaceManager {
             window_to_workspace: HashMap::default(),
             window_rule_floating: HashMap::default(),
             last_rule_decision: HashMap::default(),
+            window_rule_placement: HashMap::default(),
             floating_positions: HashMap::default(),
+            scratchpads: HashMap::default(),
+            sticky_windows: HashSet::default(),
             workspace_counter: 1,
             app_rules: config.app_rules.clone(),
             max_workspaces,
@@ -148,6 +184,7 @@ impl VirtualWorkspaceManager {
             default_workspace_names: config.workspace_names.clone(),
             default_workspace,
             workspace_auto_back_and_forth: config.workspace_auto_back_and_forth,
+            workspace_layout_modes: config.layout_modes.clone(),
         }
     }
 
@@ -156,6 +193,7 @@ impl VirtualWorkspaceManager {
         self.default_workspace_count = config.default_workspace_count;
         self.default_workspace_names = config.workspace_names.clone();
         self.workspace_auto_back_and_forth = config.workspace_auto_back_and_forth;
+        self.workspace_layout_modes = config.layout_modes.clone();
 
         let target_count = self.default_workspace_count.max(1).min(self.max_workspaces);
         self.default_workspace = config.default_workspace.min(target_count - 1);
@@ -275,6 +313,16 @@ impl VirtualWorkspaceManager {
             new_positions.insert((target_space, ws_id), positions);
         }
         self.floating_positions = new_positions;
+
+        if let Some(existing) = self.scratchpads.remove(&new_space) {
+            self.workspaces.remove(existing.workspace);
+        }
+        if let Some(scratchpad) = self.scratchpads.remove(&old_space) {
+            if let Some(ws) = self.workspaces.get_mut(scratchpad.workspace) {
+                ws.space = new_space;
+            }
+            self.scratchpads.insert(new_space, scratchpad);
+        }
     }
 
     pub fn create_workspace(
@@ -324,6 +372,115 @@ impl VirtualWorkspaceManager {
 
     pub fn workspace_auto_back_and_forth(&self) -> bool { self.workspace_auto_back_and_forth }
 
+    /// Layout mode for the workspace: the runtime override if one was set,
+    /// otherwise the first matching `layout_modes` rule from the config.
+    pub fn layout_mode(&self, workspace_id: VirtualWorkspaceId) -> Option<LayoutMode> {
+        let workspace = self.workspaces.get(workspace_id)?;
+        if let Some(mode) = workspace.layout_mode {
+            return Some(mode);
+        }
+        let idx = self
+            .workspaces_by_space
+            .get(&workspace.space)
+            .and_then(|ids| ids.iter().position(|&id| id == workspace_id));
+        self.workspace_layout_modes
+            .iter()
+            .find(|rule| match &rule.workspace {
+                WorkspaceSelector::Index(i) => idx == Some(*i),
+                WorkspaceSelector::Name(name) => *name == workspace.name,
+            })
+            .map(|rule| rule.mode)
+    }
+
+    pub fn set_layout_mode(&mut self, workspace_id: VirtualWorkspaceId, mode: LayoutMode) {
+        if let Some(workspace) = self.workspaces.get_mut(workspace_id) {
+            workspace.layout_mode = Some(mode);
+        }
+    }
+
+    fn scratchpad_workspace(&mut self, space: SpaceId) -> VirtualWorkspaceId {
+        if let Some(scratchpad) = self.scratchpads.get(&space) {
+            return scratchpad.workspace;
+        }
+        let workspace = self.workspaces.insert(VirtualWorkspace::new("Scratchpad".into(), space));
+        self.scratchpads.insert(space, Scratchpad {
+            workspace,
+            entries: Vec::new(),
+            shown: None,
+        });
+        workspace
+    }
+
+    fn is_scratchpad_workspace(&self, workspace_id: VirtualWorkspaceId) -> bool {
+        self.scratchpads.values().any(|s| s.workspace == workspace_id)
+    }
+
+    fn forget_scratchpad_window(&mut self, window_id: WindowId) {
+        for scratchpad in self.scratchpads.values_mut() {
+            scratchpad.entries.retain(|entry| entry.window != window_id);
+            if scratchpad.shown == Some(window_id) {
+                scratchpad.shown = None;
+            }
+        }
+    }
+
+    /// Moves the window into the space's scratchpad, optionally under a name
+    /// that `toggle_scratchpad` can refer to. A name already in use is taken
+    /// over by the new window; the window that had it stays in the scratchpad
+    /// without a name.
+    pub fn send_to_scratchpad(
+        &mut self,
+        space: SpaceId,
+        window_id: WindowId,
+        name: Option<String>,
+    ) -> bool {
+        self.ensure_space_initialized(space);
+        let workspace = self.scratchpad_workspace(space);
+        self.remove_floating_position(window_id);
+        if !self.assign_window_to_workspace(space, window_id, workspace) {
+            return false;
+        }
+        let scratchpad = self.scratchpads.get_mut(&space).expect("scratchpad was just created");
+        scratchpad.entries.retain(|entry| entry.window != window_id);
+        if name.is_some() {
+            for entry in scratchpad.entries.iter_mut().filter(|entry| entry.name == name) {
+                entry.name = None;
+            }
+        }
+        scratchpad.entries.push(ScratchpadEntry { window: window_id, name });
+        if scratchpad.shown == Some(window_id) {
+            scratchpad.shown = None;
+        }
+        true
+    }
+
+    pub fn is_scratchpad_window(&self, window_id: WindowId) -> bool {
+        self.scratchpads
+            .values()
+            .any(|s| s.entries.iter().any(|entry| entry.window == window_id))
+    }
+
+    /// The named scratchpad window, or the most recently sent one.
+    pub fn scratchpad_window(&self, space: SpaceId, name: Option<&str>) -> Option<WindowId> {
+        let entries = &self.scratchpads.get(&space)?.entries;
+        match name {
+            Some(name) => entries.iter().find(|entry| entry.name.as_deref() == Some(name)),
+            None => entries.last(),
+        }
+        .map(|entry| entry.window)
+    }
+
+    pub fn shown_scratchpad_window(&self, space: SpaceId) -> Option<WindowId> {
+        self.scratchpads.get(&space)?.shown
+    }
+
+    pub fn set_shown_scratchpad_window(&mut self, space: SpaceId, window_id: Option<WindowId>) {
+        if let Some(scratchpad) = self.scratchpads.get_mut(&space) {
+            scratchpad.shown =
+                window_id.filter(|wid| scratchpad.entries.iter().any(|entry| entry.window == *wid));
+        }
+    }
+
     pub fn set_active_workspace(
         &mut self,
         space: SpaceId,
@@ -336,6 +493,7 @@ impl VirtualWorkspaceManager {
                 && self.workspaces.get(workspace_id).map(|w| w.space) == Some(space)
             {
                 self.active_workspace_per_space.insert(space, (active, workspace_id));
+                self.carry_sticky_windows(space, workspace_id);
                 true
             } else {
                 error!(
@@ -349,6 +507,59 @@ impl VirtualWorkspaceManager {
         })
     }
 
+    /// Moves the space's sticky windows into `workspace_id`, along with their
+    /// stored floating positions, so they stay on screen across switches.
+    fn carry_sticky_windows(&mut self, space: SpaceId, workspace_id: VirtualWorkspaceId) {
+        let carried: Vec<(WindowId, VirtualWorkspaceId)> = self
+            .sticky_windows
+            .iter()
+            .filter_map(|&wid| {
+                let from = *self.window_to_workspace.get(&(space, wid))?;
+                (from != workspace_id && !self.is_scratchpad_workspace(from)).then_some((wid, from))
+            })
+            .collect();
+
+        for (wid, from) in carried {
+            if let Some(workspace) = self.workspaces.get_mut(from) {
+                workspace.remove_window(wid);
+            }
+            if let Some(workspace) = self.workspaces.get_mut(workspace_id) {
+                workspace.add_window(wid);
+            }
+            self.window_to_workspace.insert((space, wid), workspace_id);
+
+            let position = self
+                .floating_positions
+                .get_mut(&(space, from))
+                .and_then(|positions| positions.remove_position(wid));
+            if let Some(position) = position {
+                self.floating_positions
+                    .entry((space, workspace_id))
+                    .or_default()
+                    .store_position(wid, position);
+            }
+        }
+    }
+
+    /// Placement policy from the app rule matching the window, if it sets one.
+    pub fn placement_for_window(&self, window_id: WindowId) -> Option<FloatingPlacement> {
+        self.window_rule_placement.get(&window_id).copied()
+    }
+
+    pub fn is_sticky(&self, window_id: WindowId) -> bool {
+        self.sticky_windows.contains(&window_id)
+    }
+
+    /// Flips the sticky flag of a window and returns the new value.
+    pub fn toggle_sticky(&mut self, window_id: WindowId) -> bool {
+        if self.sticky_windows.remove(&window_id) {
+            false
+        } else {
+            self.sticky_windows.insert(window_id);
+            true
+        }
+    }
+
     fn filtered_workspace_ids(
         &self,
         space: SpaceId,
@@ -477,6 +688,10 @@ impl VirtualWorkspaceManager {
                 }
             }
 
+            if !self.is_scratchpad_workspace(workspace_id) {
+                self.forget_scratchpad_window(window_id);
+            }
+
             if let Some(workspace) = self.workspaces.get_mut(workspace_id) {
                 workspace.add_window(window_id);
                 self.window_to_workspace.insert((space, window_id), workspace_id);
@@ -519,6 +734,9 @@ impl VirtualWorkspaceManager {
                 self.last_rule_decision.remove(&(space, wid));
             }
         }
+        self.forget_scratchpad_window(window_id);
+        self.sticky_windows.remove(&window_id);
+        self.window_rule_placement.remove(&window_id);
     }
 
     pub fn remove_windows_for_app(&mut self, pid: pid_t) {
@@ -543,6 +761,14 @@ impl VirtualWorkspaceManager {
                 self.last_rule_decision.remove(&(space, window_id));
             }
         }
+        self.sticky_windows.retain(|wid| wid.pid != pid);
+        self.window_rule_placement.retain(|wid, _| wid.pid != pid);
+        for scratchpad in self.scratchpads.values_mut() {
+            scratchpad.entries.retain(|entry| entry.window.pid != pid);
+            if scratchpad.shown.is_some_and(|wid| wid.pid == pid) {
+                scratchpad.shown = None;
+            }
+        }
     }
 
     /// Gets all windows in the active virtual workspace for a given native space.
@@ -556,6 +782,9 @@ impl VirtualWorkspaceManager {
     }
 
     pub fn is_window_in_active_workspace(&self, space: SpaceId, window_id: WindowId) -> bool {
+        if self.shown_scratchpad_window(space) == Some(window_id) {
+            return true;
+        }
         if let Some(active_workspace_id) = self.active_workspace(space) {
             if let Some(window_workspace_id) = self.window_to_workspace.get(&(space, window_id)) {
                 return *window_workspace_id == active_workspace_id;
@@ -566,11 +795,13 @@ impl VirtualWorkspaceManager {
 
     pub fn windows_in_inactive_workspaces(&self, space: SpaceId) -> Vec<WindowId> {
         let active_workspace_id = self.active_workspace(space);
+        let shown_scratchpad = self.shown_scratchpad_window(space);
 
         self.workspaces
             .iter()
             .filter(|(id, workspace)| workspace.space == space && Some(*id) != active_workspace_id)
             .flat_map(|(_, workspace)| workspace.windows())
+            .filter(|wid| Some(*wid) != shown_scratchpad)
             .collect()
     }
 
@@ -785,6 +1016,15 @@ impl VirtualWorkspaceManager {
         }
     }
 
+    pub fn app_floating_positions(&self, pid: pid_t) -> Vec<(WindowId, CGRect)> {
+        self.floating_positions
+            .values()
+            .flat_map(|positions| positions.positions.iter())
+            .filter(|(wid, _)| wid.pid == pid)
+            .map(|(&wid, &frame)| (wid, frame))
+            .collect()
+    }
+
     pub fn remove_app_floating_positions(&mut self, pid: pid_t) {
         for positions in self.floating_positions.values_mut() {
             positions.remove_app_windows(pid);
@@ -873,7 +1113,25 @@ impl VirtualWorkspaceManager {
 
         let existing_assignment = self.window_to_workspace.get(&(space, window_id)).copied();
 
+        match rule_match.as_ref().and_then(|rule| rule.placement) {
+            Some(placement) => self.window_rule_placement.insert(window_id, placement),
+            None => self.window_rule_placement.remove(&window_id),
+        };
+
         if let Some(rule) = rule_match {
+            if let Some(name) = rule.scratchpad.clone() {
+                let workspace_id = match existing_assignment {
+                    Some(existing_ws) => Some(existing_ws),
+                    None => self
+                        .send_to_scratchpad(space, window_id, Some(name))
+                        .then(|| self.scratchpad_workspace(space)),
+                };
+                if let Some(workspace_id) = workspace_id {
+                    self.window_rule_floating.insert((space, window_id), true);
+                    return Ok((workspace_id, true, prev_rule_decision));
+                }
+            }
+
             let target_workspace_id = if let Some(ref ws_sel) = rule.workspace {
                 let maybe_idx: Option<usize> = match ws_sel {
                     WorkspaceSelector::Index(i) => Some(*i),
@@ -926,22 +1184,29 @@ impl VirtualWorkspaceManager {
                 }
             };
 
+            // Sticky windows always float; they are carried between workspaces
+            // rather than tiled in any one of them.
+            let floating = rule.floating || rule.sticky;
+            if rule.sticky {
+                self.sticky_windows.insert(window_id);
+            }
+
             if let Some(existing_ws) = existing_assignment {
-                if rule.floating {
+                if floating {
                     self.window_rule_floating.insert((space, window_id), true);
                 } else {
                     self.window_rule_floating.remove(&(space, window_id));
                 }
-                return Ok((existing_ws, rule.floating, prev_rule_decision));
+                return Ok((existing_ws, floating, prev_rule_decision));
             }
 
             if self.assign_window_to_workspace(space, window_id, target_workspace_id) {
-                if rule.floating {
+                if floating {
                     self.window_rule_floating.insert((space, window_id), true);
                 } else {
                     self.window_rule_floating.remove(&(space, window_id));
                 }
-                return Ok((target_workspace_id, rule.floating, prev_rule_decision));
+                return Ok((target_workspace_id, floating, prev_rule_decision));
             } else {
                 error!("Failed to assign window to workspace from app rule");
             }
@@ -1170,13 +1435,18 @@ impl VirtualWorkspaceManager {
 
     pub fn get_stats(&self) -> WorkspaceStats {
         let mut stats = WorkspaceStats {
-            total_workspaces: self.workspaces.len(),
+            total_workspaces: 0,
             total_windows: self.window_to_workspace.len(),
             active_spaces: self.active_workspace_per_space.len(),
             workspace_window_counts: HashMap::default(),
         };
 
+        // Scratchpads are kept in hidden workspaces that are not listed.
         for (workspace_id, workspace) in &self.workspaces {
+            if self.is_scratchpad_workspace(workspace_id) {
+                continue;
+            }
+            stats.total_workspaces += 1;
             stats.workspace_window_counts.insert(workspace_id, workspace.window_count());
         }
 
@@ -1344,6 +1614,131 @@ mod tests {
         assert_eq!(manager.active_workspace(space), Some(expected_ws));
     }
 
+    #[test]
+    fn layout_mode_rules_and_override() {
+        let mut settings = VirtualWorkspaceSettings::default();
+        settings.default_workspace_count = 3;
+        settings.workspace_names = vec!["main".into(), "code".into(), "chat".into()];
+        settings.layout_modes = vec![
+            WorkspaceLayoutRule {
+                workspace: WorkspaceSelector::Index(0),
+                mode: LayoutMode::Bsp,
+            },
+            WorkspaceLayoutRule {
+                workspace: WorkspaceSelector::Name("chat".into()),
+                mode: LayoutMode::Monocle,
+            },
+        ];
+
+        let mut manager = VirtualWorkspaceManager::new_with_config(&settings);
+        let space = SpaceId::new(1);
+        let ids: Vec<_> = manager.list_workspaces(space).into_iter().map(|(id, _)| id).collect();
+
+        assert_eq!(manager.layout_mode(ids[0]), Some(LayoutMode::Bsp));
+        assert_eq!(manager.layout_mode(ids[1]), None);
+        assert_eq!(manager.layout_mode(ids[2]), Some(LayoutMode::Monocle));
+
+        manager.set_layout_mode(ids[0], LayoutMode::Grid);
+        assert_eq!(manager.layout_mode(ids[0]), Some(LayoutMode::Grid));
+    }
+
+    #[test]
+    fn scratchpad_windows_are_hidden_until_shown() {
+        let mut manager = VirtualWorkspaceManager::new();
+        let space = SpaceId::new(1);
+        let term = WindowId::new(1, 1);
+        let notes = WindowId::new(1, 2);
+        let ws = manager.list_workspaces(space)[0].0;
+        assert!(manager.assign_window_to_workspace(space, term, ws));
+        assert!(manager.assign_window_to_workspace(space, notes, ws));
+
+        assert!(manager.send_to_scratchpad(space, term, Some("term".into())));
+        assert!(manager.send_to_scratchpad(space, notes, None));
+        assert!(manager.is_scratchpad_window(term));
+        assert_eq!(manager.list_workspaces(space).len(), 4);
+        assert!(manager.windows_in_active_workspace(space).is_empty());
+        assert!(manager.windows_in_inactive_workspaces(space).contains(&term));
+
+        assert_eq!(manager.scratchpad_window(space, None), Some(notes));
+        assert_eq!(manager.scratchpad_window(space, Some("term")), Some(term));
+        assert_eq!(manager.scratchpad_window(space, Some("missing")), None);
+
+        manager.set_shown_scratchpad_window(space, Some(term));
+        assert!(manager.is_window_in_active_workspace(space, term));
+        assert!(!manager.windows_in_inactive_workspaces(space).contains(&term));
+
+        assert!(manager.assign_window_to_workspace(space, term, ws));
+        assert!(!manager.is_scratchpad_window(term));
+        assert_eq!(manager.shown_scratchpad_window(space), None);
+    }
+
+    #[test]
+    fn scratchpad_names_can_be_taken_over() {
+        let mut manager = VirtualWorkspaceManager::new();
+        let space = SpaceId::new(1);
+        let old = WindowId::new(1, 1);
+        let new = WindowId::new(1, 2);
+        let ws = manager.list_workspaces(space)[0].0;
+        assert!(manager.assign_window_to_workspace(space, old, ws));
+        assert!(manager.assign_window_to_workspace(space, new, ws));
+
+        assert!(manager.send_to_scratchpad(space, old, Some("term".into())));
+        assert!(manager.send_to_scratchpad(space, new, Some("term".into())));
+        assert_eq!(manager.scratchpad_window(space, Some("term")), Some(new));
+
+        // The window that lost the name can still be shown.
+        assert!(manager.is_scratchpad_window(old));
+        manager.set_shown_scratchpad_window(space, Some(old));
+        assert!(manager.is_window_in_active_workspace(space, old));
+    }
+
+    #[test]
+    fn scratchpads_are_not_counted_as_workspaces() {
+        let mut manager = VirtualWorkspaceManager::new();
+        let (old_space, new_space) = (SpaceId::new(1), SpaceId::new(2));
+        let term = WindowId::new(1, 1);
+        let ws = manager.list_workspaces(old_space)[0].0;
+        assert!(manager.assign_window_to_workspace(old_space, term, ws));
+        assert!(manager.send_to_scratchpad(old_space, term, None));
+        let workspaces = manager.get_stats().total_workspaces;
+        assert_eq!(workspaces, manager.list_workspaces(old_space).len());
+
+        // The scratchpad made for the target space goes away with the rest of
+        // its workspaces.
+        manager.list_workspaces(new_space);
+        manager.send_to_scratchpad(new_space, WindowId::new(1, 2), None);
+        let before = manager.workspaces.len();
+        manager.remap_space(old_space, new_space);
+        assert_eq!(manager.workspaces.len(), before - workspaces - 1);
+        assert_eq!(manager.scratchpad_window(new_space, None), Some(term));
+        assert_eq!(manager.get_stats().total_workspaces, workspaces);
+    }
+
+    #[test]
+    fn sticky_windows_follow_the_active_workspace() {
+        let mut manager = VirtualWorkspaceManager::new();
+        let space = SpaceId::new(1);
+        let pip = WindowId::new(1, 1);
+        let other = WindowId::new(1, 2);
+        let ids: Vec<_> = manager.list_workspaces(space).into_iter().map(|(id, _)| id).collect();
+        assert!(manager.assign_window_to_workspace(space, pip, ids[0]));
+        assert!(manager.assign_window_to_workspace(space, other, ids[0]));
+        let frame = CGRect::new(CGPoint::new(10.0, 10.0), CGSize::new(200.0, 100.0));
+        manager.store_floating_position(space, ids[0], pip, frame);
+
+        assert!(manager.toggle_sticky(pip));
+        assert!(manager.set_active_workspace(space, ids[2]));
+
+        assert_eq!(manager.workspace_for_window(space, pip), Some(ids[2]));
+        assert_eq!(manager.workspace_for_window(space, other), Some(ids[0]));
+        assert_eq!(manager.get_floating_position(space, ids[2], pip), Some(frame));
+        assert!(!manager.windows_in_inactive_workspaces(space).contains(&pip));
+
+        assert!(!manager.toggle_sticky(pip));
+        assert!(manager.set_active_workspace(space, ids[1]));
+        assert_eq!(manager.workspace_for_window(space, pip), Some(ids[2]));
+    }
+
     #[test]
     fn test_workspace_navigation() {
         let mut manager = VirtualWorkspaceManager::new();
@@ -1386,6 +1781,9 @@ mod tests {
                 title_substring: None,
                 ax_role: None,
                 ax_subrole: None,
+                scratchpad: None,
+                sticky: false,
+                placement: None,
             },
             // Match by app_name -> workspace 1
             AppWorkspaceRule {
@@ -1397,6 +1795,9 @@ mod tests {
                 title_substring: None,
                 ax_role: None,
                 ax_subrole: None,
+                scratchpad: None,
+                sticky: false,
+                placement: None,
             },
             // Title substring -> workspace 0
             AppWorkspaceRule {
@@ -1408,6 +1809,9 @@ mod tests {
                 title_substring: Some("Preferences".into()),
                 ax_role: None,
                 ax_subrole: None,
+                scratchpad: None,
+                sticky: false,
+                placement: None,
             },
             // Title regex -> workspace 2
             AppWorkspaceRule {
@@ -1419,6 +1823,9 @@ mod tests {
                 title_substring: None,
                 ax_role: None,
                 ax_subrole: None,
+                scratchpad: None,
+                sticky: false,
+                placement: None,
             },
             // AX role + subrole floating
             AppWorkspaceRule {
@@ -1430,6 +1837,9 @@ mod tests {
                 title_substring: None,
                 ax_role: Some("AXWindow".into()),
                 ax_subrole: Some("AXDialog".into()),
+                scratchpad: None,
+                sticky: false,
+                placement: None,
             },
             // Workspace by name
             AppWorkspaceRule {
@@ -1441,6 +1851,9 @@ mod tests {
                 title_substring: None,
                 ax_role: None,
                 ax_subrole: None,
+                scratchpad: None,
+                sticky: false,
+                placement: None,
             },
             // Specificity tie breaking generic vs substring (generic workspace 0, specific workspace 2)
             AppWorkspaceRule {
@@ -1452,6 +1865,9 @@ mod tests {
                 title_substring: None,
                 ax_role: None,
                 ax_subrole: None,
+                scratchpad: None,
+                sticky: false,
+                placement: None,
             },
             AppWorkspaceRule {
                 app_id: Some("com.example.tie".into()),
@@ -1462,6 +1878,9 @@ mod tests {
                 title_substring: Some("Editor".into()),
                 ax_role: None,
                 ax_subrole: None,
+                scratchpad: None,
+                sticky: false,
+                placement: None,
             },
             // Reapplication: Bitwarden title becomes floating
             AppWorkspaceRule {
@@ -1473,6 +1892,9 @@ mod tests {
                 title_substring: Some("Bitwarden".into()),
                 ax_role: None,
                 ax_subrole: None,
+                scratchpad: None,
+                sticky: false,
+                placement: None,
             },
             AppWorkspaceRule {
                 app_id: Some("app.zen-browser.zen".into()),
@@ -1483,6 +1905,9 @@ mod tests {
                 title_substring: None,
                 ax_role: None,
                 ax_subrole: None,
+                scratchpad: None,
+                sticky: false,
+                placement: None,
             },
             // Workspace override when specific rule matches different workspace + floating
             AppWorkspaceRule {
@@ -1494,6 +1919,9 @@ mod tests {
                 title_substring: None,
                 ax_role: None,
                 ax_subrole: None,
+                scratchpad: None,
+                sticky: false,
+                placement: None,
             },
             AppWorkspaceRule {
                 app_id: Some("app.zen-browser.zen".into()),
@@ -1504,6 +1932,9 @@ mod tests {
                 title_substring: Some("bitwarden".into()),
                 ax_role: None,
                 ax_subrole: None,
+                scratchpad: None,
+                sticky: false,
+                placement: None,
             },
         ];
 
@@ -1,3 +1,4 @@
+use nix::libc;
 use objc2_core_graphics::CGError;
 
 use super::app::pid_t;
@@ -21,6 +22,31 @@ impl ProcessInfo {
     }
 }
 
+/// Returns the parent of `pid`, or `None` if the process is gone or cannot be
+/// inspected.
+pub fn parent_pid(pid: pid_t) -> Option<pid_t> {
+    let mut info: libc::proc_bsdinfo = unsafe { std::mem::zeroed() };
+    let size = size_of::<libc::proc_bsdinfo>() as libc::c_int;
+    let written = unsafe {
+        libc::proc_pidinfo(
+            pid,
+            libc::PROC_PIDTBSDINFO,
+            0,
+            &mut info as *mut libc::proc_bsdinfo as *mut libc::c_void,
+            size,
+        )
+    };
+    (written == size).then_some(info.pbi_ppid as pid_t)
+}
+
+/// Walks the parent chain of `pid`, nearest ancestor first. Stops before
+/// launchd, which every process eventually descends from.
+pub fn ancestors(pid: pid_t) -> impl Iterator<Item = pid_t> {
+    std::iter::successors(parent_pid(pid), |&pid| parent_pid(pid))
+        .take_while(|&pid| pid > 1)
+        .take(64)
+}
+
 type FourCharCode = u32;
 type OSType = FourCharCode;
 